@@ -3,15 +3,26 @@ const RISCV_REGISTER_COUNT: u64 = 32;
 const VIRTUAL_REGISTER_COUNT: u64 = 32; //  see Section 6.1 of Jolt paper
 pub const REGISTER_COUNT: u64 = RISCV_REGISTER_COUNT + VIRTUAL_REGISTER_COUNT;
 pub const BYTES_PER_INSTRUCTION: usize = 4;
+/// Instructions are 2-byte aligned, as RVC instructions are 2 bytes long.
+pub const INSTRUCTION_ALIGNMENT: usize = 2;
 pub const REG_OPS_PER_INSTRUCTION: usize = 3;
 pub const RAM_OPS_PER_INSTRUCTION: usize = 4;
 pub const MEMORY_OPS_PER_INSTRUCTION: usize = REG_OPS_PER_INSTRUCTION + RAM_OPS_PER_INSTRUCTION;
 
 pub const RAM_START_ADDRESS: u64 = 0x80000000;
+/// Guest debug console, the last byte of the I/O region. Bytes written to it are forwarded
+/// to the host during tracing but aren't part of the program's public I/O. Guests write 0 to
+/// end a message, which also leaves the console zeroed like the rest of the unused I/O region.
+pub const CONSOLE_ADDRESS: u64 = RAM_START_ADDRESS - 1;
 pub const DEFAULT_MEMORY_SIZE: u64 = 10 * 1024 * 1024;
 pub const DEFAULT_STACK_SIZE: u64 = 4096;
 pub const DEFAULT_MAX_INPUT_SIZE: u64 = 4096;
 pub const DEFAULT_MAX_OUTPUT_SIZE: u64 = 4096;
+pub const DEFAULT_MAX_BYTECODE_SIZE: u64 = 1 << 20;
+pub const DEFAULT_MAX_MEMORY_ADDRESS: u64 = 1 << 20;
+pub const DEFAULT_MAX_TRACE_LENGTH: u64 = 1 << 24;
+/// Guests still running after this many cycles are assumed not to terminate.
+pub const DEFAULT_MAX_CYCLES: u64 = 1 << 28;
 
 pub const fn memory_address_to_witness_index(address: u64, ram_witness_offset: u64) -> usize {
     (address + ram_witness_offset - RAM_START_ADDRESS) as usize
@@ -24,8 +35,8 @@ pub const fn virtual_register_index(index: u64) -> u64 {
 }
 
 // Layout of the witness (where || denotes concatenation):
-//     registers || inputs || outputs || panic || padding || RAM
+//     registers || inputs || outputs || panic || termination || padding || console || RAM
 // Layout of VM memory:
-//     peripheral devices || inputs || outputs || panic || padding || RAM
+//     peripheral devices || inputs || outputs || panic || termination || padding || console || RAM
 // Notably, we want to be able to map the VM memory address space to witness indices
 // using a constant shift, namely (RAM_WITNESS_OFFSET + RAM_START_ADDRESS)
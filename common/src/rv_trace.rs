@@ -1,6 +1,8 @@
 use std::str::FromStr;
 
-use crate::constants::{MEMORY_OPS_PER_INSTRUCTION, RAM_START_ADDRESS, REGISTER_COUNT};
+use crate::constants::{
+    CONSOLE_ADDRESS, MEMORY_OPS_PER_INSTRUCTION, RAM_START_ADDRESS, REGISTER_COUNT,
+};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use serde::{Deserialize, Serialize};
 use strum_macros::FromRepr;
@@ -223,11 +225,39 @@ pub struct ELFInstruction {
     /// If this instruction is part of a "virtual sequence" (see Section 6.2 of the
     /// Jolt paper), then this contains the instruction's index within the sequence.
     pub virtual_sequence_index: Option<usize>,
+    /// If this instruction is part of a virtual sequence, then this contains the number of
+    /// instructions that follow it in the sequence.
+    pub virtual_sequence_remaining: Option<usize>,
+    /// Whether the instruction is encoded in 16 bits (RVC). Compressed instructions are
+    /// expanded to their 32-bit equivalents, so this only determines the instruction's size.
+    pub is_compressed: bool,
 }
 
-pub const NUM_CIRCUIT_FLAGS: usize = 11;
+pub const NUM_CIRCUIT_FLAGS: usize = 13;
 
 impl ELFInstruction {
+    /// Size of the instruction's encoding in bytes, i.e. the offset of the next instruction.
+    pub fn size(&self) -> u64 {
+        if self.is_compressed {
+            2
+        } else {
+            4
+        }
+    }
+
+    /// Custom instructions are R-type, with `imm` holding `funct7 << 3 | funct3`. Returns
+    /// `(funct3, funct7)` for `CUSTOM_0`/`CUSTOM_1` instructions, so that an instruction set
+    /// can map several instructions onto the same custom opcode.
+    pub fn custom_funct(&self) -> Option<(u32, u32)> {
+        match self.opcode {
+            RV32IM::CUSTOM_0 | RV32IM::CUSTOM_1 => {
+                let funct = self.imm.unwrap_or(0);
+                Some((funct & 0b111, funct >> 3))
+            }
+            _ => None,
+        }
+    }
+
     #[rustfmt::skip]
     pub fn to_circuit_flags(&self) -> [bool; NUM_CIRCUIT_FLAGS] {
         // Jolt Appendix A.1
@@ -240,8 +270,10 @@ impl ELFInstruction {
         // 6: Instruction writes lookup output to rd
         // 7: Sign-bit of imm
         // 8: Is concat
-        // 9: Increment virtual PC
+        // 9: Do not update PC (all but the last instruction of a virtual sequence)
         // 10: Assert instruction
+        // 11: Compressed (16-bit) instruction
+        // 12: Continues a virtual sequence, i.e. follows an instruction with flag 9 set
 
         let mut flags = [false; NUM_CIRCUIT_FLAGS];
 
@@ -306,6 +338,8 @@ impl ELFInstruction {
         let mask = 1u32 << 31;
         flags[7] = matches!(self.imm, Some(imm) if imm & mask == mask);
 
+        // Custom instructions are always concat queries, i.e. their lookup index interleaves
+        // the chunks of both operands.
         flags[8] = matches!(
             self.opcode,
             RV32IM::XOR
@@ -329,35 +363,47 @@ impl ELFInstruction {
             | RV32IM::BLT
             | RV32IM::BGE
             | RV32IM::BLTU
-            | RV32IM::BGEU,
+            | RV32IM::BGEU
+            | RV32IM::CUSTOM_0
+            | RV32IM::CUSTOM_1,
         );
 
-        // TODO(moodlezoup): Use these flags in R1CS constraints
-        flags[9] = match self.virtual_sequence_index {
-            // For virtual sequences, we set
-            //     virtual PC := ProgARW     (the bytecode `a` value)
-            // if it's the first instruction in the sequence.
-            // Otherwise, we increment the virtual PC:
-            //     virtual PC += 1
-            // This prevents a malicious prover from reordering or omitting
-            // instructions from the virtual sequence.
-            Some(i) => i > 0,
-            // For "real" instructions, we always set
-            //     virtual PC := ProgARW     (the bytecode `a` value)
-            None => false
-        };
+        // The PC stays at the address of a virtual sequence until its last instruction, which
+        // moves on to the next instruction like the one it replaces would.
+        flags[9] = matches!(self.virtual_sequence_remaining, Some(remaining) if remaining > 0);
         flags[10] = matches!(self.opcode,
             RV32IM::VIRTUAL_ASSERT_EQ     |
             RV32IM::VIRTUAL_ASSERT_LTE    |
             RV32IM::VIRTUAL_ASSERT_LTU    |
             RV32IM::VIRTUAL_ASSERT_LT_ABS |
-            RV32IM::VIRTUAL_ASSERT_EQ_SIGNS,
+            RV32IM::VIRTUAL_ASSERT_EQ_SIGNS |
+            RV32IM::VIRTUAL_ASSERT_VALID_UNSIGNED_REMAINDER |
+            RV32IM::VIRTUAL_ASSERT_VALID_DIV0,
         );
 
+        flags[11] = self.is_compressed;
+
+        // Flag 12 depends on the preceding instruction rather than on this one, so it is left
+        // unset here and set when the instructions of a sequence are put in the bytecode.
+
         flags
     }
 }
 
+/// A function symbol from the guest ELF's symbol table, with its name demangled.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ELFSymbol {
+    pub name: String,
+    pub address: u64,
+    pub size: u64,
+}
+
+impl ELFSymbol {
+    pub fn contains(&self, address: u64) -> bool {
+        address >= self.address && address < self.address + self.size
+    }
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct RegisterState {
     pub rs1_val: Option<u64>,
@@ -439,8 +485,24 @@ pub enum RV32IM {
     DIVU,
     REM,
     REMU,
+    // RV32A instructions, expanded into loads, stores and ALU instructions (single hart)
+    LR_W,
+    SC_W,
+    AMOSWAP_W,
+    AMOADD_W,
+    AMOXOR_W,
+    AMOAND_W,
+    AMOOR_W,
+    AMOMIN_W,
+    AMOMAX_W,
+    AMOMINU_W,
+    AMOMAXU_W,
     FENCE,
     UNIMPL,
+    // Custom instructions (RISC-V custom-0 and custom-1 major opcodes), whose semantics are
+    // given by the `JoltInstructionSet` the program is proven with. See `ELFInstruction::custom_funct`.
+    CUSTOM_0,
+    CUSTOM_1,
     // Virtual instructions
     VIRTUAL_MOVSIGN,
     VIRTUAL_ADVICE,
@@ -449,6 +511,8 @@ pub enum RV32IM {
     VIRTUAL_ASSERT_LT_ABS,
     VIRTUAL_ASSERT_EQ_SIGNS,
     VIRTUAL_ASSERT_EQ,
+    VIRTUAL_ASSERT_VALID_UNSIGNED_REMAINDER,
+    VIRTUAL_ASSERT_VALID_DIV0,
 }
 
 impl FromStr for RV32IM {
@@ -504,8 +568,21 @@ impl FromStr for RV32IM {
             "DIVU" => Ok(Self::DIVU),
             "REM" => Ok(Self::REM),
             "REMU" => Ok(Self::REMU),
+            "LR.W" => Ok(Self::LR_W),
+            "SC.W" => Ok(Self::SC_W),
+            "AMOSWAP.W" => Ok(Self::AMOSWAP_W),
+            "AMOADD.W" => Ok(Self::AMOADD_W),
+            "AMOXOR.W" => Ok(Self::AMOXOR_W),
+            "AMOAND.W" => Ok(Self::AMOAND_W),
+            "AMOOR.W" => Ok(Self::AMOOR_W),
+            "AMOMIN.W" => Ok(Self::AMOMIN_W),
+            "AMOMAX.W" => Ok(Self::AMOMAX_W),
+            "AMOMINU.W" => Ok(Self::AMOMINU_W),
+            "AMOMAXU.W" => Ok(Self::AMOMAXU_W),
             "FENCE" => Ok(Self::FENCE),
             "UNIMPL" => Ok(Self::UNIMPL),
+            "CUSTOM_0" => Ok(Self::CUSTOM_0),
+            "CUSTOM_1" => Ok(Self::CUSTOM_1),
             _ => Err("Could not match instruction to RV32IM set.".to_string()),
         }
     }
@@ -543,7 +620,20 @@ impl RV32IM {
             RV32IM::DIV    |
             RV32IM::DIVU   |
             RV32IM::REM    |
-            RV32IM::REMU => RV32InstructionFormat::R,
+            RV32IM::REMU   |
+            RV32IM::LR_W |
+            RV32IM::SC_W |
+            RV32IM::AMOSWAP_W |
+            RV32IM::AMOADD_W |
+            RV32IM::AMOXOR_W |
+            RV32IM::AMOAND_W |
+            RV32IM::AMOOR_W |
+            RV32IM::AMOMIN_W |
+            RV32IM::AMOMAX_W |
+            RV32IM::AMOMINU_W |
+            RV32IM::AMOMAXU_W |
+            RV32IM::CUSTOM_0 |
+            RV32IM::CUSTOM_1 => RV32InstructionFormat::R,
 
             RV32IM::ADDI  |
             RV32IM::XORI  |
@@ -578,7 +668,9 @@ impl RV32IM {
             RV32IM::VIRTUAL_ASSERT_LTE    |
             RV32IM::VIRTUAL_ASSERT_LTU    |
             RV32IM::VIRTUAL_ASSERT_LT_ABS |
-            RV32IM::VIRTUAL_ASSERT_EQ_SIGNS => RV32InstructionFormat::SB,
+            RV32IM::VIRTUAL_ASSERT_EQ_SIGNS |
+            RV32IM::VIRTUAL_ASSERT_VALID_UNSIGNED_REMAINDER |
+            RV32IM::VIRTUAL_ASSERT_VALID_DIV0 => RV32InstructionFormat::SB,
 
             RV32IM::LUI   |
             RV32IM::AUIPC |
@@ -597,6 +689,8 @@ impl RV32IM {
 /// all reads from the reserved memory address space for program inputs and all writes
 /// to the reserved memory address space for program outputs.
 /// The inputs and outputs are part of the public inputs to the proof.
+/// The guest signals that it has halted by writing to the reserved termination address
+/// (or the panic address, if it panicked).
 #[derive(
     Debug, Clone, PartialEq, Serialize, Deserialize, CanonicalSerialize, CanonicalDeserialize,
 )]
@@ -604,6 +698,7 @@ pub struct JoltDevice {
     pub inputs: Vec<u8>,
     pub outputs: Vec<u8>,
     pub panic: bool,
+    pub termination: bool,
     pub memory_layout: MemoryLayout,
 }
 
@@ -613,6 +708,7 @@ impl JoltDevice {
             inputs: Vec::new(),
             outputs: Vec::new(),
             panic: false,
+            termination: false,
             memory_layout: MemoryLayout::new(max_input_size, max_output_size),
         }
     }
@@ -628,11 +724,15 @@ impl JoltDevice {
 
     pub fn store(&mut self, address: u64, value: u8) {
         if address == self.memory_layout.panic {
-            println!("GUEST PANIC");
             self.panic = true;
             return;
         }
 
+        if address == self.memory_layout.termination {
+            self.termination = true;
+            return;
+        }
+
         let internal_address = self.convert_write_address(address);
         if self.outputs.len() <= internal_address {
             self.outputs.resize(internal_address + 1, 0);
@@ -657,6 +757,21 @@ impl JoltDevice {
         address == self.memory_layout.panic
     }
 
+    pub fn is_termination(&self, address: u64) -> bool {
+        address == self.memory_layout.termination
+    }
+
+    /// Writes to the debug console aren't recorded by the device, see `CONSOLE_ADDRESS`
+    pub fn is_console(&self, address: u64) -> bool {
+        address == CONSOLE_ADDRESS
+    }
+
+    /// Whether the guest has signaled that it halted, either by writing to the
+    /// termination address or by panicking.
+    pub fn halted(&self) -> bool {
+        self.termination || self.panic
+    }
+
     fn convert_read_address(&self, address: u64) -> usize {
         (address - self.memory_layout.input_start) as usize
     }
@@ -678,6 +793,7 @@ pub struct MemoryLayout {
     pub output_start: u64,
     pub output_end: u64,
     pub panic: u64,
+    pub termination: u64,
 }
 
 impl MemoryLayout {
@@ -691,12 +807,14 @@ impl MemoryLayout {
             output_start: output_start(max_input_size, max_output_size),
             output_end: output_end(max_input_size, max_output_size),
             panic: panic_address(max_input_size, max_output_size),
+            termination: termination_address(max_input_size, max_output_size),
         }
     }
 }
 
 pub fn ram_witness_offset(max_input: u64, max_output: u64) -> u64 {
-    (REGISTER_COUNT + max_input + max_output + 1).next_power_of_two()
+    // registers || inputs || 1 || outputs || 1 || panic || termination || console
+    (REGISTER_COUNT + max_input + max_output + 5).next_power_of_two()
 }
 
 fn input_start(max_input: u64, max_output: u64) -> u64 {
@@ -718,3 +836,7 @@ fn output_end(max_input: u64, max_output: u64) -> u64 {
 fn panic_address(max_input: u64, max_output: u64) -> u64 {
     output_end(max_input, max_output) + 1
 }
+
+fn termination_address(max_input: u64, max_output: u64) -> u64 {
+    panic_address(max_input, max_output) + 1
+}
@@ -0,0 +1,13 @@
+#[test]
+fn verify_checks_expected_output() {
+    let (program, prover_key) = guest::preprocess_fib();
+    let verifier_key = prover_key.verifier_key();
+
+    let (output, proof) = guest::prove_fib(program.clone(), &prover_key, 10);
+    assert_eq!(output, 55);
+    assert!(guest::verify_fib(&verifier_key, proof, 10, output).unwrap());
+
+    // A valid proof doesn't attest to an output the program didn't return
+    let (_, proof) = guest::prove_fib(program, &prover_key, 10);
+    assert!(!guest::verify_fib(&verifier_key, proof, 10, output + 1).unwrap());
+}
@@ -82,17 +82,6 @@ where
     prove_example::<Vec<u8>, PCS, F>("sha3-guest", &vec![5u8; 2048])
 }
 
-#[allow(dead_code)]
-fn serialize_and_print_size(name: &str, item: &impl ark_serialize::CanonicalSerialize) {
-    use std::fs::File;
-    let mut file = File::create("temp_file").unwrap();
-    item.serialize_compressed(&mut file).unwrap();
-    let file_size_bytes = file.metadata().unwrap().len();
-    let file_size_kb = file_size_bytes as f64 / 1024.0;
-    let file_size_mb = file_size_kb / 1024.0;
-    println!("{:<30} : {:.3} MB", name, file_size_mb);
-}
-
 fn prove_example<T: Serialize, PCS, F>(
     example_name: &str,
     input: &T,
@@ -106,28 +95,27 @@ where
     program.set_input(input);
 
     let task = move || {
-        let (bytecode, memory_init) = program.decode();
-        let (io_device, trace, circuit_flags) = program.trace();
-
-        let preprocessing: crate::jolt::vm::JoltPreprocessing<F, PCS> =
-            RV32IJoltVM::preprocess(bytecode.clone(), memory_init, 1 << 20, 1 << 20, 1 << 22);
+        let (bytecode, memory_init) = program.decode().unwrap();
+        let (io_device, trace, circuit_flags) = program.trace().unwrap();
+
+        let preprocessing: crate::jolt::vm::JoltProverKey<F, PCS> = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 20,
+            1 << 20,
+            1 << 22,
+        );
 
         let (jolt_proof, jolt_commitments) = <RV32IJoltVM as Jolt<_, PCS, C, M>>::prove(
             io_device,
             trace,
             circuit_flags,
-            preprocessing.clone(),
+            &preprocessing,
         );
 
-        // println!("Proof sizing:");
-        // serialize_and_print_size("jolt_commitments", &jolt_commitments);
-        // serialize_and_print_size("jolt_proof", &jolt_proof);
-        // serialize_and_print_size(" jolt_proof.r1cs", &jolt_proof.r1cs);
-        // serialize_and_print_size(" jolt_proof.bytecode", &jolt_proof.bytecode);
-        // serialize_and_print_size(" jolt_proof.read_write_memory", &jolt_proof.read_write_memory);
-        // serialize_and_print_size(" jolt_proof.instruction_lookups", &jolt_proof.instruction_lookups);
-
-        let verification_result = RV32IJoltVM::verify(preprocessing, jolt_proof, jolt_commitments);
+        let verification_result =
+            RV32IJoltVM::verify(&preprocessing.verifier_key(), jolt_proof, jolt_commitments);
         assert!(
             verification_result.is_ok(),
             "Verification failed with error: {:?}",
@@ -154,19 +142,26 @@ where
     program.set_input(&1024u32);
 
     let task = move || {
-        let (bytecode, memory_init) = program.decode();
-        let (io_device, trace, circuit_flags) = program.trace();
-
-        let preprocessing: crate::jolt::vm::JoltPreprocessing<F, PCS> =
-            RV32IJoltVM::preprocess(bytecode.clone(), memory_init, 1 << 20, 1 << 20, 1 << 22);
+        let (bytecode, memory_init) = program.decode().unwrap();
+        let (io_device, trace, circuit_flags) = program.trace().unwrap();
+
+        let preprocessing: crate::jolt::vm::JoltProverKey<F, PCS> = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 20,
+            1 << 20,
+            1 << 22,
+        );
 
         let (jolt_proof, jolt_commitments) = <RV32IJoltVM as Jolt<_, PCS, C, M>>::prove(
             io_device,
             trace,
             circuit_flags,
-            preprocessing.clone(),
+            &preprocessing,
         );
-        let verification_result = RV32IJoltVM::verify(preprocessing, jolt_proof, jolt_commitments);
+        let verification_result =
+            RV32IJoltVM::verify(&preprocessing.verifier_key(), jolt_proof, jolt_commitments);
         assert!(
             verification_result.is_ok(),
             "Verification failed with error: {:?}",
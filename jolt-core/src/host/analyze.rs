@@ -1,19 +1,31 @@
-use std::{collections::HashMap, fs::File, io, path::PathBuf};
+use std::{collections::HashMap, fmt, fs::File, io, path::PathBuf};
 
+use ark_bn254::{Fr, G1Projective};
+use common::constants::RAM_START_ADDRESS;
 use serde::{Deserialize, Serialize};
-use tracer::{ELFInstruction, JoltDevice, RVTraceRow, RV32IM};
+use tracer::{ELFInstruction, ELFSymbol, JoltDevice, RVTraceRow, RV32IM};
 
 use crate::{
     field::JoltField,
-    jolt::vm::{rv32i_vm::RV32I, JoltTraceStep},
+    jolt::vm::{
+        instruction_lookups::InstructionLookupsPreprocessing,
+        read_write_memory::memory_size,
+        rv32i_vm::{RV32IJoltVM, RV32ISubtables, C, M, RV32I},
+        Jolt, JoltTraceStep,
+    },
+    poly::commitment::{commitment_scheme::CommitShape, hyrax::HyraxScheme},
+    r1cs::constraints::R1CSBuilder,
 };
 
+use super::profile::Profile;
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ProgramSummary {
     pub raw_trace: Vec<RVTraceRow>,
 
     pub bytecode: Vec<ELFInstruction>,
     pub memory_init: Vec<(u64, u8)>,
+    pub symbols: Vec<ELFSymbol>,
 
     pub io_device: JoltDevice,
     pub processed_trace: Vec<JoltTraceStep<RV32I>>,
@@ -43,6 +55,57 @@ impl ProgramSummary {
         counts
     }
 
+    /// Attributes the trace to the guest's functions; see [`Profile`].
+    pub fn profile(&self) -> Profile {
+        Profile::new::<RV32I>(&self.symbols, &self.raw_trace)
+    }
+
+    /// Summarizes the trace and the commitments needed to prove it. The commitment shapes are
+    /// those `Jolt::preprocess` requests for the smallest bounds that fit this program, which
+    /// are the same for every commitment scheme.
+    ///
+    /// `throughput` is the number of witness field elements the prover gets through per
+    /// second and core on the target machine; without it, the proving time isn't estimated.
+    /// It can be calibrated by proving a program and dividing the witness size of its report
+    /// by the proving time and the number of threads.
+    pub fn report<F: JoltField>(&self, throughput: Option<f64>) -> ProgramReport {
+        let raw_trace_length = self.raw_trace.len();
+        let trace_length = self.processed_trace.len();
+        let padded_trace_length = trace_length.next_power_of_two();
+        let bytecode_size = self.bytecode.len();
+        let memory_size = memory_size(&self.io_device.memory_layout, &self.processed_trace);
+
+        // Any field and scheme will do, the shapes are the same for all of them
+        let instruction_lookups_preprocessing =
+            InstructionLookupsPreprocessing::preprocess::<C, M, RV32I, RV32ISubtables<Fr>>();
+        let commit_shapes =
+            <RV32IJoltVM as Jolt<Fr, HyraxScheme<G1Projective>, C, M>>::commitment_shapes(
+                &instruction_lookups_preprocessing,
+                bytecode_size,
+                memory_size,
+                padded_trace_length,
+            );
+        let max_commit_length = commit_shapes
+            .iter()
+            .map(|shape| shape.input_length)
+            .max()
+            .unwrap_or(0);
+        let estimate = ProverEstimate::new::<F>(&commit_shapes, padded_trace_length, throughput);
+
+        ProgramReport {
+            instruction_counts: self.analyze::<F>(),
+            raw_trace_length,
+            trace_length,
+            padded_trace_length,
+            bytecode_size,
+            memory_init_size: self.memory_init.len(),
+            memory_size,
+            commit_shapes,
+            max_commit_length,
+            estimate,
+        }
+    }
+
     pub fn write_to_file(self, path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
         let mut file = File::create(path)?;
         let data = bincode::serialize(&self)?;
@@ -50,3 +113,167 @@ impl ProgramSummary {
         Ok(())
     }
 }
+
+/// Statistics of a program's execution, and an estimate of the cost of proving it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProgramReport {
+    /// Executed RISC-V instructions by opcode, most frequent first.
+    pub instruction_counts: Vec<(RV32IM, usize)>,
+    /// Number of RISC-V instructions executed.
+    pub raw_trace_length: usize,
+    /// Number of Jolt trace steps, i.e. after virtual sequences have been expanded.
+    pub trace_length: usize,
+    /// Trace length after padding to a power of two, as proven.
+    pub padded_trace_length: usize,
+    /// Number of instructions in the program's bytecode.
+    pub bytecode_size: usize,
+    /// Bytes of memory initialized from the ELF (code and data sections).
+    pub memory_init_size: usize,
+    /// Memory cells (registers, program I/O and RAM) covered by read-write memory checking.
+    pub memory_size: usize,
+    /// Shapes of the committed polynomials, which determine the commitment scheme's setup.
+    pub commit_shapes: Vec<CommitShape>,
+    /// Length of the largest committed polynomial. The setup grows linearly with it for
+    /// KZG-based schemes, and with its square root for Hyrax.
+    pub max_commit_length: usize,
+    /// Rough estimate of the prover's memory and time.
+    pub estimate: ProverEstimate,
+}
+
+/// Rough estimate of the resources needed to prove a program, derived from the sizes of the
+/// polynomials the prover builds.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProverEstimate {
+    /// Field elements in the committed polynomials and the R1CS witness.
+    pub witness_size: usize,
+    /// Bytes taken by the witness, which dominates the prover's peak memory. Sumchecks
+    /// allocate a few more polynomials of the trace length, so expect somewhat more.
+    pub memory: usize,
+    /// Number of threads the prover is assumed to run on, i.e. rayon's thread pool size.
+    pub num_threads: usize,
+    /// Proving time in seconds, assuming the prover scales linearly with `num_threads`. `None`
+    /// if no throughput was given.
+    pub time_secs: Option<f64>,
+}
+
+impl ProverEstimate {
+    fn new<F: JoltField>(
+        commit_shapes: &[CommitShape],
+        padded_trace_length: usize,
+        throughput: Option<f64>,
+    ) -> Self {
+        let committed_size: usize = commit_shapes
+            .iter()
+            .map(|shape| shape.num_polys * shape.input_length)
+            .sum();
+
+        // R1CS: witness variables, and Az, Bz, Cz of each constraint
+        let mut r1cs = R1CSBuilder::<RV32I>::default();
+        R1CSBuilder::jolt_r1cs_matrices(&mut r1cs, RAM_START_ADDRESS);
+        let r1cs_size = (r1cs.num_aux + 3 * (r1cs.num_constraints + 1)) * padded_trace_length;
+
+        let witness_size = committed_size + r1cs_size;
+        let num_threads = rayon::current_num_threads();
+        Self {
+            witness_size,
+            memory: witness_size * std::mem::size_of::<F>(),
+            num_threads,
+            time_secs: throughput
+                .map(|throughput| witness_size as f64 / (throughput * num_threads as f64)),
+        }
+    }
+}
+
+impl fmt::Display for ProgramReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Raw trace length:        {}", self.raw_trace_length)?;
+        writeln!(f, "Trace length:            {}", self.trace_length)?;
+        writeln!(f, "Padded trace length:     {}", self.padded_trace_length)?;
+        writeln!(f, "Bytecode size:           {}", self.bytecode_size)?;
+        writeln!(
+            f,
+            "Initialized memory:      {} bytes",
+            self.memory_init_size
+        )?;
+        writeln!(f, "Memory size:             {}", self.memory_size)?;
+        writeln!(f, "Max commitment length:   {}", self.max_commit_length)?;
+
+        writeln!(f, "\nProver estimate (rough):")?;
+        writeln!(f, "  Witness size:          {}", self.estimate.witness_size)?;
+        writeln!(
+            f,
+            "  Memory:                {:.1} MiB",
+            self.estimate.memory as f64 / (1 << 20) as f64
+        )?;
+        if let Some(time_secs) = self.estimate.time_secs {
+            writeln!(
+                f,
+                "  Time:                  {:.1} s on {} threads",
+                time_secs, self.estimate.num_threads
+            )?;
+        }
+
+        writeln!(f, "\nCommitment shapes:")?;
+        for shape in self.commit_shapes.iter() {
+            writeln!(
+                f,
+                "  {:<16} {} x {}",
+                format!("{:?}", shape.batch_type),
+                shape.num_polys,
+                shape.input_length
+            )?;
+        }
+
+        writeln!(f, "\nInstruction counts:")?;
+        for (opcode, count) in self.instruction_counts.iter() {
+            writeln!(f, "  {:<16} {}", format!("{:?}", opcode), count)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tracer::test_utils::{addi, terminate};
+
+    use crate::host::{test_program, Program};
+
+    use super::*;
+
+    #[test]
+    fn report() {
+        let termination = Program::new("analyze-report").memory_layout().termination;
+        let code = [
+            vec![addi(5, 0, 1), addi(5, 5, 2), addi(5, 5, 3)],
+            terminate(termination),
+        ]
+        .concat();
+        let program = test_program("analyze-report", &code);
+
+        let summary = program.trace_analyze::<Fr>().unwrap();
+        let report = summary.report::<Fr>(Some(1e6));
+
+        assert_eq!(report.raw_trace_length, 6);
+        assert_eq!(report.trace_length, 6);
+        assert_eq!(report.padded_trace_length, 8);
+        assert_eq!(report.bytecode_size, 6);
+        assert_eq!(report.memory_init_size, 24);
+        // LUI and SB are tied, so their order is unspecified
+        assert_eq!(report.instruction_counts.len(), 3);
+        assert_eq!(report.instruction_counts[0], (RV32IM::ADDI, 4));
+
+        let estimate = &report.estimate;
+        assert_eq!(estimate.memory, estimate.witness_size * 32);
+        assert_eq!(
+            estimate.time_secs,
+            Some(estimate.witness_size as f64 / (1e6 * estimate.num_threads as f64))
+        );
+        assert_eq!(summary.report::<Fr>(None).estimate.time_secs, None);
+        // Dominated by the instruction lookups' final counts for a trace this short
+        assert!(estimate.witness_size > 8 * M);
+
+        let printed = report.to_string();
+        assert!(printed.contains("Padded trace length:     8"));
+        assert!(printed.contains("Prover estimate (rough):"));
+    }
+}
@@ -1,11 +1,11 @@
 #![allow(clippy::type_complexity)]
 
-use core::str::FromStr;
 use std::{
     fs::{self, File},
-    io::{self, Write},
+    io::Write,
     path::PathBuf,
     process::Command,
+    sync::Arc,
 };
 
 use postcard;
@@ -14,29 +14,34 @@ use serde::Serialize;
 
 use common::{
     constants::{
-        DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE, DEFAULT_MEMORY_SIZE, DEFAULT_STACK_SIZE,
+        DEFAULT_MAX_CYCLES, DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE, DEFAULT_MEMORY_SIZE,
+        DEFAULT_STACK_SIZE,
     },
-    rv_trace::{JoltDevice, NUM_CIRCUIT_FLAGS},
+    rv_trace::{JoltDevice, MemoryLayout},
 };
-use strum::EnumCount;
-use tracer::ELFInstruction;
+use tracer::{ELFInstruction, ELFSymbol, RVTraceRow};
 
 use crate::{
     field::JoltField,
     jolt::{
-        instruction::{
-            mulh::MULHInstruction, mulhsu::MULHSUInstruction, VirtualInstructionSequence,
+        instruction::JoltInstructionSet,
+        vm::{
+            bytecode::BytecodeRow, read_write_memory::max_memory_size, rv32i_vm::RV32I,
+            JoltTraceStep,
         },
-        vm::{bytecode::BytecodeRow, rv32i_vm::RV32I, JoltTraceStep},
     },
-    utils::thread::unsafe_allocate_zero_vec,
+    utils::errors::HostError,
 };
 
 use self::{analyze::ProgramSummary, toolchain::install_toolchain};
 
 pub mod analyze;
+pub mod profile;
 pub mod toolchain;
 
+/// Receives the messages a guest writes to its debug console while it is traced.
+pub type ConsoleHandler = Arc<dyn Fn(&[u8]) + Send + Sync>;
+
 #[derive(Clone)]
 pub struct Program {
     guest: String,
@@ -46,7 +51,9 @@ pub struct Program {
     stack_size: u64,
     max_input_size: u64,
     max_output_size: u64,
+    max_cycles: u64,
     std: bool,
+    console: Option<ConsoleHandler>,
     pub elf: Option<PathBuf>,
 }
 
@@ -60,7 +67,9 @@ impl Program {
             stack_size: DEFAULT_STACK_SIZE,
             max_input_size: DEFAULT_MAX_INPUT_SIZE,
             max_output_size: DEFAULT_MAX_OUTPUT_SIZE,
+            max_cycles: DEFAULT_MAX_CYCLES,
             std: false,
+            console: None,
             elf: None,
         }
     }
@@ -78,6 +87,11 @@ impl Program {
         self.input.append(&mut serialized);
     }
 
+    /// Appends input bytes that are already serialized with `postcard`.
+    pub fn set_input_bytes(&mut self, input: &[u8]) {
+        self.input.extend_from_slice(input);
+    }
+
     pub fn set_memory_size(&mut self, len: u64) {
         self.memory_size = len;
     }
@@ -94,17 +108,49 @@ impl Program {
         self.max_output_size = size;
     }
 
+    /// Bounds how many cycles the guest may run before tracing gives up on it.
+    pub fn set_max_cycles(&mut self, cycles: u64) {
+        self.max_cycles = cycles;
+    }
+
+    /// Forwards the guest's debug console to `console` instead of stdout.
+    pub fn set_console(&mut self, console: impl Fn(&[u8]) + Send + Sync + 'static) {
+        self.console = Some(Arc::new(console));
+    }
+
+    fn print_console(&self, message: &[u8]) {
+        match &self.console {
+            Some(console) => console(message),
+            None => {
+                let mut stdout = std::io::stdout().lock();
+                let _ = stdout.write_all(message);
+                let _ = stdout.flush();
+            }
+        }
+    }
+
+    /// Layout of the guest's I/O region, as determined by the maximum input and output sizes.
+    pub fn memory_layout(&self) -> MemoryLayout {
+        MemoryLayout::new(self.max_input_size, self.max_output_size)
+    }
+
+    /// Tight `max_memory_address` preprocessing parameter for this program, assuming the
+    /// guest stays within its `memory_size` bytes of RAM.
+    pub fn max_memory_address(&self) -> usize {
+        max_memory_size(&self.memory_layout(), self.memory_size)
+    }
+
     #[tracing::instrument(skip_all, name = "Program::build")]
-    pub fn build(&mut self) {
+    pub fn build(&mut self) -> Result<(), HostError> {
         if self.elf.is_none() {
-            install_toolchain().unwrap();
-            self.save_linker();
+            install_toolchain().map_err(toolchain::host_error)?;
+            self.save_linker()?;
 
             let rust_flags = [
                 "-C",
                 &format!("link-arg=-T{}", self.linker_path()),
                 "-C",
-                "passes=loweratomic",
+                "target-feature=+a,+c",
                 "-C",
                 "panic=abort",
             ];
@@ -137,116 +183,164 @@ impl Program {
                     "--target-dir",
                     &target,
                     "--target",
-                    toolchain
+                    toolchain,
                 ])
-                .output()
-                .expect("failed to build guest");
+                .output()?;
 
             if !output.status.success() {
-                io::stderr().write_all(&output.stderr).unwrap();
-                panic!("failed to compile guest");
+                return Err(HostError::CompileFailed {
+                    guest: self.guest.clone(),
+                    stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+                });
             }
 
             let elf = format!("{}/{}/release/guest", target, toolchain);
-            self.elf = Some(PathBuf::from_str(&elf).unwrap());
+            self.elf = Some(PathBuf::from(elf));
         }
+        Ok(())
     }
 
-    pub fn decode(&mut self) -> (Vec<ELFInstruction>, Vec<(u64, u8)>) {
-        self.build();
+    pub fn decode(&mut self) -> Result<tracer::DecodedElf, HostError> {
+        self.build()?;
         let elf = self.elf.as_ref().unwrap();
-        tracer::decode(elf)
+        Ok(tracer::decode(elf)?)
     }
 
-    // TODO(moodlezoup): Make this generic over InstructionSet
+    pub fn symbols(&mut self) -> Result<Vec<ELFSymbol>, HostError> {
+        self.build()?;
+        let elf = self.elf.as_ref().unwrap();
+        Ok(tracer::symbols(elf)?)
+    }
+
+    /// Traces the guest program, expanding each row into its virtual sequence (as
+    /// specified by the instruction set `I`).
     #[tracing::instrument(skip_all, name = "Program::trace")]
-    pub fn trace<F: JoltField>(mut self) -> (JoltDevice, Vec<JoltTraceStep<RV32I>>, Vec<F>) {
-        self.build();
-        let elf = self.elf.unwrap();
-        let (raw_trace, io_device) =
-            tracer::trace(&elf, &self.input, self.max_input_size, self.max_output_size);
-
-        let trace: Vec<_> = raw_trace
-            .into_par_iter()
-            .flat_map(|row| match row.instruction.opcode {
-                tracer::RV32IM::MULH => MULHInstruction::<32>::virtual_sequence(row),
-                tracer::RV32IM::MULHSU => MULHSUInstruction::<32>::virtual_sequence(row),
-                tracer::RV32IM::DIV => todo!(),
-                tracer::RV32IM::DIVU => todo!(),
-                tracer::RV32IM::REM => todo!(),
-                tracer::RV32IM::REMU => todo!(),
-                _ => vec![row],
-            })
-            .map(|row| {
-                let instruction_lookup = if let Ok(jolt_instruction) = RV32I::try_from(&row) {
-                    Some(jolt_instruction)
-                } else {
-                    // Instruction does not use lookups
-                    None
-                };
-
-                JoltTraceStep {
-                    instruction_lookup,
-                    bytecode_row: BytecodeRow::from_instruction::<RV32I>(&row.instruction),
-                    memory_ops: (&row).into(),
-                }
-            })
-            .collect();
-        let padded_trace_len = trace.len().next_power_of_two();
-
-        let mut circuit_flag_trace = unsafe_allocate_zero_vec(padded_trace_len * NUM_CIRCUIT_FLAGS);
-        circuit_flag_trace
-            .par_chunks_mut(padded_trace_len)
-            .enumerate()
-            .for_each(|(flag_index, chunk)| {
-                chunk.iter_mut().zip(trace.iter()).for_each(|(flag, row)| {
-                    let packed_circuit_flags = row.bytecode_row.bitflags >> RV32I::COUNT;
-                    // Check if the flag is set in the packed representation
-                    if (packed_circuit_flags >> (NUM_CIRCUIT_FLAGS - flag_index - 1)) & 1 != 0 {
-                        *flag = F::one();
-                    }
-                });
-            });
+    pub fn trace<F: JoltField, I: JoltInstructionSet>(
+        mut self,
+    ) -> Result<(JoltDevice, Vec<JoltTraceStep<I>>, Vec<F>), HostError> {
+        self.build()?;
+        let elf = self.elf.as_ref().unwrap();
+        let (raw_trace, io_device) = tracer::trace(
+            elf,
+            &self.input,
+            self.max_input_size,
+            self.max_output_size,
+            self.max_cycles,
+            Some(custom_instruction_output::<I>),
+            |message| self.print_console(message),
+        )?;
+
+        let trace = jolt_trace_steps(raw_trace);
+        let circuit_flag_trace = JoltTraceStep::circuit_flags(&trace);
+
+        Ok((io_device, trace, circuit_flag_trace))
+    }
 
-        (io_device, trace, circuit_flag_trace)
+    /// Same as `trace`, but runs the guest without holding its whole trace in memory: the
+    /// expanded trace is handed to `on_chunk` in chunks of exactly `chunk_length` steps (the
+    /// last one may be shorter) as it is generated. Returns the final program I/O.
+    #[tracing::instrument(skip_all, name = "Program::trace_chunked")]
+    pub fn trace_chunked<I: JoltInstructionSet>(
+        &mut self,
+        chunk_length: usize,
+        on_chunk: impl FnMut(Vec<JoltTraceStep<I>>),
+    ) -> Result<JoltDevice, HostError> {
+        self.trace_chunks(chunk_length, true, on_chunk)
     }
 
-    pub fn trace_analyze<F: JoltField>(mut self) -> ProgramSummary {
-        self.build();
+    /// Same as `trace_chunked`, but drops what the guest prints, for runs which are repeated.
+    pub(crate) fn trace_chunked_silently<I: JoltInstructionSet>(
+        &mut self,
+        chunk_length: usize,
+        on_chunk: impl FnMut(Vec<JoltTraceStep<I>>),
+    ) -> Result<JoltDevice, HostError> {
+        self.trace_chunks(chunk_length, false, on_chunk)
+    }
+
+    fn trace_chunks<I: JoltInstructionSet>(
+        &mut self,
+        chunk_length: usize,
+        print_console: bool,
+        mut on_chunk: impl FnMut(Vec<JoltTraceStep<I>>),
+    ) -> Result<JoltDevice, HostError> {
+        self.build()?;
         let elf = self.elf.as_ref().unwrap();
-        let (raw_trace, _) =
-            tracer::trace(elf, &self.input, self.max_input_size, self.max_output_size);
+        let mut steps = Vec::new();
+        let io_device = tracer::trace_chunked(
+            elf,
+            &self.input,
+            self.max_input_size,
+            self.max_output_size,
+            self.max_cycles,
+            Some(custom_instruction_output::<I>),
+            chunk_length,
+            |raw_chunk| {
+                // Virtual sequences make the expanded chunk longer than the raw one
+                steps.extend(jolt_trace_steps(raw_chunk));
+                while steps.len() >= chunk_length {
+                    let rest = steps.split_off(chunk_length);
+                    on_chunk(std::mem::replace(&mut steps, rest));
+                }
+            },
+            |message| {
+                if print_console {
+                    self.print_console(message)
+                }
+            },
+        )?;
+        if !steps.is_empty() {
+            on_chunk(steps);
+        }
 
-        let (bytecode, memory_init) = self.decode();
-        let (io_device, processed_trace, circuit_flags) = self.trace();
+        Ok(io_device)
+    }
+
+    pub fn trace_analyze<F: JoltField>(mut self) -> Result<ProgramSummary, HostError> {
+        self.build()?;
+        let elf = self.elf.as_ref().unwrap();
+        let (raw_trace, _) = tracer::trace(
+            elf,
+            &self.input,
+            self.max_input_size,
+            self.max_output_size,
+            self.max_cycles,
+            Some(custom_instruction_output::<RV32I>),
+            // Printed when traced again below
+            |_| {},
+        )?;
+
+        let (bytecode, memory_init) = self.decode()?;
+        let symbols = self.symbols()?;
+        let (io_device, processed_trace, circuit_flags) = self.trace::<F, RV32I>()?;
         let circuit_flags: Vec<bool> = circuit_flags
             .into_iter()
             .map(|flag: F| flag.is_one())
             .collect();
 
-        ProgramSummary {
+        Ok(ProgramSummary {
             raw_trace,
             bytecode,
             memory_init,
+            symbols,
             io_device,
             processed_trace,
             circuit_flags,
-        }
+        })
     }
 
-    fn save_linker(&self) {
-        let linker_path = PathBuf::from_str(&self.linker_path()).unwrap();
+    fn save_linker(&self) -> Result<(), HostError> {
+        let linker_path = PathBuf::from(self.linker_path());
         if let Some(parent) = linker_path.parent() {
-            fs::create_dir_all(parent).expect("could not create linker file");
+            fs::create_dir_all(parent)?;
         }
 
         let linker_script = LINKER_SCRIPT_TEMPLATE
             .replace("{MEMORY_SIZE}", &self.memory_size.to_string())
             .replace("{STACK_SIZE}", &self.stack_size.to_string());
 
-        let mut file = File::create(linker_path).expect("could not create linker file");
-        file.write_all(linker_script.as_bytes())
-            .expect("could not save linker");
+        let mut file = File::create(linker_path)?;
+        file.write_all(linker_script.as_bytes())?;
+        Ok(())
     }
 
     fn linker_path(&self) -> String {
@@ -254,6 +348,62 @@ impl Program {
     }
 }
 
+/// Expands each row of `raw_trace` into its virtual sequence (as specified by the
+/// instruction set `I`).
+fn jolt_trace_steps<I: JoltInstructionSet>(raw_trace: Vec<RVTraceRow>) -> Vec<JoltTraceStep<I>> {
+    raw_trace
+        .into_par_iter()
+        .flat_map_iter(|row| {
+            let virtual_sequence = I::virtual_sequence(row);
+            let bytecode_rows = BytecodeRow::from_instructions::<I>(
+                virtual_sequence.iter().map(|row| &row.instruction),
+            );
+            virtual_sequence
+                .into_iter()
+                .zip(bytecode_rows)
+                .map(|(row, bytecode_row)| {
+                    let instruction_lookup = if let Ok(jolt_instruction) = I::try_from(&row) {
+                        Some(jolt_instruction)
+                    } else {
+                        // Instruction does not use lookups
+                        None
+                    };
+
+                    JoltTraceStep {
+                        instruction_lookup,
+                        bytecode_row,
+                        memory_ops: (&row).into(),
+                    }
+                })
+        })
+        .collect()
+}
+
+/// Program built by hand from `code` (see `tracer::test_utils`), so that tests don't need
+/// the guest toolchain.
+#[cfg(test)]
+pub(crate) fn test_program(name: &str, code: &[u32]) -> Program {
+    test_program_text(name, &tracer::test_utils::words(code))
+}
+
+/// Same as `test_program`, but takes the program's text as bytes (see
+/// `tracer::test_utils::elf_text`).
+#[cfg(test)]
+pub(crate) fn test_program_text(name: &str, text: &[u8]) -> Program {
+    let elf = std::env::temp_dir().join(format!("jolt-test-{}.elf", name));
+    fs::write(&elf, tracer::test_utils::elf_text(text)).unwrap();
+    let mut program = Program::new(name);
+    program.elf = Some(elf);
+    program
+}
+
+/// Executes a custom instruction in the tracer, using the instruction set's lookup semantics.
+fn custom_instruction_output<I: JoltInstructionSet>(row: &RVTraceRow) -> Option<u64> {
+    I::try_from(row)
+        .ok()
+        .map(|instruction| instruction.lookup_entry())
+}
+
 const LINKER_SCRIPT_TEMPLATE: &str = r#"
 MEMORY {
   program (rwx) : ORIGIN = 0x80000000, LENGTH = {MEMORY_SIZE}
@@ -281,5 +431,6 @@ SECTIONS {
   _STACK_PTR = .;
   . = ALIGN(8);
   _HEAP_PTR = .;
+  _HEAP_END = ORIGIN(program) + LENGTH(program);
 }
 "#;
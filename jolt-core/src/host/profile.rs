@@ -0,0 +1,286 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+use tracer::{ELFInstruction, ELFSymbol, RVTraceRow, RV32IM};
+
+use crate::jolt::instruction::JoltInstructionSet;
+
+const UNKNOWN_FUNCTION: &str = "[unknown]";
+
+/// Cycles attributed to a single guest function.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FunctionProfile {
+    pub name: String,
+    /// Number of times execution entered the function at its first instruction.
+    pub calls: usize,
+    /// RISC-V instructions executed in the function's own body.
+    pub self_cycles: usize,
+    /// Jolt trace steps for the function's own body, i.e. after virtual sequences
+    /// (DIV, MULH, ...) have been expanded.
+    pub self_jolt_cycles: usize,
+    /// Jolt trace steps for the function and everything it calls.
+    pub total_jolt_cycles: usize,
+}
+
+/// Per-function profile of a guest execution. Each row of the raw trace is attributed to
+/// the ELF symbol containing its address, and call stacks are reconstructed from calls and
+/// returns under the standard RISC-V calling convention.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Profile {
+    /// Call stacks (outermost function first) and the Jolt trace steps spent in each.
+    pub stacks: Vec<(Vec<String>, usize)>,
+    /// Per-function cycle counts, sorted by decreasing `self_jolt_cycles`.
+    pub functions: Vec<FunctionProfile>,
+}
+
+impl Profile {
+    pub fn new<I: JoltInstructionSet>(symbols: &[ELFSymbol], raw_trace: &[RVTraceRow]) -> Self {
+        // Index `symbols.len()` stands for any address outside of a known function
+        let unknown = symbols.len();
+        let function_at = |address: u64| {
+            let index = symbols.partition_point(|symbol| symbol.address <= address);
+            match index.checked_sub(1) {
+                Some(index) if symbols[index].contains(address) => index,
+                _ => unknown,
+            }
+        };
+
+        let mut calls = vec![0; symbols.len() + 1];
+        let mut self_cycles = vec![0; symbols.len() + 1];
+        let mut self_jolt_cycles = vec![0; symbols.len() + 1];
+        let mut stack_cycles: HashMap<Vec<usize>, usize> = HashMap::new();
+
+        let mut stack: Vec<usize> = vec![];
+        let mut current_stack: Vec<usize> = vec![];
+        let mut current_cycles = 0;
+        let mut previous: Option<&ELFInstruction> = None;
+
+        for row in raw_trace {
+            let address = row.instruction.address;
+            let function = function_at(address);
+
+            match previous {
+                Some(instruction) if is_call(instruction) => stack.push(function),
+                Some(instruction) if is_return(instruction) => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+            // Tail calls and unbalanced returns leave the top of the stack stale
+            match stack.last_mut() {
+                Some(top) if *top == function => {}
+                Some(top) => *top = function,
+                None => stack.push(function),
+            }
+
+            if function != unknown && address == symbols[function].address {
+                calls[function] += 1;
+            }
+
+            if stack != current_stack {
+                if current_cycles > 0 {
+                    *stack_cycles.entry(current_stack.clone()).or_default() += current_cycles;
+                }
+                current_stack.clone_from(&stack);
+                current_cycles = 0;
+            }
+
+            let jolt_cycles = I::virtual_sequence(row.clone()).len();
+            self_cycles[function] += 1;
+            self_jolt_cycles[function] += jolt_cycles;
+            current_cycles += jolt_cycles;
+            previous = Some(&row.instruction);
+        }
+        if current_cycles > 0 {
+            *stack_cycles.entry(current_stack).or_default() += current_cycles;
+        }
+
+        // Recursive functions appear multiple times in a stack but are only counted once
+        let mut total_jolt_cycles = vec![0; symbols.len() + 1];
+        for (stack, cycles) in stack_cycles.iter() {
+            let mut functions = stack.clone();
+            functions.sort_unstable();
+            functions.dedup();
+            for function in functions {
+                total_jolt_cycles[function] += cycles;
+            }
+        }
+
+        let name = |function: usize| {
+            symbols
+                .get(function)
+                .map_or(UNKNOWN_FUNCTION, |symbol| symbol.name.as_str())
+                .to_string()
+        };
+
+        let mut stacks: Vec<_> = stack_cycles
+            .into_iter()
+            .map(|(stack, cycles)| (stack.into_iter().map(name).collect::<Vec<_>>(), cycles))
+            .collect();
+        stacks.sort();
+
+        let mut functions: Vec<_> = (0..=symbols.len())
+            .filter(|&function| total_jolt_cycles[function] > 0)
+            .map(|function| FunctionProfile {
+                name: name(function),
+                calls: calls[function],
+                self_cycles: self_cycles[function],
+                self_jolt_cycles: self_jolt_cycles[function],
+                total_jolt_cycles: total_jolt_cycles[function],
+            })
+            .collect();
+        functions.sort_by(|a, b| {
+            b.self_jolt_cycles
+                .cmp(&a.self_jolt_cycles)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        Self { stacks, functions }
+    }
+
+    /// Total number of Jolt trace steps (before padding).
+    pub fn jolt_cycles(&self) -> usize {
+        self.stacks.iter().map(|(_, cycles)| cycles).sum()
+    }
+
+    /// Writes the call stacks in the "folded" format consumed by flamegraph tools
+    /// (e.g. `inferno-flamegraph` or `flamegraph.pl`): one `outer;...;inner cycles` per line.
+    pub fn write_folded(&self, path: PathBuf) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        for (stack, cycles) in self.stacks.iter() {
+            // Semicolons separate frames, so they can't appear in names (e.g. `[u8; 32]`)
+            let frames: Vec<_> = stack.iter().map(|name| name.replace(';', ",")).collect();
+            writeln!(writer, "{} {}", frames.join(";"), cycles)?;
+        }
+        writer.flush()
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>12} {:>12} {:>12} {:>8}  function",
+            "self", "self (jolt)", "total (jolt)", "calls"
+        )?;
+        for function in self.functions.iter() {
+            writeln!(
+                f,
+                "{:>12} {:>12} {:>12} {:>8}  {}",
+                function.self_cycles,
+                function.self_jolt_cycles,
+                function.total_jolt_cycles,
+                function.calls,
+                function.name
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `instruction` is a call, i.e. a jump that links `ra` or `t0`.
+fn is_call(instruction: &ELFInstruction) -> bool {
+    matches!(instruction.opcode, RV32IM::JAL | RV32IM::JALR)
+        && matches!(instruction.rd, Some(1) | Some(5))
+}
+
+/// Whether `instruction` is a return, i.e. an unlinked jump to `ra` or `t0`.
+fn is_return(instruction: &ELFInstruction) -> bool {
+    instruction.opcode == RV32IM::JALR
+        && matches!(instruction.rd, None | Some(0))
+        && matches!(instruction.rs1, Some(1) | Some(5))
+}
+
+#[cfg(test)]
+mod test {
+    use tracer::RegisterState;
+
+    use crate::jolt::vm::rv32i_vm::RV32I;
+
+    use super::*;
+
+    fn symbol(name: &str, address: u64) -> ELFSymbol {
+        ELFSymbol {
+            name: name.to_string(),
+            address,
+            size: 0x100,
+        }
+    }
+
+    fn row(address: u64, opcode: RV32IM, rd: u64, rs1: u64) -> RVTraceRow {
+        RVTraceRow {
+            instruction: ELFInstruction {
+                address,
+                opcode,
+                rs1: Some(rs1),
+                rs2: Some(2),
+                rd: Some(rd),
+                imm: Some(0),
+                virtual_sequence_index: None,
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(7),
+                rs2_val: Some(2),
+                rd_post_val: Some(0),
+            },
+            memory_state: None,
+        }
+    }
+
+    #[test]
+    fn profile_call_stacks() {
+        let symbols = vec![symbol("main", 0x1000), symbol("div", 0x2000)];
+        let divu_len = RV32I::virtual_sequence(row(0x2000, RV32IM::DIVU, 3, 3)).len();
+        assert!(divu_len > 1);
+
+        let trace = vec![
+            row(0x1000, RV32IM::ADD, 3, 3),
+            row(0x1004, RV32IM::JAL, 1, 0),
+            // div
+            row(0x2000, RV32IM::DIVU, 3, 3),
+            row(0x2004, RV32IM::JALR, 0, 1),
+            // main
+            row(0x1008, RV32IM::JAL, 1, 0),
+            // div
+            row(0x2000, RV32IM::DIVU, 3, 3),
+            row(0x2004, RV32IM::JALR, 0, 1),
+            // main, then a jump to an address without a symbol
+            row(0x100c, RV32IM::JAL, 0, 0),
+            row(0x3000, RV32IM::ADD, 3, 3),
+        ];
+        let profile = Profile::new::<RV32I>(&symbols, &trace);
+
+        let stack = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        assert_eq!(
+            profile.stacks,
+            vec![
+                (stack(&["[unknown]"]), 1),
+                (stack(&["main"]), 4),
+                (stack(&["main", "div"]), 2 * (divu_len + 1)),
+            ]
+        );
+        assert_eq!(profile.jolt_cycles(), 5 + 2 * (divu_len + 1));
+
+        let div = &profile.functions[0];
+        assert_eq!(div.name, "div");
+        assert_eq!(div.calls, 2);
+        assert_eq!(div.self_cycles, 4);
+        assert_eq!(div.self_jolt_cycles, 2 * (divu_len + 1));
+        assert_eq!(div.total_jolt_cycles, 2 * (divu_len + 1));
+
+        let main = &profile.functions[1];
+        assert_eq!(main.name, "main");
+        assert_eq!(main.calls, 1);
+        assert_eq!(main.self_cycles, 4);
+        assert_eq!(main.self_jolt_cycles, 4);
+        assert_eq!(main.total_jolt_cycles, 4 + 2 * (divu_len + 1));
+    }
+}
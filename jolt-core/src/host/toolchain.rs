@@ -1,26 +1,79 @@
 use std::{
+    env,
     fs::{self, read_to_string, File},
     future::Future,
-    io::Write,
-    path::PathBuf,
+    io::{self, Write},
+    path::{Path, PathBuf},
 };
 
 use dirs::home_dir;
 use eyre::{bail, eyre, Result};
 use indicatif::{ProgressBar, ProgressStyle};
 use reqwest::Client;
+use sha2::{Digest, Sha256};
 use tokio::runtime::Runtime;
 
+use crate::utils::errors::HostError;
+
 const TOOLCHAIN_TAG: &str = "nightly-3c5f0ec3f4f98a2d211061a83bade8d62c6a6135";
 const DOWNLOAD_RETRIES: usize = 5;
 const DELAY_BASE_MS: u64 = 500;
 
-/// Installs the toolchain if it is not already
+/// SHA-256 checksums of the toolchain archives released under `TOOLCHAIN_TAG`, in the
+/// format of `sha256sum`. Must be updated together with `TOOLCHAIN_TAG`.
+const TOOLCHAIN_CHECKSUMS: &str = include_str!("toolchain.sha256");
+/// Hosts a toolchain archive is released for under `TOOLCHAIN_TAG`.
+const SUPPORTED_TARGETS: [&str; 2] = ["x86_64-unknown-linux-gnu", "aarch64-apple-darwin"];
+
+/// Path to a toolchain archive to install instead of downloading one.
+pub const TOOLCHAIN_ARCHIVE_ENV: &str = "JOLT_TOOLCHAIN_ARCHIVE";
+/// Expected SHA-256 checksum of the toolchain archive, overriding the pinned one.
+pub const TOOLCHAIN_SHA256_ENV: &str = "JOLT_TOOLCHAIN_SHA256";
+/// If set, the toolchain is never downloaded.
+pub const OFFLINE_ENV: &str = "JOLT_OFFLINE";
+
+/// Installs the toolchain if it is not already. It is installed from the archive in
+/// `JOLT_TOOLCHAIN_ARCHIVE` if set, and downloaded otherwise unless `JOLT_OFFLINE` is set.
+/// Downloads without a pinned checksum are installed unverified, with a warning.
 pub fn install_toolchain() -> Result<()> {
     if has_toolchain() {
         return Ok(());
     }
 
+    let checksum = env::var(TOOLCHAIN_SHA256_ENV).ok();
+    if let Some(archive) = env::var_os(TOOLCHAIN_ARCHIVE_ENV) {
+        return install_toolchain_from(Path::new(&archive), checksum.as_deref());
+    }
+    if !SUPPORTED_TARGETS.contains(&target_lexicon::HOST.to_string().as_str()) {
+        bail!(
+            "toolchain {} is not released for {}; build it yourself and install it with \
+            `jolt install-toolchain --from <archive> --sha256 <checksum>`",
+            TOOLCHAIN_TAG,
+            target_lexicon::HOST
+        );
+    }
+    if env::var_os(OFFLINE_ENV).is_some() {
+        bail!(
+            "toolchain {} is not installed and {} disables downloading it; \
+            install it with `jolt install-toolchain --from <archive>` or set {}",
+            TOOLCHAIN_TAG,
+            OFFLINE_ENV,
+            TOOLCHAIN_ARCHIVE_ENV
+        );
+    }
+
+    // Resolved before downloading, so that a missing checksum is reported up front
+    let archive_name = toolchain_archive_name();
+    let expected = expected_checksum(TOOLCHAIN_CHECKSUMS, &archive_name, checksum.as_deref());
+    if expected.is_none() {
+        tracing::warn!(
+            "no checksum pinned for {}, installing the release download unverified; \
+            pass the expected checksum with {} to verify it",
+            archive_name,
+            TOOLCHAIN_SHA256_ENV
+        );
+    }
+
     let client = Client::builder().user_agent("Mozilla/5.0").build()?;
     let toolchain_url = toolchain_url();
 
@@ -28,24 +81,76 @@ pub fn install_toolchain() -> Result<()> {
     rt.block_on(retry_times(DOWNLOAD_RETRIES, DELAY_BASE_MS, || {
         download_toolchain(&client, &toolchain_url)
     }))?;
-    unpack_toolchain()?;
+
+    install_archive(
+        &jolt_dir().join("rust-toolchain.tar.gz"),
+        expected.as_deref(),
+    )
+}
+
+/// Installs the toolchain from a local archive, after verifying it against `checksum`
+/// or, if `None`, the checksum pinned for this host. Archives without either are rejected.
+pub fn install_toolchain_from(archive: &Path, checksum: Option<&str>) -> Result<()> {
+    let archive_name = toolchain_archive_name();
+    let Some(expected) = expected_checksum(TOOLCHAIN_CHECKSUMS, &archive_name, checksum) else {
+        bail!(
+            "no checksum pinned for {}; pass the expected checksum with --sha256 or {}",
+            archive_name,
+            TOOLCHAIN_SHA256_ENV
+        );
+    };
+    install_archive(archive, Some(&expected))
+}
+
+/// Unpacks and links the toolchain in `archive`, after verifying it against `expected` if
+/// given.
+fn install_archive(archive: &Path, expected: Option<&str>) -> Result<()> {
+    if let Some(expected) = expected {
+        let actual = sha256(archive)?;
+        if actual != expected {
+            bail!(
+                "checksum mismatch for {}: expected {}, got {}",
+                archive.display(),
+                expected,
+                actual
+            );
+        }
+    }
+
+    fs::create_dir_all(jolt_dir())?;
+    unpack_toolchain(archive)?;
     link_toolchain()?;
 
     write_tag_file()
 }
 
+/// Converts an error from installing the toolchain into a `HostError`: I/O failures are
+/// reported as such, and anything else means the toolchain is not available.
+pub(crate) fn host_error(err: eyre::Report) -> HostError {
+    match err.downcast::<io::Error>() {
+        Ok(err) => HostError::Io(err),
+        Err(err) => HostError::ToolchainMissing(err.to_string()),
+    }
+}
+
 async fn retry_times<F, T, E>(times: usize, base_ms: u64, f: F) -> Result<T>
 where
     F: Fn() -> E,
     E: Future<Output = Result<T>>,
 {
     for i in 0..times {
-        println!("Attempt {}/{}", i + 1, times);
+        tracing::info!("toolchain download attempt {}/{}", i + 1, times);
         match f().await {
             Ok(t) => return Ok(t),
             Err(e) => {
                 let timeout = delay_timeout(i, base_ms);
-                println!("Toolchain download error {i}/{times}: {e}. Retrying in {timeout}ms");
+                tracing::warn!(
+                    "toolchain download attempt {}/{} failed: {}, retrying in {}ms",
+                    i + 1,
+                    times,
+                    e,
+                    timeout
+                );
                 tokio::time::sleep(std::time::Duration::from_millis(timeout)).await;
             }
         }
@@ -83,9 +188,11 @@ fn link_toolchain() -> Result<()> {
     Ok(())
 }
 
-fn unpack_toolchain() -> Result<()> {
+fn unpack_toolchain(archive: &Path) -> Result<()> {
+    let archive = archive.canonicalize()?;
     let output = std::process::Command::new("tar")
-        .args(["-xzf", "rust-toolchain.tar.gz"])
+        .arg("-xzf")
+        .arg(archive)
         .current_dir(jolt_dir())
         .output()?;
 
@@ -103,7 +210,7 @@ async fn download_toolchain(client: &Client, url: &str) -> Result<()> {
         fs::create_dir(&jolt_dir)?;
     }
 
-    println!("Downloading toolchain");
+    tracing::info!("downloading toolchain from {}", url);
     let mut response = client.get(url).send().await?;
     if response.status().is_success() {
         let mut file = File::create(output_path)?;
@@ -133,13 +240,45 @@ async fn download_toolchain(client: &Client, url: &str) -> Result<()> {
 }
 
 fn toolchain_url() -> String {
-    let target = target_lexicon::HOST;
     format!(
-        "https://github.com/a16z/rust/releases/download/{}/rust-toolchain-{}.tar.gz",
-        TOOLCHAIN_TAG, target,
+        "https://github.com/a16z/rust/releases/download/{}/{}",
+        TOOLCHAIN_TAG,
+        toolchain_archive_name(),
     )
 }
 
+fn toolchain_archive_name() -> String {
+    archive_name(&target_lexicon::HOST.to_string())
+}
+
+fn archive_name(target: &str) -> String {
+    format!("rust-toolchain-{}.tar.gz", target)
+}
+
+/// Checksum `file_name` must match: `checksum` if given, and otherwise the one pinned in
+/// `manifest`.
+fn expected_checksum(manifest: &str, file_name: &str, checksum: Option<&str>) -> Option<String> {
+    checksum
+        .map(str::to_lowercase)
+        .or_else(|| pinned_checksum(manifest, file_name))
+}
+
+/// Looks up the checksum of `file_name` in a `sha256sum`-formatted manifest.
+fn pinned_checksum(manifest: &str, file_name: &str) -> Option<String> {
+    manifest
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once(char::is_whitespace))
+        .find(|(_, name)| name.trim_start().trim_start_matches('*') == file_name)
+        .map(|(checksum, _)| checksum.to_lowercase())
+}
+
+fn sha256(path: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
 fn has_toolchain() -> bool {
     let tag_path = toolchain_tag_file();
     if let Ok(tag) = read_to_string(tag_path) {
@@ -156,3 +295,93 @@ fn jolt_dir() -> PathBuf {
 fn toolchain_tag_file() -> PathBuf {
     jolt_dir().join(".toolchaintag")
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pinned_checksums() {
+        let manifest = "\
+            # comment\n\
+            0123abcd  rust-toolchain-x86_64-unknown-linux-gnu.tar.gz\n\
+            4567EF01 *rust-toolchain-aarch64-apple-darwin.tar.gz\n";
+        assert_eq!(
+            pinned_checksum(manifest, "rust-toolchain-x86_64-unknown-linux-gnu.tar.gz"),
+            Some("0123abcd".to_string())
+        );
+        assert_eq!(
+            pinned_checksum(manifest, "rust-toolchain-aarch64-apple-darwin.tar.gz"),
+            Some("4567ef01".to_string())
+        );
+        assert_eq!(
+            pinned_checksum(manifest, "rust-toolchain-x86_64-pc-windows-msvc.tar.gz"),
+            None
+        );
+    }
+
+    #[test]
+    fn explicit_checksum_overrides_pinned() {
+        let manifest = "0123abcd  rust-toolchain-x86_64-unknown-linux-gnu.tar.gz\n";
+        let file_name = "rust-toolchain-x86_64-unknown-linux-gnu.tar.gz";
+        assert_eq!(
+            expected_checksum(manifest, file_name, Some("4567EF01")),
+            Some("4567ef01".to_string())
+        );
+        assert_eq!(
+            expected_checksum(manifest, file_name, None),
+            Some("0123abcd".to_string())
+        );
+        assert_eq!(expected_checksum("", file_name, None), None);
+    }
+
+    #[test]
+    fn pinned_checksums_are_well_formed() {
+        let archives = SUPPORTED_TARGETS.map(archive_name);
+        for line in TOOLCHAIN_CHECKSUMS.lines() {
+            if line.starts_with('#') || line.trim().is_empty() {
+                continue;
+            }
+            let (checksum, name) = line.split_once(char::is_whitespace).unwrap();
+            let name = name.trim_start().trim_start_matches('*');
+            assert!(
+                archives.iter().any(|archive| archive == name),
+                "checksum pinned for {}, which isn't the archive of a supported target",
+                name
+            );
+            assert!(
+                checksum.len() == 64 && checksum.chars().all(|c| c.is_ascii_hexdigit()),
+                "malformed SHA-256 checksum pinned for {}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn install_rejects_checksum_mismatch() {
+        let archive = env::temp_dir().join("jolt-toolchain-checksum-test.tar.gz");
+        fs::write(&archive, b"not a toolchain").unwrap();
+
+        let result = install_toolchain_from(&archive, Some(&"0".repeat(64)));
+        fs::remove_file(&archive).unwrap();
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("checksum mismatch"));
+    }
+
+    #[test]
+    fn install_errors_map_to_host_errors() {
+        let archive = env::temp_dir().join("jolt-toolchain-missing-test.tar.gz");
+        let result = install_toolchain_from(&archive, Some(&"0".repeat(64)));
+        assert!(matches!(
+            host_error(result.unwrap_err()),
+            HostError::Io(err) if err.kind() == io::ErrorKind::NotFound
+        ));
+
+        assert!(matches!(
+            host_error(eyre!("not released")),
+            HostError::ToolchainMissing(message) if message == "not released"
+        ));
+    }
+}
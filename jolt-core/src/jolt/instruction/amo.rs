@@ -0,0 +1,322 @@
+use common::constants::virtual_register_index;
+use tracer::{ELFInstruction, MemoryState, RVTraceRow, RegisterState, RV32IM};
+
+use super::{instructions_of, zero_trace_row, VirtualInstructionSequence};
+use crate::jolt::instruction::{
+    add::ADDInstruction, and::ANDInstruction, mul::MULInstruction, or::ORInstruction,
+    slt::SLTInstruction, sltu::SLTUInstruction, sub::SUBInstruction, xor::XORInstruction,
+    JoltInstruction,
+};
+
+/// Atomic memory operations on words (AMO*.W, RV32A). Jolt proves a single hart, so an AMO
+/// is a load, an ALU operation and a store:
+///
+/// ```text
+/// LW   v_old, 0(rs1)
+/// OP   v_new, v_old, rs2    (omitted for AMOSWAP.W)
+/// SW   v_new, 0(rs1)
+/// ADDI rd, v_old, 0
+/// ```
+///
+/// AMOMIN/AMOMAX[U] select between the two values without branching:
+/// `v_new = v_old + (rs2 < v_old) * (rs2 - v_old)`, with the comparison flipped for max.
+pub struct AMOInstruction;
+
+impl VirtualInstructionSequence for AMOInstruction {
+    fn virtual_sequence(trace_row: RVTraceRow) -> Vec<RVTraceRow> {
+        // AMO operands
+        let base = trace_row.register_state.rs1_val.unwrap();
+        let src = trace_row.register_state.rs2_val.unwrap();
+        let (address, old) = match trace_row.memory_state {
+            Some(MemoryState::Read { address, value }) => (address, value),
+            _ => panic!("Expected MemoryState::Read"),
+        };
+        // AMO source registers
+        let r_base = trace_row.instruction.rs1;
+        let r_src = trace_row.instruction.rs2;
+        // Virtual registers used in sequence
+        let v_old = Some(virtual_register_index(0));
+        let v_new = Some(virtual_register_index(1));
+        let v_lt = Some(virtual_register_index(2));
+        let v_diff = Some(virtual_register_index(3));
+
+        let instruction_address = trace_row.instruction.address;
+        let alu_row = |opcode: RV32IM,
+                       (rs1, rs1_val): (Option<u64>, u64),
+                       (rs2, rs2_val): (Option<u64>, u64),
+                       rd: Option<u64>,
+                       rd_post_val: u64| RVTraceRow {
+            instruction: ELFInstruction {
+                address: instruction_address,
+                opcode,
+                rs1,
+                rs2,
+                rd,
+                imm: None,
+                virtual_sequence_index: None,
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(rs1_val),
+                rs2_val: Some(rs2_val),
+                rd_post_val: Some(rd_post_val),
+            },
+            memory_state: None,
+        };
+
+        let mut virtual_sequence = vec![RVTraceRow {
+            instruction: ELFInstruction {
+                address: instruction_address,
+                opcode: RV32IM::LW,
+                rs1: r_base,
+                rs2: None,
+                rd: v_old,
+                imm: Some(0),
+                virtual_sequence_index: None,
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(base),
+                rs2_val: None,
+                rd_post_val: Some(old),
+            },
+            memory_state: Some(MemoryState::Read {
+                address,
+                value: old,
+            }),
+        }];
+
+        let (r_new, new) = match trace_row.instruction.opcode {
+            RV32IM::AMOSWAP_W => (r_src, src),
+            RV32IM::AMOADD_W | RV32IM::AMOXOR_W | RV32IM::AMOAND_W | RV32IM::AMOOR_W => {
+                let (opcode, new) = match trace_row.instruction.opcode {
+                    RV32IM::AMOADD_W => {
+                        (RV32IM::ADD, ADDInstruction::<32>(old, src).lookup_entry())
+                    }
+                    RV32IM::AMOXOR_W => (RV32IM::XOR, XORInstruction(old, src).lookup_entry()),
+                    RV32IM::AMOAND_W => (RV32IM::AND, ANDInstruction(old, src).lookup_entry()),
+                    _ => (RV32IM::OR, ORInstruction(old, src).lookup_entry()),
+                };
+                virtual_sequence.push(alu_row(opcode, (v_old, old), (r_src, src), v_new, new));
+                (v_new, new)
+            }
+            RV32IM::AMOMIN_W | RV32IM::AMOMAX_W | RV32IM::AMOMINU_W | RV32IM::AMOMAXU_W => {
+                // Compare such that the result is 1 iff rs2 should be stored
+                let (opcode, lhs, rhs) = match trace_row.instruction.opcode {
+                    RV32IM::AMOMIN_W => (RV32IM::SLT, (r_src, src), (v_old, old)),
+                    RV32IM::AMOMAX_W => (RV32IM::SLT, (v_old, old), (r_src, src)),
+                    RV32IM::AMOMINU_W => (RV32IM::SLTU, (r_src, src), (v_old, old)),
+                    _ => (RV32IM::SLTU, (v_old, old), (r_src, src)),
+                };
+                let lt = match opcode {
+                    RV32IM::SLT => SLTInstruction::<32>(lhs.1, rhs.1).lookup_entry(),
+                    _ => SLTUInstruction(lhs.1, rhs.1).lookup_entry(),
+                };
+                virtual_sequence.push(alu_row(opcode, lhs, rhs, v_lt, lt));
+
+                let diff = SUBInstruction::<32>(src, old).lookup_entry();
+                virtual_sequence.push(alu_row(
+                    RV32IM::SUB,
+                    (r_src, src),
+                    (v_old, old),
+                    v_diff,
+                    diff,
+                ));
+
+                let selected_diff = MULInstruction::<32>(lt, diff).lookup_entry();
+                virtual_sequence.push(alu_row(
+                    RV32IM::MUL,
+                    (v_lt, lt),
+                    (v_diff, diff),
+                    v_diff,
+                    selected_diff,
+                ));
+
+                let new = ADDInstruction::<32>(old, selected_diff).lookup_entry();
+                virtual_sequence.push(alu_row(
+                    RV32IM::ADD,
+                    (v_old, old),
+                    (v_diff, selected_diff),
+                    v_new,
+                    new,
+                ));
+                (v_new, new)
+            }
+            opcode => panic!("Unexpected opcode {opcode:?}"),
+        };
+
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: instruction_address,
+                opcode: RV32IM::SW,
+                rs1: r_base,
+                rs2: r_new,
+                rd: None,
+                imm: Some(0),
+                virtual_sequence_index: None,
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(base),
+                rs2_val: Some(new),
+                rd_post_val: None,
+            },
+            memory_state: Some(MemoryState::Write {
+                address,
+                post_value: new,
+            }),
+        });
+
+        // The tracer reports rd_post_val = 0 if rd is x0
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: instruction_address,
+                opcode: RV32IM::ADDI,
+                rs1: v_old,
+                rs2: None,
+                rd: trace_row.instruction.rd,
+                imm: Some(0),
+                virtual_sequence_index: None,
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(old),
+                rs2_val: None,
+                rd_post_val: trace_row.register_state.rd_post_val,
+            },
+            memory_state: None,
+        });
+
+        for (i, row) in virtual_sequence.iter_mut().enumerate() {
+            row.instruction.virtual_sequence_index = Some(i);
+        }
+        virtual_sequence
+    }
+
+    fn sequence_instructions(instruction: &ELFInstruction) -> Vec<ELFInstruction> {
+        let memory_state = Some(MemoryState::Read {
+            address: 0,
+            value: 0,
+        });
+        instructions_of(Self::virtual_sequence(zero_trace_row(
+            instruction,
+            memory_state,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_std::test_rng;
+    use common::constants::MEMORY_OPS_PER_INSTRUCTION;
+    use common::rv_trace::MemoryOp;
+    use rand_chacha::rand_core::RngCore;
+
+    use crate::jolt::vm::rv32i_vm::RV32I;
+
+    use super::*;
+
+    fn amo(opcode: RV32IM, old: u32, src: u32) -> u32 {
+        match opcode {
+            RV32IM::AMOSWAP_W => src,
+            RV32IM::AMOADD_W => old.wrapping_add(src),
+            RV32IM::AMOXOR_W => old ^ src,
+            RV32IM::AMOAND_W => old & src,
+            RV32IM::AMOOR_W => old | src,
+            RV32IM::AMOMIN_W => (old as i32).min(src as i32) as u32,
+            RV32IM::AMOMAX_W => (old as i32).max(src as i32) as u32,
+            RV32IM::AMOMINU_W => old.min(src),
+            RV32IM::AMOMAXU_W => old.max(src),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn amo_virtual_sequence_32() {
+        let opcodes = [
+            RV32IM::AMOSWAP_W,
+            RV32IM::AMOADD_W,
+            RV32IM::AMOXOR_W,
+            RV32IM::AMOAND_W,
+            RV32IM::AMOOR_W,
+            RV32IM::AMOMIN_W,
+            RV32IM::AMOMAX_W,
+            RV32IM::AMOMINU_W,
+            RV32IM::AMOMAXU_W,
+        ];
+        let mut rng = test_rng();
+        for opcode in opcodes {
+            for i in 0..64 {
+                let address = 0x8000_0000 + (rng.next_u64() % (1 << 20)) * 4;
+                let old = rng.next_u32();
+                let src = match i {
+                    0 => old,
+                    1 => old.wrapping_add(1),
+                    2 => i32::MIN as u32,
+                    _ => rng.next_u32(),
+                };
+                let rd = rng.next_u64() % 32;
+
+                let trace_row = RVTraceRow {
+                    instruction: ELFInstruction {
+                        address: 0,
+                        opcode,
+                        rs1: Some(1),
+                        rs2: Some(2),
+                        rd: Some(rd),
+                        imm: None,
+                        virtual_sequence_index: None,
+                        virtual_sequence_remaining: None,
+                        is_compressed: false,
+                    },
+                    register_state: RegisterState {
+                        rs1_val: Some(address),
+                        rs2_val: Some(src as u64),
+                        rd_post_val: Some(if rd == 0 { 0 } else { old as u64 }),
+                    },
+                    memory_state: Some(MemoryState::Read {
+                        address,
+                        value: old as u64,
+                    }),
+                };
+
+                let virtual_sequence = AMOInstruction::virtual_sequence(trace_row);
+                let mut bytes_read = vec![];
+                let mut bytes_written = vec![];
+                for row in virtual_sequence.iter() {
+                    let lookup = RV32I::try_from(row).unwrap();
+                    if row.instruction.rd.is_some_and(|rd| rd != 0) {
+                        if let Some(rd_post_val) = row.register_state.rd_post_val {
+                            assert_eq!(lookup.lookup_entry(), rd_post_val, "{opcode:?}");
+                        }
+                    }
+                    let memory_ops: [MemoryOp; MEMORY_OPS_PER_INSTRUCTION] = row.into();
+                    for op in &memory_ops[3..] {
+                        match op {
+                            MemoryOp::Read(byte_address) if *byte_address != 0 => {
+                                bytes_read.push(*byte_address)
+                            }
+                            MemoryOp::Write(byte_address, byte) if *byte_address != 0 => {
+                                bytes_written.push((*byte_address, *byte as u8))
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+
+                let expected = amo(opcode, old, src);
+                assert_eq!(bytes_read, (address..address + 4).collect::<Vec<_>>());
+                let expected_bytes: Vec<_> =
+                    (address..address + 4).zip(expected.to_le_bytes()).collect();
+                assert_eq!(bytes_written, expected_bytes, "{opcode:?}");
+                let last_row = virtual_sequence.last().unwrap();
+                assert_eq!(last_row.instruction.rd, Some(rd));
+                assert_eq!(last_row.register_state.rs1_val, Some(old as u64));
+            }
+        }
+    }
+}
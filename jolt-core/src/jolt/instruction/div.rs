@@ -0,0 +1,592 @@
+use common::constants::virtual_register_index;
+use tracer::{ELFInstruction, RVTraceRow, RegisterState, RV32IM};
+
+use super::VirtualInstructionSequence;
+use crate::jolt::instruction::{
+    add::ADDInstruction, beq::BEQInstruction, movsign::MOVSIGNInstruction, mulhu::MULHUInstruction,
+    mulu::MULUInstruction, sltu::SLTUInstruction, sub::SUBInstruction,
+    virtual_assert_valid_div0::ASSERTVALIDDIV0Instruction,
+    virtual_assert_valid_unsigned_remainder::ASSERTVALIDUNSIGNEDREMAINDERInstruction,
+    xor::XORInstruction, JoltInstruction,
+};
+
+/// Perform signed division and return the quotient
+pub struct DIVInstruction<const WORD_SIZE: usize>;
+
+impl<const WORD_SIZE: usize> VirtualInstructionSequence for DIVInstruction<WORD_SIZE> {
+    fn virtual_sequence(trace_row: RVTraceRow) -> Vec<RVTraceRow> {
+        assert_eq!(trace_row.instruction.opcode, RV32IM::DIV);
+        // DIV operands
+        let x = trace_row.register_state.rs1_val.unwrap();
+        let y = trace_row.register_state.rs2_val.unwrap();
+        // DIV source registers
+        let r_x = trace_row.instruction.rs1;
+        let r_y = trace_row.instruction.rs2;
+        let r_zero = Some(0);
+        // Virtual registers used in sequence
+        let v_qa = Some(virtual_register_index(0));
+        let v_ra = Some(virtual_register_index(1));
+        let v_sx = Some(virtual_register_index(2));
+        let v_sy = Some(virtual_register_index(3));
+        let v_xa = Some(virtual_register_index(4));
+        let v_ya = Some(virtual_register_index(5));
+        let v_qy = Some(virtual_register_index(6));
+        let v_0 = Some(virtual_register_index(7));
+        let v_1 = Some(virtual_register_index(8));
+
+        let mut virtual_sequence = vec![];
+
+        // The division is checked on the absolute values of the operands, so the
+        // prover supplies |quotient| and |remainder| as advice. Division by zero and
+        // overflow (MIN / -1) follow the RISC-V spec; both fall out of the same checks.
+        let (quotient, remainder) = match WORD_SIZE {
+            32 => {
+                let (x, y) = (x as i32, y as i32);
+                if y == 0 {
+                    (u32::MAX as u64, x as u32 as u64)
+                } else {
+                    (
+                        x.wrapping_div(y) as u32 as u64,
+                        x.wrapping_rem(y) as u32 as u64,
+                    )
+                }
+            }
+            64 => {
+                let (x, y) = (x as i64, y as i64);
+                if y == 0 {
+                    (u64::MAX, x as u64)
+                } else {
+                    (x.wrapping_div(y) as u64, x.wrapping_rem(y) as u64)
+                }
+            }
+            _ => panic!("only implemented for u32 / u64"),
+        };
+        let quotient_abs = if ((x ^ y) >> (WORD_SIZE - 1)) & 1 == 1 {
+            quotient.wrapping_neg()
+        } else {
+            quotient
+        };
+        let remainder_abs = if (x >> (WORD_SIZE - 1)) & 1 == 1 {
+            remainder.wrapping_neg()
+        } else {
+            remainder
+        };
+        let (quotient_abs, remainder_abs) = match WORD_SIZE {
+            32 => (quotient_abs as u32 as u64, remainder_abs as u32 as u64),
+            64 => (quotient_abs, remainder_abs),
+            _ => panic!("only implemented for u32 / u64"),
+        };
+
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::VIRTUAL_ADVICE,
+                rs1: None,
+                rs2: None,
+                rd: v_qa,
+                imm: None,
+                virtual_sequence_index: Some(0),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: None,
+                rs2_val: None,
+                rd_post_val: Some(quotient_abs),
+            },
+            memory_state: None,
+        });
+
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::VIRTUAL_ADVICE,
+                rs1: None,
+                rs2: None,
+                rd: v_ra,
+                imm: None,
+                virtual_sequence_index: Some(1),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: None,
+                rs2_val: None,
+                rd_post_val: Some(remainder_abs),
+            },
+            memory_state: None,
+        });
+
+        let s_x = MOVSIGNInstruction::<WORD_SIZE>(x).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::VIRTUAL_MOVSIGN,
+                rs1: r_x,
+                rs2: None,
+                rd: v_sx,
+                imm: None,
+                virtual_sequence_index: Some(2),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(x),
+                rs2_val: None,
+                rd_post_val: Some(s_x),
+            },
+            memory_state: None,
+        });
+
+        let s_y = MOVSIGNInstruction::<WORD_SIZE>(y).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::VIRTUAL_MOVSIGN,
+                rs1: r_y,
+                rs2: None,
+                rd: v_sy,
+                imm: None,
+                virtual_sequence_index: Some(3),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(y),
+                rs2_val: None,
+                rd_post_val: Some(s_y),
+            },
+            memory_state: None,
+        });
+
+        let x_flipped = XORInstruction(x, s_x).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::XOR,
+                rs1: r_x,
+                rs2: v_sx,
+                rd: v_xa,
+                imm: None,
+                virtual_sequence_index: Some(4),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(x),
+                rs2_val: Some(s_x),
+                rd_post_val: Some(x_flipped),
+            },
+            memory_state: None,
+        });
+
+        let x_abs = SUBInstruction::<WORD_SIZE>(x_flipped, s_x).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::SUB,
+                rs1: v_xa,
+                rs2: v_sx,
+                rd: v_xa,
+                imm: None,
+                virtual_sequence_index: Some(5),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(x_flipped),
+                rs2_val: Some(s_x),
+                rd_post_val: Some(x_abs),
+            },
+            memory_state: None,
+        });
+
+        let y_flipped = XORInstruction(y, s_y).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::XOR,
+                rs1: r_y,
+                rs2: v_sy,
+                rd: v_ya,
+                imm: None,
+                virtual_sequence_index: Some(6),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(y),
+                rs2_val: Some(s_y),
+                rd_post_val: Some(y_flipped),
+            },
+            memory_state: None,
+        });
+
+        let y_abs = SUBInstruction::<WORD_SIZE>(y_flipped, s_y).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::SUB,
+                rs1: v_ya,
+                rs2: v_sy,
+                rd: v_ya,
+                imm: None,
+                virtual_sequence_index: Some(7),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(y_flipped),
+                rs2_val: Some(s_y),
+                rd_post_val: Some(y_abs),
+            },
+            memory_state: None,
+        });
+
+        let q_y = MULUInstruction::<WORD_SIZE>(quotient_abs, y_abs).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::MULU,
+                rs1: v_qa,
+                rs2: v_ya,
+                rd: v_qy,
+                imm: None,
+                virtual_sequence_index: Some(8),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(quotient_abs),
+                rs2_val: Some(y_abs),
+                rd_post_val: Some(q_y),
+            },
+            memory_state: None,
+        });
+
+        let q_y_upper = MULHUInstruction::<WORD_SIZE>(quotient_abs, y_abs).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::MULHU,
+                rs1: v_qa,
+                rs2: v_ya,
+                rd: v_0,
+                imm: None,
+                virtual_sequence_index: Some(9),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(quotient_abs),
+                rs2_val: Some(y_abs),
+                rd_post_val: Some(q_y_upper),
+            },
+            memory_state: None,
+        });
+
+        let is_valid: u64 = BEQInstruction(q_y_upper, 0).lookup_entry();
+        assert_eq!(is_valid, 1);
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::VIRTUAL_ASSERT_EQ,
+                rs1: v_0,
+                rs2: r_zero,
+                rd: None,
+                imm: None,
+                virtual_sequence_index: Some(10),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(q_y_upper),
+                rs2_val: Some(0),
+                rd_post_val: None,
+            },
+            memory_state: None,
+        });
+
+        let add_0 = ADDInstruction::<WORD_SIZE>(q_y, remainder_abs).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::ADD,
+                rs1: v_qy,
+                rs2: v_ra,
+                rd: v_0,
+                imm: None,
+                virtual_sequence_index: Some(11),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(q_y),
+                rs2_val: Some(remainder_abs),
+                rd_post_val: Some(add_0),
+            },
+            memory_state: None,
+        });
+
+        let overflow = SLTUInstruction(add_0, q_y).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::SLTU,
+                rs1: v_0,
+                rs2: v_qy,
+                rd: v_1,
+                imm: None,
+                virtual_sequence_index: Some(12),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(add_0),
+                rs2_val: Some(q_y),
+                rd_post_val: Some(overflow),
+            },
+            memory_state: None,
+        });
+
+        let is_valid: u64 = BEQInstruction(overflow, 0).lookup_entry();
+        assert_eq!(is_valid, 1);
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::VIRTUAL_ASSERT_EQ,
+                rs1: v_1,
+                rs2: r_zero,
+                rd: None,
+                imm: None,
+                virtual_sequence_index: Some(13),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(overflow),
+                rs2_val: Some(0),
+                rd_post_val: None,
+            },
+            memory_state: None,
+        });
+
+        let is_valid: u64 = BEQInstruction(add_0, x_abs).lookup_entry();
+        assert_eq!(is_valid, 1);
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::VIRTUAL_ASSERT_EQ,
+                rs1: v_0,
+                rs2: v_xa,
+                rd: None,
+                imm: None,
+                virtual_sequence_index: Some(14),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(add_0),
+                rs2_val: Some(x_abs),
+                rd_post_val: None,
+            },
+            memory_state: None,
+        });
+
+        let is_valid: u64 =
+            ASSERTVALIDUNSIGNEDREMAINDERInstruction(remainder_abs, y_abs).lookup_entry();
+        assert_eq!(is_valid, 1);
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::VIRTUAL_ASSERT_VALID_UNSIGNED_REMAINDER,
+                rs1: v_ra,
+                rs2: v_ya,
+                rd: None,
+                imm: None,
+                virtual_sequence_index: Some(15),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(remainder_abs),
+                rs2_val: Some(y_abs),
+                rd_post_val: None,
+            },
+            memory_state: None,
+        });
+
+        let s_q = XORInstruction(s_x, s_y).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::XOR,
+                rs1: v_sx,
+                rs2: v_sy,
+                rd: v_0,
+                imm: None,
+                virtual_sequence_index: Some(16),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(s_x),
+                rs2_val: Some(s_y),
+                rd_post_val: Some(s_q),
+            },
+            memory_state: None,
+        });
+
+        let q_flipped = XORInstruction(quotient_abs, s_q).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::XOR,
+                rs1: v_qa,
+                rs2: v_0,
+                rd: v_1,
+                imm: None,
+                virtual_sequence_index: Some(17),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(quotient_abs),
+                rs2_val: Some(s_q),
+                rd_post_val: Some(q_flipped),
+            },
+            memory_state: None,
+        });
+
+        let q = SUBInstruction::<WORD_SIZE>(q_flipped, s_q).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::SUB,
+                rs1: v_1,
+                rs2: v_0,
+                rd: v_1,
+                imm: None,
+                virtual_sequence_index: Some(18),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(q_flipped),
+                rs2_val: Some(s_q),
+                rd_post_val: Some(q),
+            },
+            memory_state: None,
+        });
+
+        let is_valid: u64 = ASSERTVALIDDIV0Instruction::<WORD_SIZE>(y, q).lookup_entry();
+        assert_eq!(is_valid, 1);
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::VIRTUAL_ASSERT_VALID_DIV0,
+                rs1: r_y,
+                rs2: v_1,
+                rd: None,
+                imm: None,
+                virtual_sequence_index: Some(19),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(y),
+                rs2_val: Some(q),
+                rd_post_val: None,
+            },
+            memory_state: None,
+        });
+
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::ADDI,
+                rs1: v_1,
+                rs2: None,
+                rd: trace_row.instruction.rd,
+                imm: Some(0),
+                virtual_sequence_index: Some(20),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(q),
+                rs2_val: None,
+                rd_post_val: Some(q),
+            },
+            memory_state: None,
+        });
+        virtual_sequence
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_std::test_rng;
+    use common::constants::REGISTER_COUNT;
+    use rand_chacha::rand_core::RngCore;
+
+    use crate::{jolt::vm::rv32i_vm::RV32I, jolt_virtual_sequence_test};
+
+    use super::*;
+
+    fn div(x: u64, y: u64) -> u64 {
+        if y == 0 {
+            u32::MAX as u64
+        } else {
+            (x as i32).wrapping_div(y as i32) as u32 as u64
+        }
+    }
+
+    #[test]
+    fn div_virtual_sequence_32() {
+        let mut rng = test_rng();
+
+        // Random
+        for _ in 0..256 {
+            let r_x = rng.next_u64() % 31 + 1;
+            let r_y = rng.next_u64() % 31 + 1;
+            let rd = rng.next_u64() % 31 + 1;
+
+            let x = rng.next_u32() as u64;
+            let y = if r_x == r_y { x } else { rng.next_u32() as u64 };
+            let result = div(x, y);
+            jolt_virtual_sequence_test!(
+                DIVInstruction::<32>,
+                RV32IM::DIV,
+                x,
+                y,
+                r_x,
+                r_y,
+                rd,
+                result
+            );
+        }
+
+        // Edge-cases
+        let i32_min = i32::MIN as u32 as u64;
+        let neg_one = -1i32 as u32 as u64;
+        let operands = [
+            (0, 0),
+            (1, 0),
+            (neg_one, 0),
+            (i32_min, 0),
+            (i32_min, neg_one),
+            (i32_min, 1),
+            (i32_min, i32_min),
+            (neg_one, neg_one),
+            (-7i32 as u32 as u64, 2),
+            (7, -2i32 as u32 as u64),
+            (-7i32 as u32 as u64, -2i32 as u32 as u64),
+            (i32::MAX as u64, neg_one),
+        ];
+        for (x, y) in operands {
+            let result = div(x, y);
+            jolt_virtual_sequence_test!(DIVInstruction::<32>, RV32IM::DIV, x, y, 1, 2, 3, result);
+        }
+    }
+}
@@ -0,0 +1,370 @@
+use common::constants::virtual_register_index;
+use tracer::{ELFInstruction, RVTraceRow, RegisterState, RV32IM};
+
+use super::VirtualInstructionSequence;
+use crate::jolt::instruction::{
+    add::ADDInstruction, beq::BEQInstruction, mulhu::MULHUInstruction, mulu::MULUInstruction,
+    sltu::SLTUInstruction, virtual_assert_valid_div0::ASSERTVALIDDIV0Instruction,
+    virtual_assert_valid_unsigned_remainder::ASSERTVALIDUNSIGNEDREMAINDERInstruction,
+    JoltInstruction,
+};
+
+/// Perform unsigned division and return the quotient
+pub struct DIVUInstruction<const WORD_SIZE: usize>;
+
+impl<const WORD_SIZE: usize> VirtualInstructionSequence for DIVUInstruction<WORD_SIZE> {
+    fn virtual_sequence(trace_row: RVTraceRow) -> Vec<RVTraceRow> {
+        assert_eq!(trace_row.instruction.opcode, RV32IM::DIVU);
+        // DIVU operands
+        let x = trace_row.register_state.rs1_val.unwrap();
+        let y = trace_row.register_state.rs2_val.unwrap();
+        // DIVU source registers
+        let r_x = trace_row.instruction.rs1;
+        let r_y = trace_row.instruction.rs2;
+        let r_zero = Some(0);
+        // Virtual registers used in sequence
+        let v_q = Some(virtual_register_index(0));
+        let v_r = Some(virtual_register_index(1));
+        let v_qy = Some(virtual_register_index(2));
+        let v_0 = Some(virtual_register_index(3));
+        let v_1 = Some(virtual_register_index(4));
+
+        let mut virtual_sequence = vec![];
+
+        // The prover supplies the quotient and remainder as advice. Division by zero
+        // follows the RISC-V spec: the quotient is all ones and the remainder is x.
+        let (quotient, remainder) = match WORD_SIZE {
+            32 => {
+                if y == 0 {
+                    (u32::MAX as u64, x)
+                } else {
+                    (x / y, x % y)
+                }
+            }
+            64 => {
+                if y == 0 {
+                    (u64::MAX, x)
+                } else {
+                    (x / y, x % y)
+                }
+            }
+            _ => panic!("only implemented for u32 / u64"),
+        };
+
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::VIRTUAL_ADVICE,
+                rs1: None,
+                rs2: None,
+                rd: v_q,
+                imm: None,
+                virtual_sequence_index: Some(0),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: None,
+                rs2_val: None,
+                rd_post_val: Some(quotient),
+            },
+            memory_state: None,
+        });
+
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::VIRTUAL_ADVICE,
+                rs1: None,
+                rs2: None,
+                rd: v_r,
+                imm: None,
+                virtual_sequence_index: Some(1),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: None,
+                rs2_val: None,
+                rd_post_val: Some(remainder),
+            },
+            memory_state: None,
+        });
+
+        let q_y = MULUInstruction::<WORD_SIZE>(quotient, y).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::MULU,
+                rs1: v_q,
+                rs2: r_y,
+                rd: v_qy,
+                imm: None,
+                virtual_sequence_index: Some(2),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(quotient),
+                rs2_val: Some(y),
+                rd_post_val: Some(q_y),
+            },
+            memory_state: None,
+        });
+
+        let q_y_upper = MULHUInstruction::<WORD_SIZE>(quotient, y).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::MULHU,
+                rs1: v_q,
+                rs2: r_y,
+                rd: v_0,
+                imm: None,
+                virtual_sequence_index: Some(3),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(quotient),
+                rs2_val: Some(y),
+                rd_post_val: Some(q_y_upper),
+            },
+            memory_state: None,
+        });
+
+        let is_valid: u64 = BEQInstruction(q_y_upper, 0).lookup_entry();
+        assert_eq!(is_valid, 1);
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::VIRTUAL_ASSERT_EQ,
+                rs1: v_0,
+                rs2: r_zero,
+                rd: None,
+                imm: None,
+                virtual_sequence_index: Some(4),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(q_y_upper),
+                rs2_val: Some(0),
+                rd_post_val: None,
+            },
+            memory_state: None,
+        });
+
+        let add_0 = ADDInstruction::<WORD_SIZE>(q_y, remainder).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::ADD,
+                rs1: v_qy,
+                rs2: v_r,
+                rd: v_0,
+                imm: None,
+                virtual_sequence_index: Some(5),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(q_y),
+                rs2_val: Some(remainder),
+                rd_post_val: Some(add_0),
+            },
+            memory_state: None,
+        });
+
+        let overflow = SLTUInstruction(add_0, q_y).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::SLTU,
+                rs1: v_0,
+                rs2: v_qy,
+                rd: v_1,
+                imm: None,
+                virtual_sequence_index: Some(6),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(add_0),
+                rs2_val: Some(q_y),
+                rd_post_val: Some(overflow),
+            },
+            memory_state: None,
+        });
+
+        let is_valid: u64 = BEQInstruction(overflow, 0).lookup_entry();
+        assert_eq!(is_valid, 1);
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::VIRTUAL_ASSERT_EQ,
+                rs1: v_1,
+                rs2: r_zero,
+                rd: None,
+                imm: None,
+                virtual_sequence_index: Some(7),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(overflow),
+                rs2_val: Some(0),
+                rd_post_val: None,
+            },
+            memory_state: None,
+        });
+
+        let is_valid: u64 = BEQInstruction(add_0, x).lookup_entry();
+        assert_eq!(is_valid, 1);
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::VIRTUAL_ASSERT_EQ,
+                rs1: v_0,
+                rs2: r_x,
+                rd: None,
+                imm: None,
+                virtual_sequence_index: Some(8),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(add_0),
+                rs2_val: Some(x),
+                rd_post_val: None,
+            },
+            memory_state: None,
+        });
+
+        let is_valid: u64 = ASSERTVALIDUNSIGNEDREMAINDERInstruction(remainder, y).lookup_entry();
+        assert_eq!(is_valid, 1);
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::VIRTUAL_ASSERT_VALID_UNSIGNED_REMAINDER,
+                rs1: v_r,
+                rs2: r_y,
+                rd: None,
+                imm: None,
+                virtual_sequence_index: Some(9),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(remainder),
+                rs2_val: Some(y),
+                rd_post_val: None,
+            },
+            memory_state: None,
+        });
+
+        let is_valid: u64 = ASSERTVALIDDIV0Instruction::<WORD_SIZE>(y, quotient).lookup_entry();
+        assert_eq!(is_valid, 1);
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::VIRTUAL_ASSERT_VALID_DIV0,
+                rs1: r_y,
+                rs2: v_q,
+                rd: None,
+                imm: None,
+                virtual_sequence_index: Some(10),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(y),
+                rs2_val: Some(quotient),
+                rd_post_val: None,
+            },
+            memory_state: None,
+        });
+
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::ADDI,
+                rs1: v_q,
+                rs2: None,
+                rd: trace_row.instruction.rd,
+                imm: Some(0),
+                virtual_sequence_index: Some(11),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(quotient),
+                rs2_val: None,
+                rd_post_val: Some(quotient),
+            },
+            memory_state: None,
+        });
+        virtual_sequence
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_std::test_rng;
+    use common::constants::REGISTER_COUNT;
+    use rand_chacha::rand_core::RngCore;
+
+    use crate::{jolt::vm::rv32i_vm::RV32I, jolt_virtual_sequence_test};
+
+    use super::*;
+
+    fn divu(x: u64, y: u64) -> u64 {
+        x.checked_div(y).unwrap_or(u32::MAX as u64)
+    }
+
+    #[test]
+    fn divu_virtual_sequence_32() {
+        let mut rng = test_rng();
+
+        // Random
+        for _ in 0..256 {
+            let r_x = rng.next_u64() % 31 + 1;
+            let r_y = rng.next_u64() % 31 + 1;
+            let rd = rng.next_u64() % 31 + 1;
+
+            let x = rng.next_u32() as u64;
+            let y = if r_x == r_y { x } else { rng.next_u32() as u64 };
+            let result = divu(x, y);
+            jolt_virtual_sequence_test!(
+                DIVUInstruction::<32>,
+                RV32IM::DIVU,
+                x,
+                y,
+                r_x,
+                r_y,
+                rd,
+                result
+            );
+        }
+
+        // Edge-cases
+        let u32_max = u32::MAX as u64;
+        let operands = [
+            (0, 0),
+            (1, 0),
+            (u32_max, 0),
+            (0, 1),
+            (0, u32_max),
+            (u32_max, 1),
+            (u32_max, 2),
+            (u32_max, u32_max),
+            (u32_max - 1, u32_max),
+            (1 << 31, u32_max),
+            (100, 7),
+        ];
+        for (x, y) in operands {
+            let result = divu(x, y);
+            jolt_virtual_sequence_test!(DIVUInstruction::<32>, RV32IM::DIVU, x, y, 1, 2, 3, result);
+        }
+    }
+}
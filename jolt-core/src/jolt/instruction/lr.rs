@@ -0,0 +1,93 @@
+use tracer::{ELFInstruction, MemoryState, RVTraceRow, RegisterState, RV32IM};
+
+use super::{instructions_of, zero_trace_row, VirtualInstructionSequence};
+
+/// Load-reserved word (LR.W, RV32A). Jolt proves a single hart, so no other hart can
+/// invalidate the reservation and LR.W is just a load.
+pub struct LRInstruction;
+
+impl VirtualInstructionSequence for LRInstruction {
+    fn virtual_sequence(trace_row: RVTraceRow) -> Vec<RVTraceRow> {
+        assert_eq!(trace_row.instruction.opcode, RV32IM::LR_W);
+        assert!(
+            matches!(trace_row.memory_state, Some(MemoryState::Read { .. })),
+            "Expected MemoryState::Read"
+        );
+
+        vec![RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::LW,
+                rs1: trace_row.instruction.rs1,
+                rs2: None,
+                rd: trace_row.instruction.rd,
+                imm: Some(0),
+                virtual_sequence_index: Some(0),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: trace_row.register_state.rs1_val,
+                rs2_val: None,
+                rd_post_val: trace_row.register_state.rd_post_val,
+            },
+            memory_state: trace_row.memory_state,
+        }]
+    }
+
+    fn sequence_instructions(instruction: &ELFInstruction) -> Vec<ELFInstruction> {
+        let memory_state = Some(MemoryState::Read {
+            address: 0,
+            value: 0,
+        });
+        instructions_of(Self::virtual_sequence(zero_trace_row(
+            instruction,
+            memory_state,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::constants::MEMORY_OPS_PER_INSTRUCTION;
+    use common::rv_trace::MemoryOp;
+
+    use crate::jolt::{instruction::JoltInstruction, vm::rv32i_vm::RV32I};
+
+    use super::*;
+
+    #[test]
+    fn lr_virtual_sequence_32() {
+        let address = 0x8000_1000;
+        let value = 0xdead_beef;
+        let trace_row = RVTraceRow {
+            instruction: ELFInstruction {
+                address: 0x8000_0000,
+                opcode: RV32IM::LR_W,
+                rs1: Some(10),
+                rs2: Some(0),
+                rd: Some(11),
+                imm: None,
+                virtual_sequence_index: None,
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(address),
+                rs2_val: Some(0),
+                rd_post_val: Some(value),
+            },
+            memory_state: Some(MemoryState::Read { address, value }),
+        };
+
+        let virtual_sequence = LRInstruction::virtual_sequence(trace_row);
+        assert_eq!(virtual_sequence.len(), 1);
+        let row = &virtual_sequence[0];
+        assert_eq!(RV32I::try_from(row).unwrap().lookup_entry(), value);
+        let memory_ops: [MemoryOp; MEMORY_OPS_PER_INSTRUCTION] = row.into();
+        assert_eq!(memory_ops[2], MemoryOp::Write(11, value));
+        for (i, op) in memory_ops[3..].iter().enumerate() {
+            assert_eq!(*op, MemoryOp::Read(address + i as u64));
+        }
+    }
+}
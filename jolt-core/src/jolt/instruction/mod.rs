@@ -1,19 +1,17 @@
-use enum_dispatch::enum_dispatch;
 use fixedbitset::*;
 use rand::prelude::StdRng;
 use serde::Serialize;
 use std::marker::Sync;
 use std::ops::Range;
 use strum::{EnumCount, IntoEnumIterator};
-use tracer::RVTraceRow;
+use tracer::{MemoryState, RVTraceRow, RegisterState};
 
 use crate::field::JoltField;
 use crate::jolt::subtable::LassoSubtable;
 use crate::utils::instruction_utils::chunk_operand;
-use common::rv_trace::ELFInstruction;
+use common::rv_trace::{ELFInstruction, NUM_CIRCUIT_FLAGS};
 use std::fmt::Debug;
 
-#[enum_dispatch]
 pub trait JoltInstruction: Clone + Debug + Send + Sync + Serialize {
     fn operands(&self) -> (u64, u64);
     /// Combines `vals` according to the instruction's "collation" polynomial `g`.
@@ -70,12 +68,186 @@ pub trait JoltInstruction: Clone + Debug + Send + Sync + Serialize {
     }
 }
 
+/// Bytecode rows pack the circuit flags and the one-hot instruction flags into a single u64
+/// (see `BytecodeRow::bitflags`), which the R1CS recombines using i64 coefficients. This bounds
+/// the number of instructions in a `JoltInstructionSet`.
+pub const MAX_INSTRUCTION_SET_SIZE: usize = 63 - NUM_CIRCUIT_FLAGS;
+
+/// An enum of `JoltInstruction`s (see `instruction_set!`) along with its mapping from RISC-V
+/// instructions. Custom instructions (`RV32IM::CUSTOM_0` and `RV32IM::CUSTOM_1`) are supported
+/// by mapping them in the `TryFrom` impls: the tracer computes their output using
+/// `JoltInstruction::lookup_entry`, and their lookup queries must concatenate the operand chunks.
 pub trait JoltInstructionSet:
-    JoltInstruction + IntoEnumIterator + EnumCount + for<'a> TryFrom<&'a ELFInstruction> + Send + Sync
+    JoltInstruction
+    + IntoEnumIterator
+    + EnumCount
+    + for<'a> TryFrom<&'a ELFInstruction>
+    + for<'a> TryFrom<&'a RVTraceRow>
+    + Send
+    + Sync
 {
     fn enum_index(instruction: &Self) -> usize {
         unsafe { *<*const _>::from(instruction).cast::<u8>() as usize }
     }
+
+    /// Expands a trace row into the sequence of rows that is actually proven. Instructions
+    /// that are not natively supported by the instruction set (e.g. MULH in RV32I) are
+    /// replaced by their virtual sequence; all other rows are returned as-is.
+    fn virtual_sequence(trace_row: RVTraceRow) -> Vec<RVTraceRow> {
+        vec![trace_row]
+    }
+
+    /// The instructions a decoded `instruction` takes up in the bytecode: those of all the
+    /// virtual sequences `virtual_sequence` may expand it to, or just `instruction` itself.
+    fn bytecode_sequence(instruction: &ELFInstruction) -> Vec<ELFInstruction> {
+        vec![instruction.clone()]
+    }
+}
+
+/// Generates an enum out of a list of JoltInstruction types, implementing `JoltInstruction`
+/// for the enum by dispatching to the variant's instruction, and `From` for each variant type.
+/// The generated enum still needs `TryFrom<&ELFInstruction>`, `TryFrom<&RVTraceRow>` and
+/// `JoltInstructionSet` impls to be used as the instruction set of a `Jolt` VM.
+///
+/// Can be invoked from other crates, which then need to depend on `rand`, `serde`, `strum`
+/// and `strum_macros`. To extend RV32I with custom instructions, see `rv32i_instruction_set!`.
+#[macro_export]
+macro_rules! instruction_set {
+    ($enum_name:ident, $($alias:ident: $struct:ty),+ $(,)?) => {
+        #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+        #[repr(u8)]
+        #[derive(
+            Copy,
+            Clone,
+            Debug,
+            ::strum_macros::EnumIter,
+            ::strum_macros::EnumCount,
+            ::serde::Serialize,
+            ::serde::Deserialize,
+        )]
+        pub enum $enum_name { $($alias($struct)),+ }
+
+        impl $enum_name {
+            pub fn random_instruction(rng: &mut ::rand::rngs::StdRng) -> Self {
+                let index = ::rand::RngCore::next_u64(rng) as usize
+                    % <$enum_name as ::strum::EnumCount>::COUNT;
+                let instruction = <$enum_name as ::strum::IntoEnumIterator>::iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i == index)
+                    .map(|(_, x)| x)
+                    .next()
+                    .unwrap();
+                $crate::jolt::instruction::JoltInstruction::random(&instruction, rng)
+            }
+        }
+
+        $(
+            impl From<$struct> for $enum_name {
+                fn from(instruction: $struct) -> Self {
+                    $enum_name::$alias(instruction)
+                }
+            }
+        )+
+
+        impl $crate::jolt::instruction::JoltInstruction for $enum_name {
+            fn operands(&self) -> (u64, u64) {
+                match self {
+                    $($enum_name::$alias(instruction) => {
+                        $crate::jolt::instruction::JoltInstruction::operands(instruction)
+                    })+
+                }
+            }
+
+            fn combine_lookups<F: $crate::field::JoltField>(
+                &self,
+                vals: &[F],
+                c: usize,
+                m: usize,
+            ) -> F {
+                match self {
+                    $($enum_name::$alias(instruction) => {
+                        $crate::jolt::instruction::JoltInstruction::combine_lookups(
+                            instruction, vals, c, m,
+                        )
+                    })+
+                }
+            }
+
+            fn g_poly_degree(&self, c: usize) -> usize {
+                match self {
+                    $($enum_name::$alias(instruction) => {
+                        $crate::jolt::instruction::JoltInstruction::g_poly_degree(instruction, c)
+                    })+
+                }
+            }
+
+            fn subtables<F: $crate::field::JoltField>(
+                &self,
+                c: usize,
+                m: usize,
+            ) -> Vec<(
+                Box<dyn $crate::jolt::subtable::LassoSubtable<F>>,
+                $crate::jolt::instruction::SubtableIndices,
+            )> {
+                match self {
+                    $($enum_name::$alias(instruction) => {
+                        $crate::jolt::instruction::JoltInstruction::subtables(instruction, c, m)
+                    })+
+                }
+            }
+
+            fn to_indices(&self, c: usize, log_m: usize) -> Vec<usize> {
+                match self {
+                    $($enum_name::$alias(instruction) => {
+                        $crate::jolt::instruction::JoltInstruction::to_indices(
+                            instruction, c, log_m,
+                        )
+                    })+
+                }
+            }
+
+            fn lookup_entry(&self) -> u64 {
+                match self {
+                    $($enum_name::$alias(instruction) => {
+                        $crate::jolt::instruction::JoltInstruction::lookup_entry(instruction)
+                    })+
+                }
+            }
+
+            fn operand_chunks(&self, c: usize, log_m: usize) -> (Vec<u64>, Vec<u64>) {
+                match self {
+                    $($enum_name::$alias(instruction) => {
+                        $crate::jolt::instruction::JoltInstruction::operand_chunks(
+                            instruction, c, log_m,
+                        )
+                    })+
+                }
+            }
+
+            fn random(&self, rng: &mut ::rand::rngs::StdRng) -> Self {
+                match self {
+                    $($enum_name::$alias(instruction) => {
+                        $crate::jolt::instruction::JoltInstruction::random(instruction, rng).into()
+                    })+
+                }
+            }
+
+            fn slice_values<'a, F: $crate::field::JoltField>(
+                &self,
+                vals: &'a [F],
+                c: usize,
+                m: usize,
+            ) -> Vec<&'a [F]> {
+                match self {
+                    $($enum_name::$alias(instruction) => {
+                        $crate::jolt::instruction::JoltInstruction::slice_values(
+                            instruction, vals, c, m,
+                        )
+                    })+
+                }
+            }
+        }
+    };
 }
 
 #[derive(Clone)]
@@ -125,16 +297,61 @@ impl From<Range<usize>> for SubtableIndices {
 
 pub trait VirtualInstructionSequence {
     fn virtual_sequence(trace_row: RVTraceRow) -> Vec<RVTraceRow>;
+
+    /// The instructions of the virtual sequences `instruction` may expand to, i.e. the rows it
+    /// takes up in the bytecode. By default, the instructions of the sequence are assumed not
+    /// to depend on the register and memory values, and are read off a trace row with all of
+    /// them 0.
+    fn sequence_instructions(instruction: &ELFInstruction) -> Vec<ELFInstruction> {
+        instructions_of(Self::virtual_sequence(zero_trace_row(instruction, None)))
+    }
+}
+
+/// A trace row of `instruction` in which all register values are 0, and which accesses
+/// `memory_state`.
+fn zero_trace_row(instruction: &ELFInstruction, memory_state: Option<MemoryState>) -> RVTraceRow {
+    RVTraceRow {
+        instruction: instruction.clone(),
+        register_state: RegisterState {
+            rs1_val: Some(0),
+            rs2_val: Some(0),
+            rd_post_val: Some(0),
+        },
+        memory_state,
+    }
+}
+
+fn instructions_of(virtual_sequence: Vec<RVTraceRow>) -> Vec<ELFInstruction> {
+    with_remaining_counts(virtual_sequence)
+        .into_iter()
+        .map(|row| row.instruction)
+        .collect()
+}
+
+/// Sets `virtual_sequence_remaining` of each row of a virtual sequence, which determines
+/// whether the row updates the PC.
+pub fn with_remaining_counts(mut virtual_sequence: Vec<RVTraceRow>) -> Vec<RVTraceRow> {
+    let len = virtual_sequence.len();
+    for (i, row) in virtual_sequence.iter_mut().enumerate() {
+        if row.instruction.virtual_sequence_index.is_some() {
+            row.instruction.virtual_sequence_remaining = Some(len - i - 1);
+        }
+    }
+    virtual_sequence
 }
 
 pub mod add;
+pub mod amo;
 pub mod and;
 pub mod beq;
 pub mod bge;
 pub mod bgeu;
 pub mod bne;
+pub mod div;
+pub mod divu;
 pub mod lb;
 pub mod lh;
+pub mod lr;
 pub mod movsign;
 pub mod mul;
 pub mod mulh;
@@ -142,7 +359,10 @@ pub mod mulhsu;
 pub mod mulhu;
 pub mod mulu;
 pub mod or;
+pub mod rem;
+pub mod remu;
 pub mod sb;
+pub mod sc;
 pub mod sh;
 pub mod sll;
 pub mod slt;
@@ -155,6 +375,8 @@ pub mod virtual_advice;
 pub mod virtual_assert_eq_signs;
 pub mod virtual_assert_lt_abs;
 pub mod virtual_assert_lte;
+pub mod virtual_assert_valid_div0;
+pub mod virtual_assert_valid_unsigned_remainder;
 pub mod xor;
 
 #[cfg(test)]
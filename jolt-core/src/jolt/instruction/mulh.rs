@@ -38,6 +38,8 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHInstruction<WORD
                 rd: v_sx,
                 imm: None,
                 virtual_sequence_index: Some(0),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
             },
             register_state: RegisterState {
                 rs1_val: Some(x),
@@ -57,6 +59,8 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHInstruction<WORD
                 rd: v_sy,
                 imm: None,
                 virtual_sequence_index: Some(1),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
             },
             register_state: RegisterState {
                 rs1_val: Some(y),
@@ -76,6 +80,8 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHInstruction<WORD
                 rd: v_0,
                 imm: None,
                 virtual_sequence_index: Some(2),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
             },
             register_state: RegisterState {
                 rs1_val: Some(x),
@@ -95,6 +101,8 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHInstruction<WORD
                 rd: v_1,
                 imm: None,
                 virtual_sequence_index: Some(3),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
             },
             register_state: RegisterState {
                 rs1_val: Some(s_x),
@@ -114,6 +122,8 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHInstruction<WORD
                 rd: v_2,
                 imm: None,
                 virtual_sequence_index: Some(4),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
             },
             register_state: RegisterState {
                 rs1_val: Some(s_y),
@@ -133,6 +143,8 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHInstruction<WORD
                 rd: v_3,
                 imm: None,
                 virtual_sequence_index: Some(5),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
             },
             register_state: RegisterState {
                 rs1_val: Some(xy_high_bits),
@@ -152,6 +164,8 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHInstruction<WORD
                 rd: trace_row.instruction.rd,
                 imm: None,
                 virtual_sequence_index: Some(6),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
             },
             register_state: RegisterState {
                 rs1_val: Some(partial_sum),
@@ -196,6 +210,8 @@ mod test {
                 rd: Some(rd),
                 imm: None,
                 virtual_sequence_index: None,
+                virtual_sequence_remaining: None,
+                is_compressed: false,
             },
             register_state: RegisterState {
                 rs1_val: Some(x),
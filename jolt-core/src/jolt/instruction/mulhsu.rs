@@ -36,6 +36,8 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHSUInstruction<WO
                 rd: v_sx,
                 imm: None,
                 virtual_sequence_index: Some(0),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
             },
             register_state: RegisterState {
                 rs1_val: Some(x),
@@ -55,6 +57,8 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHSUInstruction<WO
                 rd: v_1,
                 imm: None,
                 virtual_sequence_index: Some(1),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
             },
             register_state: RegisterState {
                 rs1_val: Some(x),
@@ -74,6 +78,8 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHSUInstruction<WO
                 rd: v_2,
                 imm: None,
                 virtual_sequence_index: Some(2),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
             },
             register_state: RegisterState {
                 rs1_val: Some(s_x),
@@ -93,6 +99,8 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHSUInstruction<WO
                 rd: trace_row.instruction.rd,
                 imm: None,
                 virtual_sequence_index: Some(3),
+                virtual_sequence_remaining: None,
+                is_compressed: false,
             },
             register_state: RegisterState {
                 rs1_val: Some(xy_high_bits),
@@ -137,6 +145,8 @@ mod test {
                 rd: Some(rd),
                 imm: None,
                 virtual_sequence_index: None,
+                virtual_sequence_remaining: None,
+                is_compressed: false,
             },
             register_state: RegisterState {
                 rs1_val: Some(x),
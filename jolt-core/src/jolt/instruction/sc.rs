@@ -0,0 +1,243 @@
+use common::constants::virtual_register_index;
+use tracer::{ELFInstruction, MemoryState, RVTraceRow, RegisterState, RV32IM};
+
+use super::{instructions_of, zero_trace_row, VirtualInstructionSequence};
+use crate::jolt::instruction::{sub::SUBInstruction, JoltInstruction};
+
+/// Store-conditional word (SC.W, RV32A). Jolt proves a single hart, so a reservation made by
+/// LR.W can only be invalidated by the hart's own SC.W. The reservation itself is not part of
+/// the proof; the sequence follows the tracer's outcome: a successful SC.W stores rs2 and
+/// writes 0 to rd, a failed one only writes 1 to rd. Both sequences are in the bytecode, the
+/// failed one after the successful one.
+pub struct SCInstruction;
+
+impl VirtualInstructionSequence for SCInstruction {
+    fn virtual_sequence(trace_row: RVTraceRow) -> Vec<RVTraceRow> {
+        assert_eq!(trace_row.instruction.opcode, RV32IM::SC_W);
+        // SC operands
+        let base = trace_row.register_state.rs1_val.unwrap();
+        let value = trace_row.register_state.rs2_val.unwrap();
+        // SC source registers
+        let r_base = trace_row.instruction.rs1;
+        let r_value = trace_row.instruction.rs2;
+        // Virtual registers used in sequence
+        let v_zero = Some(virtual_register_index(0));
+
+        let mut virtual_sequence = vec![];
+
+        let zero = SUBInstruction::<32>(base, base).lookup_entry();
+        match trace_row.memory_state {
+            Some(MemoryState::Write { address, .. }) => {
+                virtual_sequence.push(RVTraceRow {
+                    instruction: ELFInstruction {
+                        address: trace_row.instruction.address,
+                        opcode: RV32IM::SW,
+                        rs1: r_base,
+                        rs2: r_value,
+                        rd: None,
+                        imm: Some(0),
+                        virtual_sequence_index: Some(0),
+                        virtual_sequence_remaining: None,
+                        is_compressed: false,
+                    },
+                    register_state: RegisterState {
+                        rs1_val: Some(base),
+                        rs2_val: Some(value),
+                        rd_post_val: None,
+                    },
+                    memory_state: Some(MemoryState::Write {
+                        address,
+                        post_value: value,
+                    }),
+                });
+
+                // The tracer reports rd_post_val = 0 if rd is x0
+                virtual_sequence.push(RVTraceRow {
+                    instruction: ELFInstruction {
+                        address: trace_row.instruction.address,
+                        opcode: RV32IM::SUB,
+                        rs1: r_base,
+                        rs2: r_base,
+                        rd: trace_row.instruction.rd,
+                        imm: None,
+                        virtual_sequence_index: Some(1),
+                        virtual_sequence_remaining: None,
+                        is_compressed: false,
+                    },
+                    register_state: RegisterState {
+                        rs1_val: Some(base),
+                        rs2_val: Some(base),
+                        rd_post_val: trace_row.register_state.rd_post_val,
+                    },
+                    memory_state: None,
+                });
+            }
+            None => {
+                virtual_sequence.push(RVTraceRow {
+                    instruction: ELFInstruction {
+                        address: trace_row.instruction.address,
+                        opcode: RV32IM::SUB,
+                        rs1: r_base,
+                        rs2: r_base,
+                        rd: v_zero,
+                        imm: None,
+                        virtual_sequence_index: Some(2),
+                        virtual_sequence_remaining: None,
+                        is_compressed: false,
+                    },
+                    register_state: RegisterState {
+                        rs1_val: Some(base),
+                        rs2_val: Some(base),
+                        rd_post_val: Some(zero),
+                    },
+                    memory_state: None,
+                });
+
+                virtual_sequence.push(RVTraceRow {
+                    instruction: ELFInstruction {
+                        address: trace_row.instruction.address,
+                        opcode: RV32IM::ADDI,
+                        rs1: v_zero,
+                        rs2: None,
+                        rd: trace_row.instruction.rd,
+                        imm: Some(1),
+                        virtual_sequence_index: Some(3),
+                        virtual_sequence_remaining: None,
+                        is_compressed: false,
+                    },
+                    register_state: RegisterState {
+                        rs1_val: Some(zero),
+                        rs2_val: None,
+                        rd_post_val: trace_row.register_state.rd_post_val,
+                    },
+                    memory_state: None,
+                });
+            }
+            Some(MemoryState::Read { .. }) => panic!("Unexpected MemoryState::Read"),
+        }
+
+        virtual_sequence
+    }
+
+    fn sequence_instructions(instruction: &ELFInstruction) -> Vec<ELFInstruction> {
+        let succeeded = Some(MemoryState::Write {
+            address: 0,
+            post_value: 0,
+        });
+        [succeeded, None]
+            .into_iter()
+            .flat_map(|memory_state| {
+                instructions_of(Self::virtual_sequence(zero_trace_row(
+                    instruction,
+                    memory_state,
+                )))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::constants::MEMORY_OPS_PER_INSTRUCTION;
+    use common::rv_trace::MemoryOp;
+
+    use crate::jolt::instruction::JoltInstructionSet;
+    use crate::jolt::vm::rv32i_vm::RV32I;
+
+    use super::*;
+
+    fn sc_trace_row(succeeded: bool, rd: u64) -> RVTraceRow {
+        let address = 0x8000_1000;
+        RVTraceRow {
+            instruction: ELFInstruction {
+                address: 0x8000_0000,
+                opcode: RV32IM::SC_W,
+                rs1: Some(10),
+                rs2: Some(12),
+                rd: Some(rd),
+                imm: None,
+                virtual_sequence_index: None,
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(address),
+                rs2_val: Some(0x1234_5678),
+                rd_post_val: Some(if succeeded || rd == 0 { 0 } else { 1 }),
+            },
+            memory_state: succeeded.then_some(MemoryState::Write {
+                address,
+                post_value: 0x1234_5678,
+            }),
+        }
+    }
+
+    #[test]
+    fn sc_virtual_sequence_32() {
+        for succeeded in [true, false] {
+            let virtual_sequence = SCInstruction::virtual_sequence(sc_trace_row(succeeded, 11));
+            let mut bytes_written = vec![];
+            for row in virtual_sequence.iter() {
+                if let Some(rd_post_val) = row.register_state.rd_post_val {
+                    let lookup = RV32I::try_from(row).unwrap();
+                    assert_eq!(lookup.lookup_entry(), rd_post_val);
+                }
+                let memory_ops: [MemoryOp; MEMORY_OPS_PER_INSTRUCTION] = row.into();
+                for op in &memory_ops[3..] {
+                    if let MemoryOp::Write(byte_address, byte) = op {
+                        if *byte_address != 0 {
+                            bytes_written.push((*byte_address, *byte as u8));
+                        }
+                    }
+                }
+            }
+
+            let last_row = virtual_sequence.last().unwrap();
+            assert_eq!(last_row.instruction.rd, Some(11));
+            if succeeded {
+                let expected: Vec<_> = (0x8000_1000..0x8000_1004)
+                    .zip(0x1234_5678u32.to_le_bytes())
+                    .collect();
+                assert_eq!(bytes_written, expected);
+                assert_eq!(last_row.register_state.rd_post_val, Some(0));
+            } else {
+                assert!(bytes_written.is_empty());
+                assert_eq!(last_row.register_state.rd_post_val, Some(1));
+            }
+        }
+
+        // Both outcomes are in the bytecode, so that the trace can take either
+        let instructions =
+            SCInstruction::sequence_instructions(&sc_trace_row(true, 11).instruction);
+        let indices: Vec<_> = instructions
+            .iter()
+            .map(|instruction| {
+                (
+                    instruction.virtual_sequence_index,
+                    instruction.virtual_sequence_remaining,
+                )
+            })
+            .collect();
+        assert_eq!(
+            indices,
+            [
+                (Some(0), Some(1)),
+                (Some(1), Some(0)),
+                (Some(2), Some(1)),
+                (Some(3), Some(0))
+            ]
+        );
+        for succeeded in [true, false] {
+            for row in RV32I::virtual_sequence(sc_trace_row(succeeded, 11)) {
+                assert!(instructions.contains(&row.instruction));
+            }
+        }
+
+        // Writes to x0 are discarded
+        let virtual_sequence = SCInstruction::virtual_sequence(sc_trace_row(false, 0));
+        assert_eq!(
+            virtual_sequence.last().unwrap().register_state.rd_post_val,
+            Some(0)
+        );
+    }
+}
@@ -24,3 +24,85 @@ macro_rules! jolt_instruction_test {
         assert_eq!(actual, expected, "{:?}", $instr);
     };
 }
+
+#[macro_export]
+/// Tests that a virtual sequence faithfully emulates the instruction it replaces. In detail:
+/// 1. Expands a trace row for `$opcode` into its virtual sequence
+/// 2. Executes the sequence on a register file, checking that each row's register reads
+///    match the register file and that each virtual assert's lookup output is 1
+/// 3. Checks that the result was written to `rd` and that no other real register was touched
+macro_rules! jolt_virtual_sequence_test {
+    ($instr_type:ty, $opcode:expr, $x:expr, $y:expr, $r_x:expr, $r_y:expr, $rd:expr, $result:expr) => {
+        let (x, y, r_x, r_y, rd, result): (u64, u64, u64, u64, u64, u64) =
+            ($x, $y, $r_x, $r_y, $rd, $result);
+
+        let trace_row = RVTraceRow {
+            instruction: ELFInstruction {
+                address: 0,
+                opcode: $opcode,
+                rs1: Some(r_x),
+                rs2: Some(r_y),
+                rd: Some(rd),
+                imm: None,
+                virtual_sequence_index: None,
+                virtual_sequence_remaining: None,
+                is_compressed: false,
+            },
+            register_state: RegisterState {
+                rs1_val: Some(x),
+                rs2_val: Some(y),
+                rd_post_val: Some(result),
+            },
+            memory_state: None,
+        };
+
+        let virtual_sequence = <$instr_type>::virtual_sequence(trace_row);
+        let mut registers = vec![0u64; REGISTER_COUNT as usize];
+        registers[r_x as usize] = x;
+        registers[r_y as usize] = y;
+
+        for row in virtual_sequence {
+            if let Some(rs1_val) = row.register_state.rs1_val {
+                assert_eq!(registers[row.instruction.rs1.unwrap() as usize], rs1_val);
+            }
+            if let Some(rs2_val) = row.register_state.rs2_val {
+                assert_eq!(registers[row.instruction.rs2.unwrap() as usize], rs2_val);
+            }
+
+            let lookup = RV32I::try_from(&row).unwrap();
+            let output = lookup.lookup_entry();
+            if let Some(rd) = row.instruction.rd {
+                registers[rd as usize] = output;
+                assert_eq!(
+                    registers[rd as usize],
+                    row.register_state.rd_post_val.unwrap()
+                );
+            } else {
+                // Virtual assert instruction
+                assert!(
+                    output == 1,
+                    "{:?} failed for ({}, {})",
+                    row.instruction.opcode,
+                    x,
+                    y
+                );
+            }
+        }
+
+        for (index, val) in registers.iter().enumerate() {
+            if index as u64 == rd {
+                // Check that result was written to rd
+                assert_eq!(*val, result);
+            } else if index as u64 == r_x {
+                // Check that r_x hasn't been clobbered
+                assert_eq!(*val, x);
+            } else if index as u64 == r_y {
+                // Check that r_y hasn't been clobbered
+                assert_eq!(*val, y);
+            } else if index < 32 {
+                // None of the other "real" registers were touched
+                assert_eq!(*val, 0);
+            }
+        }
+    };
+}
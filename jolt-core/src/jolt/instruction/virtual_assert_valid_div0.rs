@@ -0,0 +1,125 @@
+use rand::prelude::StdRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use super::{JoltInstruction, SubtableIndices};
+use crate::{
+    field::JoltField,
+    jolt::subtable::{
+        div_by_zero::DivByZeroSubtable, left_is_zero::LeftIsZeroSubtable, LassoSubtable,
+    },
+    utils::instruction_utils::chunk_and_concatenate_operands,
+};
+
+/// (divisor, quotient)
+#[derive(Copy, Clone, Default, Debug, Serialize, Deserialize)]
+pub struct ASSERTVALIDDIV0Instruction<const WORD_SIZE: usize>(pub u64, pub u64);
+
+impl<const WORD_SIZE: usize> JoltInstruction for ASSERTVALIDDIV0Instruction<WORD_SIZE> {
+    fn operands(&self) -> (u64, u64) {
+        (self.0, self.1)
+    }
+
+    fn combine_lookups<F: JoltField>(&self, vals: &[F], C: usize, M: usize) -> F {
+        let vals_by_subtable = self.slice_values(vals, C, M);
+        let left_is_zero = vals_by_subtable[0];
+        let div_by_zero = vals_by_subtable[1];
+
+        // divisor != 0 || quotient == 2^WORD_SIZE - 1
+        let divisor_is_zero: F = left_is_zero.iter().product();
+        let is_valid_div_by_zero: F = div_by_zero.iter().product();
+
+        F::one() - divisor_is_zero + is_valid_div_by_zero
+    }
+
+    fn g_poly_degree(&self, C: usize) -> usize {
+        C
+    }
+
+    fn subtables<F: JoltField>(
+        &self,
+        C: usize,
+        _: usize,
+    ) -> Vec<(Box<dyn LassoSubtable<F>>, SubtableIndices)> {
+        vec![
+            (
+                Box::new(LeftIsZeroSubtable::new()),
+                SubtableIndices::from(0..C),
+            ),
+            (
+                Box::new(DivByZeroSubtable::new()),
+                SubtableIndices::from(0..C),
+            ),
+        ]
+    }
+
+    fn to_indices(&self, C: usize, log_M: usize) -> Vec<usize> {
+        chunk_and_concatenate_operands(self.0, self.1, C, log_M)
+    }
+
+    fn lookup_entry(&self) -> u64 {
+        let divisor = self.0;
+        let quotient = self.1;
+        if divisor == 0 {
+            match WORD_SIZE {
+                32 => (quotient == u32::MAX as u64).into(),
+                64 => (quotient == u64::MAX).into(),
+                _ => panic!("only implemented for u32 / u64"),
+            }
+        } else {
+            1
+        }
+    }
+
+    fn random(&self, rng: &mut StdRng) -> Self {
+        Self(rng.next_u32() as u64, rng.next_u32() as u64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Fr;
+    use ark_std::test_rng;
+    use rand_chacha::rand_core::RngCore;
+
+    use crate::{jolt::instruction::JoltInstruction, jolt_instruction_test};
+
+    use super::ASSERTVALIDDIV0Instruction;
+
+    #[test]
+    fn assert_valid_div0_instruction_32_e2e() {
+        let mut rng = test_rng();
+        const C: usize = 4;
+        const M: usize = 1 << 16;
+        const WORD_SIZE: usize = 32;
+
+        // Random
+        for _ in 0..256 {
+            let x = rng.next_u32() as u64;
+            let y = rng.next_u32() as u64;
+            let instruction = ASSERTVALIDDIV0Instruction::<WORD_SIZE>(x, y);
+            jolt_instruction_test!(instruction);
+        }
+
+        // Divisor is zero
+        for _ in 0..256 {
+            let y = rng.next_u32() as u64;
+            jolt_instruction_test!(ASSERTVALIDDIV0Instruction::<WORD_SIZE>(0, y));
+        }
+
+        // Edge-cases
+        let u32_max: u64 = u32::MAX as u64;
+        let instructions = vec![
+            ASSERTVALIDDIV0Instruction::<WORD_SIZE>(0, u32_max),
+            ASSERTVALIDDIV0Instruction::<WORD_SIZE>(0, u32_max - 1),
+            ASSERTVALIDDIV0Instruction::<WORD_SIZE>(0, 0),
+            ASSERTVALIDDIV0Instruction::<WORD_SIZE>(1, u32_max),
+            ASSERTVALIDDIV0Instruction::<WORD_SIZE>(u32_max, u32_max),
+            ASSERTVALIDDIV0Instruction::<WORD_SIZE>(u32_max, 0),
+            ASSERTVALIDDIV0Instruction::<WORD_SIZE>(1 << 8, u32_max),
+        ];
+        for instruction in instructions {
+            jolt_instruction_test!(instruction);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use rand::prelude::StdRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use super::{JoltInstruction, SubtableIndices};
+use crate::{
+    field::JoltField,
+    jolt::subtable::{
+        eq::EqSubtable, ltu::LtuSubtable, right_is_zero::RightIsZeroSubtable, LassoSubtable,
+    },
+    utils::instruction_utils::chunk_and_concatenate_operands,
+};
+
+/// (remainder, divisor)
+#[derive(Copy, Clone, Default, Debug, Serialize, Deserialize)]
+pub struct ASSERTVALIDUNSIGNEDREMAINDERInstruction(pub u64, pub u64);
+
+impl JoltInstruction for ASSERTVALIDUNSIGNEDREMAINDERInstruction {
+    fn operands(&self) -> (u64, u64) {
+        (self.0, self.1)
+    }
+
+    fn combine_lookups<F: JoltField>(&self, vals: &[F], C: usize, M: usize) -> F {
+        let vals_by_subtable = self.slice_values(vals, C, M);
+        let ltu = vals_by_subtable[0];
+        let eq = vals_by_subtable[1];
+        let right_is_zero = vals_by_subtable[2];
+
+        // LTU(x, y)
+        let mut sum = F::zero();
+        let mut eq_prod = F::one();
+
+        for i in 0..C {
+            sum += ltu[i] * eq_prod;
+            eq_prod *= eq[i];
+        }
+
+        // LTU(x, y) || y == 0
+        // The two cases are mutually exclusive, so we can simply add them.
+        sum + right_is_zero.iter().product::<F>()
+    }
+
+    fn g_poly_degree(&self, C: usize) -> usize {
+        C
+    }
+
+    fn subtables<F: JoltField>(
+        &self,
+        C: usize,
+        _: usize,
+    ) -> Vec<(Box<dyn LassoSubtable<F>>, SubtableIndices)> {
+        vec![
+            (Box::new(LtuSubtable::new()), SubtableIndices::from(0..C)),
+            (Box::new(EqSubtable::new()), SubtableIndices::from(0..C)),
+            (
+                Box::new(RightIsZeroSubtable::new()),
+                SubtableIndices::from(0..C),
+            ),
+        ]
+    }
+
+    fn to_indices(&self, C: usize, log_M: usize) -> Vec<usize> {
+        chunk_and_concatenate_operands(self.0, self.1, C, log_M)
+    }
+
+    fn lookup_entry(&self) -> u64 {
+        // Same for both 32-bit and 64-bit word sizes
+        let remainder = self.0;
+        let divisor = self.1;
+        (divisor == 0 || remainder < divisor).into()
+    }
+
+    fn random(&self, rng: &mut StdRng) -> Self {
+        Self(rng.next_u32() as u64, rng.next_u32() as u64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Fr;
+    use ark_std::test_rng;
+    use rand_chacha::rand_core::RngCore;
+
+    use crate::{jolt::instruction::JoltInstruction, jolt_instruction_test};
+
+    use super::ASSERTVALIDUNSIGNEDREMAINDERInstruction;
+
+    #[test]
+    fn assert_valid_unsigned_remainder_instruction_32_e2e() {
+        let mut rng = test_rng();
+        const C: usize = 4;
+        const M: usize = 1 << 16;
+
+        // Random
+        for _ in 0..256 {
+            let x = rng.next_u32() as u64;
+            let y = rng.next_u32() as u64;
+            let instruction = ASSERTVALIDUNSIGNEDREMAINDERInstruction(x, y);
+            jolt_instruction_test!(instruction);
+        }
+
+        // x == y
+        for _ in 0..256 {
+            let x = rng.next_u32() as u64;
+            jolt_instruction_test!(ASSERTVALIDUNSIGNEDREMAINDERInstruction(x, x));
+        }
+
+        // y == 0
+        for _ in 0..256 {
+            let x = rng.next_u32() as u64;
+            jolt_instruction_test!(ASSERTVALIDUNSIGNEDREMAINDERInstruction(x, 0));
+        }
+
+        // Edge-cases
+        let u32_max: u64 = u32::MAX as u64;
+        let instructions = vec![
+            ASSERTVALIDUNSIGNEDREMAINDERInstruction(100, 0),
+            ASSERTVALIDUNSIGNEDREMAINDERInstruction(0, 100),
+            ASSERTVALIDUNSIGNEDREMAINDERInstruction(1, 0),
+            ASSERTVALIDUNSIGNEDREMAINDERInstruction(0, 0),
+            ASSERTVALIDUNSIGNEDREMAINDERInstruction(0, u32_max),
+            ASSERTVALIDUNSIGNEDREMAINDERInstruction(u32_max, 0),
+            ASSERTVALIDUNSIGNEDREMAINDERInstruction(u32_max, u32_max),
+            ASSERTVALIDUNSIGNEDREMAINDERInstruction(u32_max, 1 << 8),
+            ASSERTVALIDUNSIGNEDREMAINDERInstruction(1 << 8, u32_max),
+        ];
+        for instruction in instructions {
+            jolt_instruction_test!(instruction);
+        }
+    }
+}
@@ -1,10 +1,8 @@
 use crate::field::JoltField;
-use enum_dispatch::enum_dispatch;
 use std::any::TypeId;
 use std::marker::Sync;
 use strum::{EnumCount, IntoEnumIterator};
 
-#[enum_dispatch]
 pub trait LassoSubtable<F: JoltField>: 'static + Sync {
     /// Returns the TypeId of this subtable.
     /// The `Jolt` trait has associated enum types `InstructionSet` and `Subtables`.
@@ -29,15 +27,85 @@ pub trait JoltSubtableSet<F: JoltField>:
     }
 }
 
+/// Generates an enum out of a list of LassoSubtable types, implementing `LassoSubtable<F>`
+/// for the enum by dispatching to the variant's subtable, and `JoltSubtableSet<F>`.
+///
+/// Can be invoked from other crates, which then need to depend on `strum` and `strum_macros`.
+/// To extend the RV32I subtables with custom ones, see `rv32i_subtable_enum!`.
+#[macro_export]
+macro_rules! subtable_enum {
+    ($enum_name:ident, $($alias:ident: $struct:ty),+ $(,)?) => {
+        #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+        #[repr(usize)]
+        #[derive(::strum_macros::EnumCount, ::strum_macros::EnumIter)]
+        pub enum $enum_name<F: $crate::field::JoltField> { $($alias($struct)),+ }
+
+        impl<F: $crate::field::JoltField> From<$crate::jolt::subtable::SubtableId>
+            for $enum_name<F>
+        {
+            fn from(subtable_id: $crate::jolt::subtable::SubtableId) -> Self {
+                $(
+                    if subtable_id == ::std::any::TypeId::of::<$struct>() {
+                        $enum_name::$alias(<$struct>::new())
+                    } else
+                )+
+                { panic!("Unexpected subtable id {:?}", subtable_id) } // TODO(moodlezoup): better error handling
+            }
+        }
+
+        impl<F: $crate::field::JoltField> From<$enum_name<F>> for usize {
+            fn from(subtable: $enum_name<F>) -> usize {
+                unsafe { *<*const _>::from(&subtable).cast::<usize>() }
+            }
+        }
+
+        impl<F: $crate::field::JoltField> $crate::jolt::subtable::LassoSubtable<F>
+            for $enum_name<F>
+        {
+            fn subtable_id(&self) -> $crate::jolt::subtable::SubtableId {
+                match self {
+                    $($enum_name::$alias(subtable) => {
+                        $crate::jolt::subtable::LassoSubtable::<F>::subtable_id(subtable)
+                    })+
+                }
+            }
+
+            fn materialize(&self, m: usize) -> Vec<F> {
+                match self {
+                    $($enum_name::$alias(subtable) => {
+                        $crate::jolt::subtable::LassoSubtable::<F>::materialize(subtable, m)
+                    })+
+                }
+            }
+
+            fn evaluate_mle(&self, point: &[F]) -> F {
+                match self {
+                    $($enum_name::$alias(subtable) => {
+                        $crate::jolt::subtable::LassoSubtable::<F>::evaluate_mle(subtable, point)
+                    })+
+                }
+            }
+        }
+
+        impl<F: $crate::field::JoltField> $crate::jolt::subtable::JoltSubtableSet<F>
+            for $enum_name<F>
+        {
+        }
+    };
+}
+
 pub mod and;
+pub mod div_by_zero;
 pub mod eq;
 pub mod eq_abs;
 pub mod eq_msb;
 pub mod gt_msb;
 pub mod identity;
+pub mod left_is_zero;
 pub mod lt_abs;
 pub mod ltu;
 pub mod or;
+pub mod right_is_zero;
 pub mod sign_extend;
 pub mod sll;
 pub mod sra_sign;
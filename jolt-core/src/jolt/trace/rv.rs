@@ -22,6 +22,8 @@ use crate::jolt::instruction::virtual_advice::ADVICEInstruction;
 use crate::jolt::instruction::virtual_assert_eq_signs::ASSERTEQSIGNSInstruction;
 use crate::jolt::instruction::virtual_assert_lt_abs::ASSERTLTABSInstruction;
 use crate::jolt::instruction::virtual_assert_lte::ASSERTLTEInstruction;
+use crate::jolt::instruction::virtual_assert_valid_div0::ASSERTVALIDDIV0Instruction;
+use crate::jolt::instruction::virtual_assert_valid_unsigned_remainder::ASSERTVALIDUNSIGNEDREMAINDERInstruction;
 use crate::jolt::instruction::xor::XORInstruction;
 use crate::jolt::instruction::{add::ADDInstruction, movsign::MOVSIGNInstruction};
 use crate::jolt::vm::rv32i_vm::RV32I;
@@ -86,6 +88,8 @@ impl TryFrom<&ELFInstruction> for RV32I {
             RV32IM::VIRTUAL_ASSERT_LTU => Ok(SLTUInstruction::default().into()),
             RV32IM::VIRTUAL_ASSERT_LT_ABS => Ok(ASSERTLTABSInstruction::default().into()),
             RV32IM::VIRTUAL_ASSERT_EQ_SIGNS => Ok(ASSERTEQSIGNSInstruction::default().into()),
+            RV32IM::VIRTUAL_ASSERT_VALID_UNSIGNED_REMAINDER => Ok(ASSERTVALIDUNSIGNEDREMAINDERInstruction::default().into()),
+            RV32IM::VIRTUAL_ASSERT_VALID_DIV0 => Ok(ASSERTVALIDDIV0Instruction::default().into()),
 
             _ => Err("No corresponding RV32I instruction")
         }
@@ -144,14 +148,16 @@ impl TryFrom<&RVTraceRow> for RV32I {
             RV32IM::MULU => Ok(MULUInstruction(row.register_state.rs1_val.unwrap(), row.register_state.rs2_val.unwrap()).into()),
             RV32IM::MULHU => Ok(MULHUInstruction(row.register_state.rs1_val.unwrap(), row.register_state.rs2_val.unwrap()).into()),
 
-            // TODO: Input of Advice is not from register
-            RV32IM::VIRTUAL_ADVICE => Ok(ADVICEInstruction(row.register_state.rs1_val.unwrap()).into()),
+            // Advice is supplied by the prover and written directly to rd
+            RV32IM::VIRTUAL_ADVICE => Ok(ADVICEInstruction(row.register_state.rd_post_val.unwrap()).into()),
             RV32IM::VIRTUAL_MOVSIGN => Ok(MOVSIGNInstruction(row.register_state.rs1_val.unwrap()).into()),
             RV32IM::VIRTUAL_ASSERT_EQ => Ok(BEQInstruction(row.register_state.rs1_val.unwrap(), row.register_state.rs2_val.unwrap()).into()),
             RV32IM::VIRTUAL_ASSERT_LTE => Ok(ASSERTLTEInstruction(row.register_state.rs1_val.unwrap(), row.register_state.rs2_val.unwrap()).into()),
             RV32IM::VIRTUAL_ASSERT_LTU => Ok(SLTUInstruction(row.register_state.rs1_val.unwrap(), row.register_state.rs2_val.unwrap()).into()),
             RV32IM::VIRTUAL_ASSERT_LT_ABS => Ok(ASSERTLTABSInstruction(row.register_state.rs1_val.unwrap(), row.register_state.rs2_val.unwrap()).into()),
             RV32IM::VIRTUAL_ASSERT_EQ_SIGNS => Ok(ASSERTEQSIGNSInstruction(row.register_state.rs1_val.unwrap(), row.register_state.rs2_val.unwrap()).into()),
+            RV32IM::VIRTUAL_ASSERT_VALID_UNSIGNED_REMAINDER => Ok(ASSERTVALIDUNSIGNEDREMAINDERInstruction(row.register_state.rs1_val.unwrap(), row.register_state.rs2_val.unwrap()).into()),
+            RV32IM::VIRTUAL_ASSERT_VALID_DIV0 => Ok(ASSERTVALIDDIV0Instruction(row.register_state.rs1_val.unwrap(), row.register_state.rs2_val.unwrap()).into()),
 
             _ => Err("No corresponding RV32I instruction")
         }
@@ -2,7 +2,10 @@ use ark_ff::Zero;
 use rand::rngs::StdRng;
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, marker::PhantomData};
+use std::{
+    collections::{BTreeMap, HashMap},
+    marker::PhantomData,
+};
 
 use crate::field::JoltField;
 use crate::jolt::instruction::JoltInstructionSet;
@@ -10,8 +13,8 @@ use crate::poly::commitment::commitment_scheme::{BatchType, CommitShape, Commitm
 use crate::poly::eq_poly::EqPolynomial;
 use crate::utils::transcript::{AppendToTranscript, ProofTranscript};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use common::constants::{BYTES_PER_INSTRUCTION, RAM_START_ADDRESS, REGISTER_COUNT};
-use common::rv_trace::ELFInstruction;
+use common::constants::{INSTRUCTION_ALIGNMENT, RAM_START_ADDRESS, REGISTER_COUNT};
+use common::rv_trace::{ELFInstruction, NUM_CIRCUIT_FLAGS};
 use common::to_ram_address;
 
 use rayon::prelude::*;
@@ -23,7 +26,7 @@ use crate::{
         identity_poly::IdentityPolynomial,
         structured_poly::{StructuredCommitment, StructuredOpeningProof},
     },
-    utils::errors::ProofVerifyError,
+    utils::{errors::ProofVerifyError, thread::drop_in_background_thread},
 };
 
 use super::JoltTraceStep;
@@ -50,6 +53,8 @@ pub struct BytecodeRow {
     rs2: u64,
     /// "Immediate" value for this instruction (0 if unused).
     imm: u64,
+    /// Index of this row within the virtual sequence of the instruction at `address`, if any.
+    virtual_sequence_index: Option<usize>,
 }
 
 impl BytecodeRow {
@@ -61,6 +66,7 @@ impl BytecodeRow {
             rs1,
             rs2,
             imm,
+            virtual_sequence_index: None,
         }
     }
 
@@ -72,9 +78,15 @@ impl BytecodeRow {
             rs1: 0,
             rs2: 0,
             imm: 0,
+            virtual_sequence_index: None,
         }
     }
 
+    /// Memory address as read from the ELF, shared by all the steps of a virtual sequence.
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
     pub fn random(index: usize, rng: &mut StdRng) -> Self {
         Self {
             address: to_ram_address(index),
@@ -83,6 +95,7 @@ impl BytecodeRow {
             rs1: rng.next_u64() % REGISTER_COUNT,
             rs2: rng.next_u64() % REGISTER_COUNT,
             imm: rng.next_u64() % (1 << 20), // U-format instructions have 20-bit imm values
+            virtual_sequence_index: None,
         }
     }
 
@@ -125,8 +138,33 @@ impl BytecodeRow {
             rs1: instruction.rs1.unwrap_or(0),
             rs2: instruction.rs2.unwrap_or(0),
             imm: instruction.imm.unwrap_or(0) as u64, // imm is always cast to its 32-bit repr, signed or unsigned
+            virtual_sequence_index: instruction.virtual_sequence_index,
         }
     }
+
+    /// Rows of consecutive `instructions`, e.g. those of a virtual sequence. Sets circuit flag
+    /// 12 of each row that follows a row which does not update the PC, so that the R1CS
+    /// constraints can tell the first row of a virtual sequence apart from the ones continuing it.
+    pub fn from_instructions<'a, InstructionSet>(
+        instructions: impl IntoIterator<Item = &'a ELFInstruction>,
+    ) -> Vec<Self>
+    where
+        InstructionSet: JoltInstructionSet,
+    {
+        let continues_sequence_bit = 1 << (InstructionSet::COUNT + NUM_CIRCUIT_FLAGS - 1 - 12);
+        let mut continues_sequence = false;
+        instructions
+            .into_iter()
+            .map(|instruction| {
+                let mut row = Self::from_instruction::<InstructionSet>(instruction);
+                if continues_sequence {
+                    row.bitflags |= continues_sequence_bit;
+                }
+                continues_sequence = instruction.to_circuit_flags()[9];
+                row
+            })
+            .collect()
+    }
 }
 
 pub fn random_bytecode_trace(
@@ -156,7 +194,7 @@ pub struct BytecodePolynomials<F: JoltField, C: CommitmentScheme<Field = F>> {
     pub(super) t_final: DensePolynomial<F>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct BytecodePreprocessing<F: JoltField> {
     /// Size of the (padded) bytecode.
     code_size: usize,
@@ -164,25 +202,30 @@ pub struct BytecodePreprocessing<F: JoltField> {
     /// the initial memory values. There are six values (address, bitflags, rd, rs1, rs2, imm)
     /// associated with each memory address, so `v_init_final` comprises five polynomials.
     v_init_final: [DensePolynomial<F>; 6],
-    /// Maps the memory address of each instruction in the bytecode to its "virtual" address.
+    /// Maps the memory address of each instruction in the bytecode, along with its index within
+    /// its virtual sequence, to its "virtual" address.
     /// See Section 6.1 of the Jolt paper, "Reflecting the program counter". The virtual address
     /// is the one used to keep track of the next (potentially virtual) instruction to execute.
-    virtual_address_map: HashMap<usize, usize>,
+    /// Only used by the prover, so it is empty in a verifier key.
+    virtual_address_map: BTreeMap<(usize, Option<usize>), usize>,
 }
 
 impl<F: JoltField> BytecodePreprocessing<F> {
     #[tracing::instrument(skip_all, name = "BytecodePreprocessing::preprocess")]
     pub fn preprocess(mut bytecode: Vec<BytecodeRow>) -> Self {
-        let mut virtual_address_map = HashMap::new();
+        let mut virtual_address_map = BTreeMap::new();
         let mut virtual_address = 1; // Account for no-op instruction prepended to bytecode
         for instruction in bytecode.iter_mut() {
             assert!(instruction.address >= RAM_START_ADDRESS as usize);
-            assert!(instruction.address % BYTES_PER_INSTRUCTION == 0);
+            assert!(instruction.address % INSTRUCTION_ALIGNMENT == 0);
             // Compress instruction address for more efficient commitment:
             instruction.address =
-                1 + (instruction.address - RAM_START_ADDRESS as usize) / BYTES_PER_INSTRUCTION;
+                1 + (instruction.address - RAM_START_ADDRESS as usize) / INSTRUCTION_ALIGNMENT;
             assert_eq!(
-                virtual_address_map.insert(instruction.address, virtual_address),
+                virtual_address_map.insert(
+                    (instruction.address, instruction.virtual_sequence_index),
+                    virtual_address
+                ),
                 None
             );
             virtual_address += 1;
@@ -190,7 +233,7 @@ impl<F: JoltField> BytecodePreprocessing<F> {
 
         // Bytecode: Prepend a single no-op instruction
         bytecode.insert(0, BytecodeRow::no_op(0));
-        assert_eq!(virtual_address_map.insert(0, 0), None);
+        assert_eq!(virtual_address_map.insert((0, None), 0), None);
 
         // Bytecode: Pad to nearest power of 2
         let code_size = bytecode.len().next_power_of_two();
@@ -227,6 +270,15 @@ impl<F: JoltField> BytecodePreprocessing<F> {
             virtual_address_map,
         }
     }
+
+    /// Copy of this preprocessing without the parts only the prover uses.
+    pub(crate) fn verifier_preprocessing(&self) -> Self {
+        Self {
+            code_size: self.code_size,
+            v_init_final: self.v_init_final.clone(),
+            virtual_address_map: BTreeMap::new(),
+        }
+    }
 }
 
 impl<F: JoltField, C: CommitmentScheme<Field = F>> BytecodePolynomials<F, C> {
@@ -244,16 +296,19 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> BytecodePolynomials<F, C> {
         for (step_index, step) in trace.iter_mut().enumerate() {
             if !step.bytecode_row.address.is_zero() {
                 assert!(step.bytecode_row.address >= RAM_START_ADDRESS as usize);
-                assert!(step.bytecode_row.address % BYTES_PER_INSTRUCTION == 0);
+                assert!(step.bytecode_row.address % INSTRUCTION_ALIGNMENT == 0);
                 // Compress instruction address for more efficient commitment:
                 step.bytecode_row.address = 1
                     + (step.bytecode_row.address - RAM_START_ADDRESS as usize)
-                        / BYTES_PER_INSTRUCTION;
+                        / INSTRUCTION_ALIGNMENT;
             }
 
             let virtual_address = preprocessing
                 .virtual_address_map
-                .get(&step.bytecode_row.address)
+                .get(&(
+                    step.bytecode_row.address,
+                    step.bytecode_row.virtual_sequence_index,
+                ))
                 .unwrap();
             a_read_write_usize[step_index] = *virtual_address;
             let counter = final_cts[*virtual_address];
@@ -299,6 +354,22 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> BytecodePolynomials<F, C> {
         }
     }
 
+    /// Frees the polynomials only read by the bytecode memory-checking proof. The register
+    /// operands (rd, rs1, rs2) in `v_read_write` are kept, since the read-write memory proof
+    /// opens them as the register addresses.
+    pub fn free_memory_checking_polys(&mut self) {
+        let [address, bitflags, _, _, _, imm] = &mut self.v_read_write;
+        let freed = [
+            std::mem::take(&mut self.a_read_write),
+            std::mem::take(address),
+            std::mem::take(bitflags),
+            std::mem::take(imm),
+            std::mem::take(&mut self.t_read),
+            std::mem::take(&mut self.t_final),
+        ];
+        drop_in_background_thread(freed);
+    }
+
     #[tracing::instrument(skip_all, name = "BytecodePolynomials::get_polys_r1cs")]
     pub fn get_polys_r1cs(&self) -> (Vec<F>, Vec<F>) {
         let (a_read_write, v_read_write) = rayon::join(
@@ -311,16 +382,19 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> BytecodePolynomials<F, C> {
 
     #[tracing::instrument(skip_all, name = "BytecodePolynomials::validate_bytecode")]
     pub fn validate_bytecode(bytecode: &[BytecodeRow], trace: &[BytecodeRow]) {
-        let mut bytecode_map: HashMap<usize, &BytecodeRow> = HashMap::new();
+        let mut bytecode_map: HashMap<(usize, Option<usize>), &BytecodeRow> = HashMap::new();
 
         for bytecode_row in bytecode.iter() {
-            bytecode_map.insert(bytecode_row.address, bytecode_row);
+            bytecode_map.insert(
+                (bytecode_row.address, bytecode_row.virtual_sequence_index),
+                bytecode_row,
+            );
         }
 
         for trace_row in trace {
             assert_eq!(
                 **bytecode_map
-                    .get(&trace_row.address)
+                    .get(&(trace_row.address, trace_row.virtual_sequence_index))
                     .expect("couldn't find in bytecode"),
                 *trace_row
             );
@@ -334,10 +408,10 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> BytecodePolynomials<F, C> {
         let max_trace_length = max_trace_length.next_power_of_two();
 
         // a_read_write, t_read, v_read_write (address, opcode, rs1, rs2, rd, imm)
-        let read_write_gen_shape = CommitShape::new(max_trace_length, BatchType::Big);
+        let read_write_gen_shape = CommitShape::new(max_trace_length, BatchType::Big, 8);
 
         // t_final
-        let init_final_gen_shape = CommitShape::new(max_bytecode_size, BatchType::Small);
+        let init_final_gen_shape = CommitShape::new(max_bytecode_size, BatchType::Small, 1);
 
         vec![read_write_gen_shape, init_final_gen_shape]
     }
@@ -655,7 +729,7 @@ where
 
     fn verify_openings(
         &self,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &BytecodeCommitment<C>,
         opening_point: &[F],
@@ -738,7 +812,7 @@ where
 
     fn verify_openings(
         &self,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &BytecodeCommitment<C>,
         opening_point: &[F],
@@ -9,7 +9,7 @@ use tracing::trace_span;
 use crate::field::JoltField;
 use crate::jolt::instruction::{JoltInstructionSet, SubtableIndices};
 use crate::jolt::subtable::JoltSubtableSet;
-use crate::lasso::memory_checking::MultisetHashes;
+use crate::lasso::memory_checking::{check_hash_count, MultisetHashes};
 use crate::poly::commitment::commitment_scheme::{BatchType, CommitShape, CommitmentScheme};
 use crate::utils::mul_0_1_optimized;
 use crate::{
@@ -23,7 +23,7 @@ use crate::{
     },
     subprotocols::sumcheck::SumcheckInstanceProof,
     utils::{
-        errors::ProofVerifyError,
+        errors::{ProofVerifyError, VerificationStage},
         math::Math,
         transcript::{AppendToTranscript, ProofTranscript},
     },
@@ -70,6 +70,26 @@ where
     pub lookup_outputs: DensePolynomial<F>,
 }
 
+// Not derived, as that would require `C: Default`
+impl<F, C> Default for InstructionPolynomials<F, C>
+where
+    F: JoltField,
+    C: CommitmentScheme<Field = F>,
+{
+    fn default() -> Self {
+        Self {
+            _marker: PhantomData,
+            dim: vec![],
+            read_cts: vec![],
+            final_cts: vec![],
+            E_polys: vec![],
+            instruction_flag_polys: vec![],
+            instruction_flag_bitvectors: vec![],
+            lookup_outputs: DensePolynomial::default(),
+        }
+    }
+}
+
 /// Commitments to BatchedInstructionPolynomials.
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
 pub struct InstructionCommitment<C: CommitmentScheme> {
@@ -177,7 +197,7 @@ where
 
     fn verify_openings(
         &self,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &InstructionCommitment<C>,
         opening_point: &[F],
@@ -298,7 +318,7 @@ where
 
     fn verify_openings(
         &self,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &InstructionCommitment<C>,
         opening_point: &[F],
@@ -400,7 +420,7 @@ where
 
     fn verify_openings(
         &self,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &InstructionCommitment<C>,
         opening_point: &[F],
@@ -547,12 +567,12 @@ where
         preprocessing: &InstructionLookupsPreprocessing<F>,
         read_write_hashes: Vec<F>,
         init_final_hashes: Vec<F>,
-    ) -> MultisetHashes<F> {
-        assert_eq!(read_write_hashes.len(), 2 * preprocessing.num_memories);
-        assert_eq!(
+    ) -> Result<MultisetHashes<F>, ProofVerifyError> {
+        check_hash_count(2 * preprocessing.num_memories, read_write_hashes.len())?;
+        check_hash_count(
+            Self::NUM_SUBTABLES + preprocessing.num_memories,
             init_final_hashes.len(),
-            Self::NUM_SUBTABLES + preprocessing.num_memories
-        );
+        )?;
 
         let mut read_hashes = Vec::with_capacity(preprocessing.num_memories);
         let mut write_hashes = Vec::with_capacity(preprocessing.num_memories);
@@ -574,46 +594,34 @@ where
             }
         }
 
-        MultisetHashes {
+        Ok(MultisetHashes {
             read_hashes,
             write_hashes,
             init_hashes,
             final_hashes,
-        }
+        })
     }
 
     fn check_multiset_equality(
         preprocessing: &InstructionLookupsPreprocessing<F>,
         multiset_hashes: &MultisetHashes<F>,
-    ) {
-        assert_eq!(multiset_hashes.init_hashes.len(), Self::NUM_SUBTABLES);
-        assert_eq!(
-            multiset_hashes.read_hashes.len(),
-            preprocessing.num_memories
-        );
-        assert_eq!(
-            multiset_hashes.write_hashes.len(),
-            preprocessing.num_memories
-        );
-        assert_eq!(
-            multiset_hashes.final_hashes.len(),
-            preprocessing.num_memories
-        );
+    ) -> Result<(), ProofVerifyError> {
+        multiset_hashes.check_counts(
+            preprocessing.num_memories,
+            Self::NUM_SUBTABLES,
+            preprocessing.num_memories,
+        )?;
 
-        (0..preprocessing.num_memories)
-            .into_par_iter()
-            .for_each(|i| {
-                let read_hash = multiset_hashes.read_hashes[i];
-                let write_hash = multiset_hashes.write_hashes[i];
-                let init_hash =
-                    multiset_hashes.init_hashes[preprocessing.memory_to_subtable_index[i]];
-                let final_hash = multiset_hashes.final_hashes[i];
-                assert_eq!(
-                    init_hash * write_hash,
-                    final_hash * read_hash,
-                    "Multiset hashes don't match"
-                );
-            });
+        for i in 0..preprocessing.num_memories {
+            let read_hash = multiset_hashes.read_hashes[i];
+            let write_hash = multiset_hashes.write_hashes[i];
+            let init_hash = multiset_hashes.init_hashes[preprocessing.memory_to_subtable_index[i]];
+            let final_hash = multiset_hashes.final_hashes[i];
+            if init_hash * write_hash != final_hash * read_hash {
+                return Err(VerificationStage::MultisetHashEquality { memory: i }.into());
+            }
+        }
+        Ok(())
     }
 
     fn protocol_name() -> &'static [u8] {
@@ -715,14 +723,15 @@ pub struct PrimarySumcheck<F: JoltField, CS: CommitmentScheme<Field = F>> {
     opening_proof: CS::BatchedProof,
 }
 
-#[derive(Clone)]
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct InstructionLookupsPreprocessing<F: JoltField> {
     subtable_to_memory_indices: Vec<Vec<usize>>, // Vec<Range<usize>>?
     instruction_to_memory_indices: Vec<Vec<usize>>,
     memory_to_subtable_index: Vec<usize>,
     memory_to_dimension_index: Vec<usize>,
+    /// Only used by the prover, so it is empty in a verifier key.
     materialized_subtables: Vec<Vec<F>>,
-    num_memories: usize,
+    pub(crate) num_memories: usize,
 }
 
 impl<F: JoltField> InstructionLookupsPreprocessing<F> {
@@ -794,6 +803,18 @@ impl<F: JoltField> InstructionLookupsPreprocessing<F> {
         }
         subtables
     }
+
+    /// Copy of this preprocessing without the parts only the prover uses.
+    pub(crate) fn verifier_preprocessing(&self) -> Self {
+        Self {
+            subtable_to_memory_indices: self.subtable_to_memory_indices.clone(),
+            instruction_to_memory_indices: self.instruction_to_memory_indices.clone(),
+            memory_to_subtable_index: self.memory_to_subtable_index.clone(),
+            memory_to_dimension_index: self.memory_to_dimension_index.clone(),
+            materialized_subtables: vec![],
+            num_memories: self.num_memories,
+        }
+    }
 }
 
 impl<F, CS, InstructionSet, Subtables, const C: usize, const M: usize>
@@ -869,7 +890,7 @@ where
 
     pub fn verify(
         preprocessing: &InstructionLookupsPreprocessing<F>,
-        generators: &CS::Setup,
+        generators: &CS::VerifierSetup,
         proof: InstructionLookupsProof<C, M, F, CS, InstructionSet, Subtables>,
         commitment: &InstructionCommitment<CS>,
         transcript: &mut ProofTranscript,
@@ -891,24 +912,28 @@ where
 
         // Verify that eq(r, r_z) * [f_1(r_z) * g(E_1(r_z)) + ... + f_F(r_z) * E_F(r_z))] = claim_last
         let eq_eval = EqPolynomial::new(r_eq.to_vec()).evaluate(&r_primary_sumcheck);
-        assert_eq!(
-            eq_eval
-                * (Self::combine_lookups(
-                    preprocessing,
-                    &proof.primary_sumcheck.openings.E_poly_openings,
-                    &proof.primary_sumcheck.openings.flag_openings,
-                ) - proof.primary_sumcheck.openings.lookup_outputs_opening),
-            claim_last,
-            "Primary sumcheck check failed."
-        );
+        if eq_eval
+            * (Self::combine_lookups(
+                preprocessing,
+                &proof.primary_sumcheck.openings.E_poly_openings,
+                &proof.primary_sumcheck.openings.flag_openings,
+            )? - proof.primary_sumcheck.openings.lookup_outputs_opening)
+            != claim_last
+        {
+            return Err(VerificationStage::SumcheckFinalClaim.into());
+        }
 
-        proof.primary_sumcheck.openings.verify_openings(
-            generators,
-            &proof.primary_sumcheck.opening_proof,
-            commitment,
-            &r_primary_sumcheck,
-            transcript,
-        )?;
+        proof
+            .primary_sumcheck
+            .openings
+            .verify_openings(
+                generators,
+                &proof.primary_sumcheck.opening_proof,
+                commitment,
+                &r_primary_sumcheck,
+                transcript,
+            )
+            .map_err(VerificationStage::opening_proof)?;
 
         Self::verify_memory_checking(
             preprocessing,
@@ -1278,9 +1303,15 @@ where
         preprocessing: &InstructionLookupsPreprocessing<F>,
         vals: &[F],
         flags: &[F],
-    ) -> F {
-        assert_eq!(vals.len(), preprocessing.num_memories);
-        assert_eq!(flags.len(), Self::NUM_INSTRUCTIONS);
+    ) -> Result<F, ProofVerifyError> {
+        for (expected, actual) in [
+            (preprocessing.num_memories, vals.len()),
+            (Self::NUM_INSTRUCTIONS, flags.len()),
+        ] {
+            if expected != actual {
+                return Err(VerificationStage::OpeningCount { expected, actual }.into());
+            }
+        }
 
         let mut sum = F::zero();
         for instruction in InstructionSet::iter() {
@@ -1290,7 +1321,7 @@ where
             sum += flags[instruction_index] * instruction.combine_lookups(&filtered_operands, C, M);
         }
 
-        sum
+        Ok(sum)
     }
 
     /// Converts instruction flag values into memory flag values. A memory flag value
@@ -1385,11 +1416,14 @@ where
     ) -> Vec<CommitShape> {
         let max_trace_length = max_trace_length.next_power_of_two();
         // { dim, read_cts, E_polys, instruction_flag_polys, lookup_outputs }
-        let read_write_generator_shape = CommitShape::new(max_trace_length, BatchType::Big);
-        let init_final_generator_shape = CommitShape::new(
-            M * preprocessing.num_memories.next_power_of_two(),
-            BatchType::Small,
+        let read_write_generator_shape = CommitShape::new(
+            max_trace_length,
+            BatchType::Big,
+            C + 2 * preprocessing.num_memories + Self::NUM_INSTRUCTIONS + 1,
         );
+        // final_cts
+        let init_final_generator_shape =
+            CommitShape::new(M, BatchType::Big, preprocessing.num_memories);
 
         vec![read_write_generator_shape, init_final_generator_shape]
     }
@@ -1412,3 +1446,31 @@ where
         b"Jolt instruction lookups"
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::jolt::vm::rv32i_vm::{RV32ISubtables, C, M, RV32I};
+    use crate::poly::commitment::hyrax::HyraxScheme;
+    use ark_bn254::{Fr, G1Projective};
+
+    type RV32ILookupsProof =
+        InstructionLookupsProof<C, M, Fr, HyraxScheme<G1Projective>, RV32I, RV32ISubtables<Fr>>;
+
+    #[test]
+    fn combine_lookups_rejects_missing_openings() {
+        let preprocessing =
+            InstructionLookupsPreprocessing::preprocess::<C, M, RV32I, RV32ISubtables<Fr>>();
+        let vals = vec![Fr::from(1u64); preprocessing.num_memories];
+        let flags = vec![Fr::from(1u64); RV32ILookupsProof::NUM_INSTRUCTIONS];
+        assert!(RV32ILookupsProof::combine_lookups(&preprocessing, &vals, &flags).is_ok());
+
+        let result =
+            RV32ILookupsProof::combine_lookups(&preprocessing, &vals, &flags[..flags.len() - 1]);
+        assert!(matches!(
+            result,
+            Err(ProofVerifyError::Rejected(VerificationStage::OpeningCount { expected, actual }))
+                if expected == flags.len() && actual == flags.len() - 1
+        ));
+    }
+}
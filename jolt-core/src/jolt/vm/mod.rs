@@ -6,33 +6,45 @@ use ark_std::log2;
 use common::constants::RAM_START_ADDRESS;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
 use strum::EnumCount;
 
+#[cfg(feature = "host")]
+use crate::host::Program;
 use crate::jolt::vm::timestamp_range_check::RangeCheckPolynomials;
 use crate::jolt::{
     instruction::JoltInstruction, subtable::JoltSubtableSet,
     vm::timestamp_range_check::TimestampValidityProof,
 };
 use crate::lasso::memory_checking::{MemoryCheckingProver, MemoryCheckingVerifier};
-use crate::poly::commitment::commitment_scheme::{BatchType, CommitmentScheme};
+use crate::poly::commitment::commitment_scheme::{BatchType, CommitShape, CommitmentScheme};
 use crate::poly::dense_mlpoly::DensePolynomial;
 use crate::poly::structured_poly::StructuredCommitment;
+use crate::r1cs::constraints::{next_pc_wire_value, R1CSBuilder};
+use crate::r1cs::r1cs_shape::R1CSShape;
 use crate::r1cs::snark::{R1CSCommitment, R1CSInputs, R1CSProof};
-use crate::r1cs::spartan::UniformSpartanKey;
-use crate::utils::errors::ProofVerifyError;
+use crate::r1cs::spartan::{UniformShapeBuilder, UniformSpartanKey};
+#[cfg(feature = "host")]
+use crate::utils::errors::HostError;
+use crate::utils::errors::{ProofVerifyError, SubProtocol};
+use crate::utils::index_to_field_bitvector;
+use crate::utils::profiling::PeakMemoryMonitor;
 use crate::utils::thread::{drop_in_background_thread, unsafe_allocate_zero_vec};
 use crate::utils::transcript::{AppendToTranscript, ProofTranscript};
 use common::{
     constants::MEMORY_OPS_PER_INSTRUCTION,
-    rv_trace::{ELFInstruction, JoltDevice, MemoryOp},
+    rv_trace::{ELFInstruction, JoltDevice, MemoryLayout, MemoryOp, NUM_CIRCUIT_FLAGS},
 };
 
 use self::bytecode::BytecodePreprocessing;
 use self::instruction_lookups::{
     InstructionCommitment, InstructionLookupsPreprocessing, InstructionLookupsProof,
 };
+use self::observer::{observe_phase, ProverEvent, ProverObserver, ProverPhase};
 use self::read_write_memory::{
-    MemoryCommitment, ReadWriteMemory, ReadWriteMemoryPreprocessing, ReadWriteMemoryProof,
+    memory_size, InitialMemory, MemoryCommitment, ReadWriteMemory, ReadWriteMemoryPreprocessing,
+    ReadWriteMemoryProof,
 };
 use self::timestamp_range_check::RangeCheckCommitment;
 use self::{
@@ -40,10 +52,14 @@ use self::{
     instruction_lookups::InstructionPolynomials,
 };
 
-use super::instruction::JoltInstructionSet;
+use super::instruction::{JoltInstructionSet, MAX_INSTRUCTION_SET_SIZE};
 
-#[derive(Clone)]
-pub struct JoltPreprocessing<F, PCS>
+/// How often the segmented provers sample the process's memory usage to report its peak.
+const MEMORY_SAMPLING_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Preprocessing of a program needed to prove its executions.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct JoltProverKey<F, PCS>
 where
     F: JoltField,
     PCS: CommitmentScheme<Field = F>,
@@ -52,6 +68,68 @@ where
     pub instruction_lookups: InstructionLookupsPreprocessing<F>,
     pub bytecode: BytecodePreprocessing<F>,
     pub read_write_memory: ReadWriteMemoryPreprocessing,
+    pub memory_layout: MemoryLayout,
+    /// R1CS constraints of a single step, from which the Spartan key is derived.
+    pub r1cs_shape: R1CSShape<F>,
+    /// Length of the longest trace the generators support.
+    pub max_trace_length: usize,
+}
+
+impl<F, PCS> JoltProverKey<F, PCS>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<Field = F>,
+{
+    /// The verifier's part of this key, which leaves out the prover's part of the setup, the
+    /// materialized subtables and the bytecode's virtual address map.
+    pub fn verifier_key(&self) -> JoltVerifierKey<F, PCS> {
+        JoltVerifierKey {
+            generators: PCS::verifier_setup(&self.generators),
+            instruction_lookups: self.instruction_lookups.verifier_preprocessing(),
+            bytecode: self.bytecode.verifier_preprocessing(),
+            read_write_memory: self.read_write_memory.clone(),
+            memory_layout: self.memory_layout.clone(),
+            r1cs_shape: self.r1cs_shape.clone(),
+            max_trace_length: self.max_trace_length,
+        }
+    }
+
+    /// The uniform Spartan key for a trace padded to `padded_trace_length` steps.
+    fn spartan_key(&self, padded_trace_length: usize) -> UniformSpartanKey<F> {
+        UniformSpartanKey::new(self.r1cs_shape.clone(), padded_trace_length)
+    }
+}
+
+/// Preprocessing of a program needed to verify proofs of its executions. It doesn't depend
+/// on any proof, so it can be serialized and reused across proofs.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct JoltVerifierKey<F, PCS>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<Field = F>,
+{
+    pub generators: PCS::VerifierSetup,
+    pub instruction_lookups: InstructionLookupsPreprocessing<F>,
+    pub bytecode: BytecodePreprocessing<F>,
+    pub read_write_memory: ReadWriteMemoryPreprocessing,
+    pub memory_layout: MemoryLayout,
+    /// R1CS constraints of a single step, from which the verifier derives the Spartan key
+    /// rather than trusting one supplied by the prover.
+    pub r1cs_shape: R1CSShape<F>,
+    /// Length of the longest trace the generators support. Proofs claiming a longer trace are
+    /// rejected before the Spartan key is derived from the claimed length.
+    pub max_trace_length: usize,
+}
+
+impl<F, PCS> JoltVerifierKey<F, PCS>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<Field = F>,
+{
+    /// The uniform Spartan key for a trace padded to `padded_trace_length` steps.
+    fn spartan_key(&self, padded_trace_length: usize) -> UniformSpartanKey<F> {
+        UniformSpartanKey::new(self.r1cs_shape.clone(), padded_trace_length)
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -83,6 +161,26 @@ impl<InstructionSet: JoltInstructionSet> JoltTraceStep<InstructionSet> {
         let padded_length = unpadded_length.next_power_of_two();
         trace.resize(padded_length, Self::no_op());
     }
+
+    /// The circuit flags of each step of `trace`, flag-major and padded to the next power of two.
+    pub fn circuit_flags<F: JoltField>(trace: &[Self]) -> Vec<F> {
+        let padded_trace_len = trace.len().next_power_of_two();
+
+        let mut circuit_flag_trace = unsafe_allocate_zero_vec(padded_trace_len * NUM_CIRCUIT_FLAGS);
+        circuit_flag_trace
+            .par_chunks_mut(padded_trace_len)
+            .enumerate()
+            .for_each(|(flag_index, chunk)| {
+                chunk.iter_mut().zip(trace.iter()).for_each(|(flag, row)| {
+                    let packed_circuit_flags = row.bytecode_row.bitflags >> InstructionSet::COUNT;
+                    // Check if the flag is set in the packed representation
+                    if (packed_circuit_flags >> (NUM_CIRCUIT_FLAGS - flag_index - 1)) & 1 != 0 {
+                        *flag = F::one();
+                    }
+                });
+            });
+        circuit_flag_trace
+    }
 }
 
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
@@ -99,6 +197,308 @@ where
     pub read_write_memory: ReadWriteMemoryProof<F, PCS>,
     pub instruction_lookups: InstructionLookupsProof<C, M, F, PCS, InstructionSet, Subtables>,
     pub r1cs: R1CSProof<F, PCS>,
+    /// Present in the segments of a chain of two or more, absent in standalone proofs.
+    pub segment_boundary: Option<SegmentBoundary<F, PCS>>,
+}
+
+impl<const C: usize, const M: usize, F, PCS, InstructionSet, Subtables>
+    JoltProof<C, M, F, PCS, InstructionSet, Subtables>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<Field = F>,
+    InstructionSet: JoltInstructionSet,
+    Subtables: JoltSubtableSet<F>,
+{
+    /// Compressed byte size of each component of the proof.
+    pub fn size(&self) -> JoltProofSize {
+        let read_write_memory = &self.read_write_memory;
+        JoltProofSize {
+            program_io: self.trace_length.compressed_size() + self.program_io.compressed_size(),
+            bytecode: self.bytecode.compressed_size(),
+            read_write_memory: read_write_memory.memory_checking_proof.compressed_size(),
+            output_sumcheck: read_write_memory.output_proof.compressed_size(),
+            timestamp_range_check: read_write_memory.timestamp_validity_proof.compressed_size(),
+            instruction_lookups: self.instruction_lookups.compressed_size(),
+            r1cs: self.r1cs.compressed_size(),
+            segment_boundary: self.segment_boundary.compressed_size(),
+        }
+    }
+}
+
+/// The position of a proof in a chain of segment proofs (see [`Jolt::prove_segmented`]). A
+/// standalone proof is both the first and the last segment of its chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SegmentPosition {
+    pub first: bool,
+    pub last: bool,
+}
+
+impl SegmentPosition {
+    pub const STANDALONE: Self = Self {
+        first: true,
+        last: true,
+    };
+
+    pub fn new(index: usize, num_segments: usize) -> Self {
+        Self {
+            first: index == 0,
+            last: index + 1 == num_segments,
+        }
+    }
+}
+
+/// The PCs at which a segment of a chain starts and ends, opened from the commitments to the
+/// state of its first step and to the next PC wire of its last step.
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct SegmentBoundary<F: JoltField, PCS: CommitmentScheme<Field = F>> {
+    /// State the segment's first step starts in (see `step_state`): its compressed PC, unless
+    /// the segment starts within a virtual sequence.
+    pub entry_pc: F,
+    /// Next PC wire of the segment's last step (see `next_pc_wire_value`).
+    pub exit_pc: F,
+    entry_proof: PCS::BatchedProof,
+    exit_proof: PCS::BatchedProof,
+}
+
+impl<F: JoltField, PCS: CommitmentScheme<Field = F>> SegmentBoundary<F, PCS> {
+    fn prove(
+        generators: &PCS::Setup,
+        trace_length: usize,
+        state: &[F],
+        next_pc: &[F],
+        transcript: &mut ProofTranscript,
+    ) -> Self {
+        let state = DensePolynomial::new(state.to_vec());
+        let next_pc = DensePolynomial::new(next_pc.to_vec());
+        let entry_point = Self::step_point(0, trace_length);
+        let exit_point = Self::step_point(trace_length - 1, trace_length);
+        let entry_pc = state.evaluate(&entry_point);
+        let exit_pc = next_pc.evaluate(&exit_point);
+        transcript.append_scalar(b"entry_pc", &entry_pc);
+        transcript.append_scalar(b"exit_pc", &exit_pc);
+
+        let entry_proof = PCS::batch_prove(
+            generators,
+            &[&state],
+            &entry_point,
+            &[entry_pc],
+            BatchType::Big,
+            transcript,
+        );
+        let exit_proof = PCS::batch_prove(
+            generators,
+            &[&next_pc],
+            &exit_point,
+            &[exit_pc],
+            BatchType::Big,
+            transcript,
+        );
+        Self {
+            entry_pc,
+            exit_pc,
+            entry_proof,
+            exit_proof,
+        }
+    }
+
+    fn verify<InstructionSet: JoltInstructionSet>(
+        &self,
+        generators: &PCS::VerifierSetup,
+        spartan_key: &UniformSpartanKey<F>,
+        trace_length: usize,
+        commitments: &JoltCommitments<PCS>,
+        transcript: &mut ProofTranscript,
+    ) -> Result<(), ProofVerifyError> {
+        // A segment has a first and a last step to open the boundary at
+        if trace_length == 0 {
+            return Err(ProofVerifyError::SegmentShapeMismatch);
+        }
+        let r1cs_commitments = commitments
+            .r1cs
+            .as_ref()
+            .ok_or(ProofVerifyError::SegmentShapeMismatch)?;
+        let state_commitment = r1cs_commitments.state()?;
+        let next_pc_commitment = r1cs_commitments.next_pc::<F, InstructionSet>(spartan_key)?;
+        transcript.append_scalar(b"entry_pc", &self.entry_pc);
+        transcript.append_scalar(b"exit_pc", &self.exit_pc);
+
+        PCS::batch_verify(
+            &self.entry_proof,
+            generators,
+            &Self::step_point(0, trace_length),
+            &[self.entry_pc],
+            &[state_commitment],
+            transcript,
+        )?;
+        PCS::batch_verify(
+            &self.exit_proof,
+            generators,
+            &Self::step_point(trace_length - 1, trace_length),
+            &[self.exit_pc],
+            &[next_pc_commitment],
+            transcript,
+        )
+    }
+
+    /// Point at which the trace polynomials evaluate to their value at `step`.
+    fn step_point(step: usize, trace_length: usize) -> Vec<F> {
+        index_to_field_bitvector(step, log2(trace_length.next_power_of_two()) as usize)
+    }
+}
+
+/// Number of steps of the segment starting at `steps[0]`: at most `segment_length`, and ending
+/// between two instructions rather than within a virtual sequence. If `steps` may be the rest
+/// of the trace, it also doesn't leave a single step, which is too short to commit to.
+fn segment_end<I: JoltInstructionSet>(steps: &[JoltTraceStep<I>], segment_length: usize) -> usize {
+    if steps.len() <= segment_length {
+        return steps.len();
+    }
+    let max_end = if steps.len() == segment_length + 1 && segment_length > 1 {
+        segment_length - 1
+    } else {
+        segment_length
+    };
+    // The steps of a virtual sequence share the address of the instruction they expand
+    let splits_instruction =
+        |end: usize| steps[end].bytecode_row.address() == steps[end - 1].bytecode_row.address();
+    (1..=max_end)
+        .rev()
+        .find(|&end| !splits_instruction(end))
+        .unwrap_or(max_end)
+}
+
+/// Lengths of the segments `prove_segmented` splits `trace` into (see `segment_end`).
+fn segment_lengths<I: JoltInstructionSet>(
+    trace: &[JoltTraceStep<I>],
+    segment_length: usize,
+) -> Vec<usize> {
+    let mut lengths = Vec::new();
+    let mut rest = trace;
+    while rest.len() > segment_length {
+        let end = segment_end(rest, segment_length);
+        lengths.push(end);
+        rest = &rest[end..];
+    }
+    lengths.push(rest.len());
+    lengths
+}
+
+/// Splits a trace handed to it in chunks into segments, as `segment_lengths` does.
+#[cfg(feature = "host")]
+struct Segmenter<I: JoltInstructionSet> {
+    segment_length: usize,
+    steps: Vec<JoltTraceStep<I>>,
+}
+
+#[cfg(feature = "host")]
+impl<I: JoltInstructionSet> Segmenter<I> {
+    fn new(segment_length: usize) -> Self {
+        Self {
+            segment_length,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Appends `chunk` to the trace and hands each segment now known not to be the last one to
+    /// `on_segment`.
+    fn push(
+        &mut self,
+        chunk: Vec<JoltTraceStep<I>>,
+        mut on_segment: impl FnMut(Vec<JoltTraceStep<I>>),
+    ) {
+        self.steps.extend(chunk);
+        while self.steps.len() > self.segment_length {
+            let end = segment_end(&self.steps, self.segment_length);
+            let rest = self.steps.split_off(end);
+            on_segment(std::mem::replace(&mut self.steps, rest));
+        }
+    }
+
+    /// The last segment.
+    fn finish(self) -> Vec<JoltTraceStep<I>> {
+        self.steps
+    }
+}
+
+/// A proof of an execution split into segments, each proven on its own. Consecutive segments
+/// are linked by the commitment to the memory state (registers, program I/O and RAM) at their
+/// boundary: a segment's `v_init` commitment must equal the previous segment's `v_final` one.
+/// They are also linked by their [`SegmentBoundary`]: a segment must start at the PC the
+/// previous one continues at.
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct JoltSegmentedProof<const C: usize, const M: usize, F, PCS, InstructionSet, Subtables>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<Field = F>,
+    InstructionSet: JoltInstructionSet,
+    Subtables: JoltSubtableSet<F>,
+{
+    pub segments: Vec<(
+        JoltProof<C, M, F, PCS, InstructionSet, Subtables>,
+        JoltCommitments<PCS>,
+    )>,
+}
+
+/// Compressed byte sizes of the components of a [`JoltProof`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JoltProofSize {
+    /// Trace length and program inputs/outputs.
+    pub program_io: usize,
+    pub bytecode: usize,
+    /// Offline memory checking of registers and RAM.
+    pub read_write_memory: usize,
+    pub output_sumcheck: usize,
+    pub timestamp_range_check: usize,
+    pub instruction_lookups: usize,
+    pub r1cs: usize,
+    pub segment_boundary: usize,
+}
+
+impl JoltProofSize {
+    pub fn total(&self) -> usize {
+        self.program_io
+            + self.bytecode
+            + self.read_write_memory
+            + self.output_sumcheck
+            + self.timestamp_range_check
+            + self.instruction_lookups
+            + self.r1cs
+            + self.segment_boundary
+    }
+}
+
+impl fmt::Display for JoltProofSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<24} {:>12} B", "program I/O", self.program_io)?;
+        writeln!(f, "{:<24} {:>12} B", "bytecode", self.bytecode)?;
+        writeln!(
+            f,
+            "{:<24} {:>12} B",
+            "read-write memory", self.read_write_memory
+        )?;
+        writeln!(
+            f,
+            "{:<24} {:>12} B",
+            "output sumcheck", self.output_sumcheck
+        )?;
+        writeln!(
+            f,
+            "{:<24} {:>12} B",
+            "timestamp range check", self.timestamp_range_check
+        )?;
+        writeln!(
+            f,
+            "{:<24} {:>12} B",
+            "instruction lookups", self.instruction_lookups
+        )?;
+        writeln!(f, "{:<24} {:>12} B", "r1cs", self.r1cs)?;
+        writeln!(
+            f,
+            "{:<24} {:>12} B",
+            "segment boundary", self.segment_boundary
+        )?;
+        writeln!(f, "{:<24} {:>12} B", "total", self.total())
+    }
 }
 
 pub struct JoltPolynomials<F, PCS>
@@ -135,6 +535,60 @@ impl<PCS: CommitmentScheme> JoltCommitments<PCS> {
             .unwrap()
             .append_to_transcript(b"r1cs", transcript);
     }
+
+    /// Compressed byte size of each commitment group.
+    pub fn size(&self) -> JoltCommitmentSize {
+        JoltCommitmentSize {
+            bytecode: self.bytecode.compressed_size(),
+            read_write_memory: self.read_write_memory.compressed_size(),
+            timestamp_range_check: self.timestamp_range_check.compressed_size(),
+            instruction_lookups: self.instruction_lookups.compressed_size(),
+            r1cs: self.r1cs.compressed_size(),
+        }
+    }
+}
+
+/// Compressed byte sizes of the commitment groups in [`JoltCommitments`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JoltCommitmentSize {
+    pub bytecode: usize,
+    pub read_write_memory: usize,
+    pub timestamp_range_check: usize,
+    pub instruction_lookups: usize,
+    pub r1cs: usize,
+}
+
+impl JoltCommitmentSize {
+    pub fn total(&self) -> usize {
+        self.bytecode
+            + self.read_write_memory
+            + self.timestamp_range_check
+            + self.instruction_lookups
+            + self.r1cs
+    }
+}
+
+impl fmt::Display for JoltCommitmentSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<24} {:>12} B", "bytecode", self.bytecode)?;
+        writeln!(
+            f,
+            "{:<24} {:>12} B",
+            "read-write memory", self.read_write_memory
+        )?;
+        writeln!(
+            f,
+            "{:<24} {:>12} B",
+            "timestamp range check", self.timestamp_range_check
+        )?;
+        writeln!(
+            f,
+            "{:<24} {:>12} B",
+            "instruction lookups", self.instruction_lookups
+        )?;
+        writeln!(f, "{:<24} {:>12} B", "r1cs", self.r1cs)?;
+        writeln!(f, "{:<24} {:>12} B", "total", self.total())
+    }
 }
 
 impl<F, PCS> StructuredCommitment<PCS> for JoltPolynomials<F, PCS>
@@ -217,6 +671,10 @@ where
             || PCS::commit(&self.read_write_memory.v_final, generators),
             || PCS::commit(&self.read_write_memory.t_final, generators),
         );
+        let memory_v_init_commitment = self
+            .read_write_memory
+            .v_init_committed
+            .then(|| PCS::commit(&self.read_write_memory.v_init, generators));
         let instruction_final_commitment = PCS::batch_commit_polys(
             &self.instruction_lookups.final_cts,
             generators,
@@ -230,6 +688,7 @@ where
             },
             read_write_memory: MemoryCommitment {
                 trace_commitments: memory_trace_commitment,
+                v_init_commitment: memory_v_init_commitment,
                 v_final_commitment: memory_v_final_commitment,
                 t_final_commitment: memory_t_final_commitment,
             },
@@ -253,16 +712,17 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
     fn preprocess(
         bytecode: Vec<ELFInstruction>,
         memory_init: Vec<(u64, u8)>,
+        memory_layout: MemoryLayout,
         max_bytecode_size: usize,
         max_memory_address: usize,
         max_trace_length: usize,
-    ) -> JoltPreprocessing<F, PCS> {
-        let bytecode_commitment_shapes =
-            BytecodePolynomials::<F, PCS>::commit_shapes(max_bytecode_size, max_trace_length);
-        let ram_commitment_shapes =
-            ReadWriteMemory::<F, PCS>::commitment_shapes(max_memory_address, max_trace_length);
-        let timestamp_range_check_commitment_shapes =
-            TimestampValidityProof::<F, PCS>::commitment_shapes(max_trace_length);
+    ) -> JoltProverKey<F, PCS> {
+        assert!(
+            Self::InstructionSet::COUNT <= MAX_INSTRUCTION_SET_SIZE,
+            "Instruction set has {} instructions, but at most {} are supported",
+            Self::InstructionSet::COUNT,
+            MAX_INSTRUCTION_SET_SIZE
+        );
 
         let instruction_lookups_preprocessing = InstructionLookupsPreprocessing::preprocess::<
             C,
@@ -270,6 +730,58 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
             Self::InstructionSet,
             Self::Subtables,
         >();
+
+        let read_write_memory_preprocessing = ReadWriteMemoryPreprocessing::preprocess(memory_init);
+
+        // Each instruction takes up as many rows as the virtual sequences it is traced as
+        let bytecode_rows: Vec<BytecodeRow> = bytecode
+            .iter()
+            .flat_map(|instruction| {
+                BytecodeRow::from_instructions::<Self::InstructionSet>(
+                    &Self::InstructionSet::bytecode_sequence(instruction),
+                )
+            })
+            .collect();
+        let max_bytecode_size = max_bytecode_size.max(bytecode_rows.len());
+        let bytecode_preprocessing = BytecodePreprocessing::<F>::preprocess(bytecode_rows);
+
+        let commitment_shapes = Self::commitment_shapes(
+            &instruction_lookups_preprocessing,
+            max_bytecode_size,
+            max_memory_address,
+            max_trace_length,
+        );
+        let generators = PCS::setup(&commitment_shapes);
+
+        let memory_start = RAM_START_ADDRESS - memory_layout.ram_witness_offset;
+        let r1cs_shape =
+            R1CSBuilder::<Self::InstructionSet>::default().single_step_shape(memory_start);
+
+        JoltProverKey {
+            generators,
+            instruction_lookups: instruction_lookups_preprocessing,
+            bytecode: bytecode_preprocessing,
+            read_write_memory: read_write_memory_preprocessing,
+            memory_layout,
+            r1cs_shape,
+            max_trace_length,
+        }
+    }
+
+    /// The shapes of the polynomials committed to when proving a program within the given
+    /// bounds, which determine the size of the commitment scheme's setup.
+    fn commitment_shapes(
+        instruction_lookups_preprocessing: &InstructionLookupsPreprocessing<F>,
+        max_bytecode_size: usize,
+        max_memory_address: usize,
+        max_trace_length: usize,
+    ) -> Vec<CommitShape> {
+        let bytecode_commitment_shapes =
+            BytecodePolynomials::<F, PCS>::commit_shapes(max_bytecode_size, max_trace_length);
+        let ram_commitment_shapes =
+            ReadWriteMemory::<F, PCS>::commitment_shapes(max_memory_address, max_trace_length);
+        let timestamp_range_check_commitment_shapes =
+            TimestampValidityProof::<F, PCS>::commitment_shapes(max_trace_length);
         let instruction_lookups_commitment_shapes = InstructionLookupsProof::<
             C,
             M,
@@ -278,135 +790,338 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
             Self::InstructionSet,
             Self::Subtables,
         >::commitment_shapes(
-            &instruction_lookups_preprocessing,
+            instruction_lookups_preprocessing,
             max_trace_length,
         );
 
-        let read_write_memory_preprocessing = ReadWriteMemoryPreprocessing::preprocess(memory_init);
-
-        let bytecode_rows: Vec<BytecodeRow> = bytecode
-            .iter()
-            .map(BytecodeRow::from_instruction::<Self::InstructionSet>)
-            .collect();
-        let bytecode_preprocessing = BytecodePreprocessing::<F>::preprocess(bytecode_rows);
-
-        let commitment_shapes = [
+        [
             bytecode_commitment_shapes,
             ram_commitment_shapes,
             timestamp_range_check_commitment_shapes,
             instruction_lookups_commitment_shapes,
         ]
-        .concat();
-        let generators = PCS::setup(&commitment_shapes);
-
-        JoltPreprocessing {
-            generators,
-            instruction_lookups: instruction_lookups_preprocessing,
-            bytecode: bytecode_preprocessing,
-            read_write_memory: read_write_memory_preprocessing,
-        }
+        .concat()
     }
 
     #[tracing::instrument(skip_all, name = "Jolt::prove")]
     fn prove(
         program_io: JoltDevice,
-        mut trace: Vec<JoltTraceStep<Self::InstructionSet>>,
+        trace: Vec<JoltTraceStep<Self::InstructionSet>>,
         circuit_flags: Vec<F>,
-        preprocessing: JoltPreprocessing<F, PCS>,
+        prover_key: &JoltProverKey<F, PCS>,
     ) -> (
         JoltProof<C, M, F, PCS, Self::InstructionSet, Self::Subtables>,
         JoltCommitments<PCS>,
     ) {
-        let trace_length = trace.len();
-        let padded_trace_length = trace_length.next_power_of_two();
-        println!("Trace length: {}", trace_length);
-
-        JoltTraceStep::pad(&mut trace);
-
-        let mut transcript = ProofTranscript::new(b"Jolt transcript");
-        Self::fiat_shamir_preamble(&mut transcript, &program_io, trace_length);
+        Self::prove_with_observer(program_io, trace, circuit_flags, prover_key, &mut |_| {})
+    }
 
-        let instruction_polynomials = InstructionLookupsProof::<
-            C,
-            M,
-            F,
-            PCS,
-            Self::InstructionSet,
-            Self::Subtables,
-        >::polynomialize(
-            &preprocessing.instruction_lookups, &trace
+    /// Same as `prove`, but reports the trace length and the start and end of
+    /// each prover phase to `observer`.
+    fn prove_with_observer(
+        program_io: JoltDevice,
+        trace: Vec<JoltTraceStep<Self::InstructionSet>>,
+        circuit_flags: Vec<F>,
+        prover_key: &JoltProverKey<F, PCS>,
+        observer: &mut impl ProverObserver,
+    ) -> (
+        JoltProof<C, M, F, PCS, Self::InstructionSet, Self::Subtables>,
+        JoltCommitments<PCS>,
+    ) {
+        let memory_size = memory_size(&program_io.memory_layout, &trace);
+        let (proof, commitments, _) = Self::prove_segment(
+            program_io,
+            trace,
+            circuit_flags,
+            prover_key,
+            InitialMemory::Program { memory_size },
+            SegmentPosition::STANDALONE,
+            observer,
         );
+        (proof, commitments)
+    }
 
-        let load_store_flags = &instruction_polynomials.instruction_flag_polys[5..10];
-        let (memory_polynomials, read_timestamps) = ReadWriteMemory::new(
-            &program_io,
-            load_store_flags,
-            &preprocessing.read_write_memory,
-            &trace,
+    /// Proves `trace` as a chain of segments of at most `segment_length` steps, so that only
+    /// one segment's polynomials are held in memory at a time. The preprocessing only needs to
+    /// support traces of `segment_length` steps. Segments end between two instructions, so
+    /// they may be shorter than `segment_length` if that would split a virtual sequence.
+    ///
+    /// Each segment starts from the final memory state of the previous one, at the PC the
+    /// previous one continues at.
+    fn prove_segmented(
+        program_io: JoltDevice,
+        trace: Vec<JoltTraceStep<Self::InstructionSet>>,
+        segment_length: usize,
+        prover_key: &JoltProverKey<F, PCS>,
+        observer: &mut impl ProverObserver,
+    ) -> JoltSegmentedProof<C, M, F, PCS, Self::InstructionSet, Self::Subtables> {
+        assert!(
+            segment_length.is_power_of_two(),
+            "segment length must be a power of two"
         );
+        // Every segment's memory has the same size, so that the commitments at their
+        // boundaries can be compared.
+        let memory_size = memory_size(&program_io.memory_layout, &trace);
+        let segment_lengths = segment_lengths(&trace, segment_length);
+        let num_segments = segment_lengths.len();
+
+        let peak_memory = PeakMemoryMonitor::start(MEMORY_SAMPLING_INTERVAL);
+        let mut trace = trace.into_iter();
+        let mut initial_memory = InitialMemory::Program { memory_size };
+        let mut segments = Vec::with_capacity(num_segments);
+        for (index, length) in segment_lengths.into_iter().enumerate() {
+            observer.on_event(ProverEvent::SegmentStarted {
+                segment: index,
+                num_segments,
+            });
+            let segment_trace: Vec<_> = trace.by_ref().take(length).collect();
+            let circuit_flags = JoltTraceStep::circuit_flags(&segment_trace);
+            let (proof, commitments, final_memory) = Self::prove_segment(
+                program_io.clone(),
+                segment_trace,
+                circuit_flags,
+                prover_key,
+                initial_memory,
+                SegmentPosition::new(index, num_segments),
+                observer,
+            );
+            initial_memory = InitialMemory::Checkpoint(final_memory);
+            segments.push((proof, commitments));
+            observer.on_event(ProverEvent::SegmentFinished {
+                segment: index,
+                peak_memory: peak_memory.peak(),
+            });
+        }
 
-        let (bytecode_polynomials, range_check_polys) = rayon::join(
-            || BytecodePolynomials::<F, PCS>::new(&preprocessing.bytecode, &mut trace),
-            || RangeCheckPolynomials::<F, PCS>::new(read_timestamps),
-        );
+        JoltSegmentedProof { segments }
+    }
 
-        let jolt_polynomials = JoltPolynomials {
-            bytecode: bytecode_polynomials,
-            read_write_memory: memory_polynomials,
-            timestamp_range_check: range_check_polys,
-            instruction_lookups: instruction_polynomials,
+    /// Same as `prove_segmented`, but for traces too long to hold in memory: rather than
+    /// taking the trace, it runs `program` twice. The first run only determines the final
+    /// program I/O and the memory size; the second regenerates the trace one segment at a
+    /// time, proving and freeing each segment before the next is emulated. Peak memory is thus
+    /// bounded by the size of a single segment, whatever the length of the execution.
+    ///
+    /// Each segment is still proven as a whole: its polynomials are committed all at once, so
+    /// `segment_length` must be small enough for a segment to fit in memory.
+    #[cfg(feature = "host")]
+    #[tracing::instrument(skip_all, name = "Jolt::prove_program_segmented")]
+    fn prove_program_segmented(
+        program: &mut Program,
+        segment_length: usize,
+        prover_key: &JoltProverKey<F, PCS>,
+        observer: &mut impl ProverObserver,
+    ) -> Result<JoltSegmentedProof<C, M, F, PCS, Self::InstructionSet, Self::Subtables>, HostError>
+    {
+        if !segment_length.is_power_of_two() {
+            return Err(HostError::SegmentLength(segment_length));
+        }
+        let peak_memory = PeakMemoryMonitor::start(MEMORY_SAMPLING_INTERVAL);
+
+        let memory_layout = program.memory_layout();
+        let mut max_memory_size = 0;
+        // The last segment is only known once the guest halts
+        let mut num_segments = 1;
+        let mut segmenter = Segmenter::new(segment_length);
+        // Printed when traced again below
+        let program_io =
+            program.trace_chunked_silently::<Self::InstructionSet>(segment_length, |chunk| {
+                max_memory_size = max_memory_size.max(memory_size(&memory_layout, &chunk));
+                segmenter.push(chunk, |_| num_segments += 1);
+            })?;
+
+        let mut initial_memory = InitialMemory::Program {
+            memory_size: max_memory_size,
         };
+        let mut segments = Vec::with_capacity(num_segments);
+        let mut prove_segment = |segment_trace: Vec<JoltTraceStep<Self::InstructionSet>>| {
+            let index = segments.len();
+            observer.on_event(ProverEvent::SegmentStarted {
+                segment: index,
+                num_segments,
+            });
+            let circuit_flags = JoltTraceStep::circuit_flags(&segment_trace);
+            let (proof, commitments, final_memory) = Self::prove_segment(
+                program_io.clone(),
+                segment_trace,
+                circuit_flags,
+                prover_key,
+                std::mem::replace(&mut initial_memory, InitialMemory::Checkpoint(vec![])),
+                SegmentPosition::new(index, num_segments),
+                observer,
+            );
+            initial_memory = InitialMemory::Checkpoint(final_memory);
+            segments.push((proof, commitments));
+            observer.on_event(ProverEvent::SegmentFinished {
+                segment: index,
+                peak_memory: peak_memory.peak(),
+            });
+        };
+        let mut segmenter = Segmenter::new(segment_length);
+        let replayed_io = program
+            .trace_chunked::<Self::InstructionSet>(segment_length, |chunk| {
+                segmenter.push(chunk, &mut prove_segment)
+            })?;
+        prove_segment(segmenter.finish());
+        if program_io != replayed_io {
+            return Err(HostError::NonDeterministicGuest);
+        }
 
-        let mut jolt_commitments = jolt_polynomials.commit(&preprocessing.generators);
+        Ok(JoltSegmentedProof { segments })
+    }
 
-        let (spartan_key, witness_segments, r1cs_commitments) = Self::r1cs_setup(
+    /// Proves a single segment starting from `initial_memory`, and returns its final memory
+    /// state along with the proof.
+    #[allow(clippy::type_complexity)]
+    fn prove_segment(
+        program_io: JoltDevice,
+        mut trace: Vec<JoltTraceStep<Self::InstructionSet>>,
+        circuit_flags: Vec<F>,
+        prover_key: &JoltProverKey<F, PCS>,
+        initial_memory: InitialMemory,
+        segment: SegmentPosition,
+        observer: &mut impl ProverObserver,
+    ) -> (
+        JoltProof<C, M, F, PCS, Self::InstructionSet, Self::Subtables>,
+        JoltCommitments<PCS>,
+        Vec<u64>,
+    ) {
+        let trace_length = trace.len();
+        let padded_trace_length = trace_length.next_power_of_two();
+        observer.on_event(ProverEvent::TraceLength {
+            trace_length,
             padded_trace_length,
-            RAM_START_ADDRESS - program_io.memory_layout.ram_witness_offset,
-            &trace,
-            &jolt_polynomials,
-            circuit_flags,
-            &preprocessing.generators,
+        });
+        assert_eq!(
+            program_io.memory_layout, prover_key.memory_layout,
+            "program I/O does not match the preprocessed memory layout"
         );
 
+        JoltTraceStep::pad(&mut trace);
+
+        let mut transcript = ProofTranscript::new(b"Jolt transcript");
+        Self::fiat_shamir_preamble(&mut transcript, &program_io, trace_length);
+
+        let (mut jolt_polynomials, final_memory) =
+            observe_phase(observer, ProverPhase::Polynomialize, || {
+                let instruction_polynomials = InstructionLookupsProof::<
+                    C,
+                    M,
+                    F,
+                    PCS,
+                    Self::InstructionSet,
+                    Self::Subtables,
+                >::polynomialize(
+                    &prover_key.instruction_lookups, &trace
+                );
+
+                let load_store_flags = &instruction_polynomials.instruction_flag_polys[5..10];
+                let (memory_polynomials, read_timestamps, final_memory) = ReadWriteMemory::new(
+                    &program_io,
+                    load_store_flags,
+                    &prover_key.read_write_memory,
+                    &trace,
+                    initial_memory,
+                );
+
+                let (bytecode_polynomials, range_check_polys) = rayon::join(
+                    || BytecodePolynomials::<F, PCS>::new(&prover_key.bytecode, &mut trace),
+                    || RangeCheckPolynomials::<F, PCS>::new(read_timestamps),
+                );
+
+                let polynomials = JoltPolynomials {
+                    bytecode: bytecode_polynomials,
+                    read_write_memory: memory_polynomials,
+                    timestamp_range_check: range_check_polys,
+                    instruction_lookups: instruction_polynomials,
+                };
+                (polynomials, final_memory)
+            });
+
+        let mut jolt_commitments = observe_phase(observer, ProverPhase::Commit, || {
+            jolt_polynomials.commit(&prover_key.generators)
+        });
+
+        let spartan_key = prover_key.spartan_key(padded_trace_length);
+        let (witness_segments, r1cs_commitments) =
+            observe_phase(observer, ProverPhase::R1CSWitness, || {
+                Self::r1cs_setup(
+                    padded_trace_length,
+                    RAM_START_ADDRESS - program_io.memory_layout.ram_witness_offset,
+                    &trace,
+                    &jolt_polynomials,
+                    circuit_flags,
+                    &prover_key.generators,
+                )
+            });
+
         // append the digest of vk (which includes R1CS matrices) and the RelaxedR1CSInstance to the transcript
         transcript.append_scalar(b"spartan key", &spartan_key.vk_digest);
 
         jolt_commitments.r1cs = Some(r1cs_commitments);
+        drop(trace);
 
         jolt_commitments.append_to_transcript(&mut transcript);
 
-        let bytecode_proof = BytecodeProof::prove_memory_checking(
-            &preprocessing.generators,
-            &preprocessing.bytecode,
-            &jolt_polynomials.bytecode,
-            &mut transcript,
-        );
-
-        let instruction_proof = InstructionLookupsProof::prove(
-            &preprocessing.generators,
-            &jolt_polynomials.instruction_lookups,
-            &preprocessing.instruction_lookups,
-            &mut transcript,
-        );
-
-        let memory_proof = ReadWriteMemoryProof::prove(
-            &preprocessing.generators,
-            &preprocessing.read_write_memory,
-            &jolt_polynomials,
-            &program_io,
-            &mut transcript,
-        );
+        let segment_boundary = (segment != SegmentPosition::STANDALONE).then(|| {
+            SegmentBoundary::prove(
+                &prover_key.generators,
+                trace_length,
+                // The state is the first witness variable of the step shape, the next PC wire
+                // the last one
+                &witness_segments[0],
+                &witness_segments[spartan_key.num_witness_segments() - 1],
+                &mut transcript,
+            )
+        });
+
+        // Proven first, so that the R1CS witness is freed before the other sub-proofs run
+        let r1cs_proof = observe_phase(observer, ProverPhase::R1CSProof, || {
+            R1CSProof::prove(
+                &prover_key.generators,
+                &spartan_key,
+                witness_segments,
+                &mut transcript,
+            )
+            .expect("proof failed")
+        });
+
+        let bytecode_proof = observe_phase(observer, ProverPhase::BytecodeProof, || {
+            BytecodeProof::prove_memory_checking(
+                &prover_key.generators,
+                &prover_key.bytecode,
+                &jolt_polynomials.bytecode,
+                &mut transcript,
+            )
+        });
+        jolt_polynomials.bytecode.free_memory_checking_polys();
+
+        let instruction_proof =
+            observe_phase(observer, ProverPhase::InstructionLookupsProof, || {
+                InstructionLookupsProof::prove(
+                    &prover_key.generators,
+                    &jolt_polynomials.instruction_lookups,
+                    &prover_key.instruction_lookups,
+                    &mut transcript,
+                )
+            });
+        // Each component's polynomials are freed once no remaining sub-proof reads them. The
+        // instruction lookup polynomials make up most of the prover's memory.
+        drop_in_background_thread(std::mem::take(&mut jolt_polynomials.instruction_lookups));
+
+        let memory_proof = observe_phase(observer, ProverPhase::ReadWriteMemoryProof, || {
+            ReadWriteMemoryProof::prove(
+                &prover_key.generators,
+                &prover_key.read_write_memory,
+                &jolt_polynomials,
+                &program_io,
+                segment,
+                &mut transcript,
+            )
+        });
 
         drop_in_background_thread(jolt_polynomials);
 
-        let r1cs_proof = R1CSProof::prove(
-            &preprocessing.generators,
-            spartan_key,
-            witness_segments,
-            &mut transcript,
-        )
-        .expect("proof failed");
-
         let jolt_proof = JoltProof {
             trace_length,
             program_io,
@@ -414,50 +1129,149 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
             read_write_memory: memory_proof,
             instruction_lookups: instruction_proof,
             r1cs: r1cs_proof,
+            segment_boundary,
         };
 
-        (jolt_proof, jolt_commitments)
+        (jolt_proof, jolt_commitments, final_memory)
     }
 
     fn verify(
-        mut preprocessing: JoltPreprocessing<F, PCS>,
+        verifier_key: &JoltVerifierKey<F, PCS>,
         proof: JoltProof<C, M, F, PCS, Self::InstructionSet, Self::Subtables>,
         commitments: JoltCommitments<PCS>,
     ) -> Result<(), ProofVerifyError> {
+        Self::verify_segment(
+            verifier_key,
+            proof,
+            commitments,
+            SegmentPosition::STANDALONE,
+        )
+    }
+
+    /// Verifies a proof produced by `prove_segmented`: each segment on its own, and that it
+    /// continues from the previous one.
+    fn verify_segmented(
+        verifier_key: &JoltVerifierKey<F, PCS>,
+        proof: JoltSegmentedProof<C, M, F, PCS, Self::InstructionSet, Self::Subtables>,
+    ) -> Result<(), ProofVerifyError> {
+        let num_segments = proof.segments.len();
+        let program_io = match proof.segments.last() {
+            Some((last, _)) => last.program_io.clone(),
+            None => return Err(ProofVerifyError::EmptySegmentChain),
+        };
+
+        let mut previous_v_final: Option<Vec<u8>> = None;
+        let mut previous_exit_pc: Option<F> = None;
+        for (index, (segment_proof, commitments)) in proof.segments.into_iter().enumerate() {
+            if segment_proof.program_io != program_io {
+                return Err(ProofVerifyError::SegmentIOMismatch(index));
+            }
+            let memory = &commitments.read_write_memory;
+            if let Some(previous_v_final) = &previous_v_final {
+                let linked = memory
+                    .v_init_commitment
+                    .as_ref()
+                    .is_some_and(|v_init| commitment_bytes(v_init) == *previous_v_final);
+                if !linked {
+                    return Err(ProofVerifyError::SegmentNotLinked(index));
+                }
+            }
+            previous_v_final = Some(commitment_bytes(&memory.v_final_commitment));
+
+            let boundary = segment_proof.segment_boundary.as_ref();
+            if let Some(previous_exit_pc) = previous_exit_pc {
+                let linked = boundary.is_some_and(|boundary| {
+                    next_pc_wire_value(boundary.entry_pc) == previous_exit_pc
+                });
+                if !linked {
+                    return Err(ProofVerifyError::SegmentPCNotLinked(index));
+                }
+            }
+            previous_exit_pc = boundary.map(|boundary| boundary.exit_pc);
+
+            Self::verify_segment(
+                verifier_key,
+                segment_proof,
+                commitments,
+                SegmentPosition::new(index, num_segments),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn verify_segment(
+        verifier_key: &JoltVerifierKey<F, PCS>,
+        proof: JoltProof<C, M, F, PCS, Self::InstructionSet, Self::Subtables>,
+        commitments: JoltCommitments<PCS>,
+        segment: SegmentPosition,
+    ) -> Result<(), ProofVerifyError> {
+        if proof.program_io.memory_layout != verifier_key.memory_layout {
+            return Err(ProofVerifyError::MemoryLayoutMismatch);
+        }
+        if proof.trace_length > verifier_key.max_trace_length {
+            return Err(ProofVerifyError::TraceTooLong(
+                proof.trace_length,
+                verifier_key.max_trace_length,
+            ));
+        }
+        // Appending the commitments to the transcript expects the R1CS ones
+        if commitments.r1cs.is_none() {
+            return Err(ProofVerifyError::SegmentShapeMismatch);
+        }
+
         let mut transcript = ProofTranscript::new(b"Jolt transcript");
         Self::fiat_shamir_preamble(&mut transcript, &proof.program_io, proof.trace_length);
 
+        // The verifier derives the key (which includes R1CS matrices) from its own preprocessing
+        let spartan_key = verifier_key.spartan_key(proof.trace_length.next_power_of_two());
         // append the digest of vk (which includes R1CS matrices) and the RelaxedR1CSInstance to the transcript
-        transcript.append_scalar(b"spartan key", &proof.r1cs.key.vk_digest);
+        transcript.append_scalar(b"spartan key", &spartan_key.vk_digest);
 
         commitments.append_to_transcript(&mut transcript);
 
+        match (
+            &proof.segment_boundary,
+            segment == SegmentPosition::STANDALONE,
+        ) {
+            (Some(boundary), false) => boundary.verify::<Self::InstructionSet>(
+                &verifier_key.generators,
+                &spartan_key,
+                proof.trace_length,
+                &commitments,
+                &mut transcript,
+            )?,
+            (None, true) => {}
+            _ => return Err(ProofVerifyError::SegmentShapeMismatch),
+        }
+
+        Self::verify_r1cs(
+            &spartan_key,
+            &verifier_key.generators,
+            proof.r1cs,
+            &commitments,
+            &mut transcript,
+        )?;
         Self::verify_bytecode(
-            &preprocessing.bytecode,
-            &preprocessing.generators,
+            &verifier_key.bytecode,
+            &verifier_key.generators,
             proof.bytecode,
             &commitments.bytecode,
             &mut transcript,
         )?;
         Self::verify_instruction_lookups(
-            &preprocessing.instruction_lookups,
-            &preprocessing.generators,
+            &verifier_key.instruction_lookups,
+            &verifier_key.generators,
             proof.instruction_lookups,
             &commitments.instruction_lookups,
             &mut transcript,
         )?;
         Self::verify_memory(
-            &mut preprocessing.read_write_memory,
-            &preprocessing.generators,
+            &verifier_key.read_write_memory,
+            &verifier_key.generators,
             proof.read_write_memory,
             &commitments,
-            proof.program_io,
-            &mut transcript,
-        )?;
-        Self::verify_r1cs(
-            &preprocessing.generators,
-            proof.r1cs,
-            commitments,
+            &proof.program_io,
+            segment,
             &mut transcript,
         )?;
         Ok(())
@@ -465,17 +1279,18 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
 
     fn verify_instruction_lookups(
         preprocessing: &InstructionLookupsPreprocessing<F>,
-        generators: &PCS::Setup,
+        generators: &PCS::VerifierSetup,
         proof: InstructionLookupsProof<C, M, F, PCS, Self::InstructionSet, Self::Subtables>,
         commitment: &InstructionCommitment<PCS>,
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
         InstructionLookupsProof::verify(preprocessing, generators, proof, commitment, transcript)
+            .map_err(|e| e.in_sub_protocol(SubProtocol::InstructionLookups))
     }
 
     fn verify_bytecode(
         preprocessing: &BytecodePreprocessing<F>,
-        generators: &PCS::Setup,
+        generators: &PCS::VerifierSetup,
         proof: BytecodeProof<F, PCS>,
         commitment: &BytecodeCommitment<PCS>,
         transcript: &mut ProofTranscript,
@@ -487,32 +1302,44 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
             commitment,
             transcript,
         )
+        .map_err(|e| e.in_sub_protocol(SubProtocol::Bytecode))
     }
 
     fn verify_memory(
-        preprocessing: &mut ReadWriteMemoryPreprocessing,
-        generators: &PCS::Setup,
+        preprocessing: &ReadWriteMemoryPreprocessing,
+        generators: &PCS::VerifierSetup,
         proof: ReadWriteMemoryProof<F, PCS>,
         commitment: &JoltCommitments<PCS>,
-        program_io: JoltDevice,
+        program_io: &JoltDevice,
+        segment: SegmentPosition,
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
-        assert!(program_io.inputs.len() <= program_io.memory_layout.max_input_size as usize);
-        assert!(program_io.outputs.len() <= program_io.memory_layout.max_output_size as usize);
-        preprocessing.program_io = Some(program_io);
-
-        ReadWriteMemoryProof::verify(proof, generators, preprocessing, commitment, transcript)
+        // The termination and panic bits are bound by the output sumcheck, so this
+        // ensures that the guest actually signaled that it halted.
+        if !program_io.halted() {
+            return Err(ProofVerifyError::ProgramDidNotHalt);
+        }
+        ReadWriteMemoryProof::verify(
+            proof,
+            generators,
+            preprocessing,
+            commitment,
+            program_io,
+            segment,
+            transcript,
+        )
     }
 
     fn verify_r1cs(
-        generators: &PCS::Setup,
+        key: &UniformSpartanKey<F>,
+        generators: &PCS::VerifierSetup,
         proof: R1CSProof<F, PCS>,
-        commitments: JoltCommitments<PCS>,
+        commitments: &JoltCommitments<PCS>,
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
         proof
-            .verify(generators, commitments, C, transcript)
-            .map_err(|e| ProofVerifyError::SpartanError(e.to_string()))
+            .verify::<Self::InstructionSet>(key, generators, commitments, C, transcript)
+            .map_err(|e| e.in_sub_protocol(SubProtocol::R1CS))
     }
 
     fn r1cs_setup(
@@ -522,7 +1349,7 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
         polynomials: &JoltPolynomials<F, PCS>,
         circuit_flags: Vec<F>,
         generators: &PCS::Setup,
-    ) -> (UniformSpartanKey<F>, Vec<Vec<F>>, R1CSCommitment<PCS>) {
+    ) -> (Vec<Vec<F>>, R1CSCommitment<PCS>) {
         let log_M = log2(M) as usize;
 
         // Assemble the polynomials and commitments from the rest of Jolt.
@@ -601,18 +1428,14 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
             instruction_flags,
         );
 
-        let (spartan_key, witness_segments, r1cs_commitments) =
-            R1CSProof::<F, PCS>::compute_witness_commit(
-                32,
-                C,
-                padded_trace_length,
-                memory_start,
-                &inputs,
-                generators,
-            )
-            .expect("R1CSProof setup failed");
-
-        (spartan_key, witness_segments, r1cs_commitments)
+        R1CSProof::<F, PCS>::compute_witness_commit::<Self::InstructionSet>(
+            32,
+            C,
+            padded_trace_length,
+            memory_start,
+            &inputs,
+            generators,
+        )
     }
 
     fn fiat_shamir_preamble(
@@ -630,11 +1453,23 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
         transcript.append_bytes(b"Program inputs", &program_io.inputs);
         transcript.append_bytes(b"Program outputs", &program_io.outputs);
         transcript.append_u64(b"Program panic", program_io.panic as u64);
+        transcript.append_u64(b"Program termination", program_io.termination as u64);
     }
 }
 
+/// The compressed serialization of a commitment, for comparing commitments (which don't
+/// implement `PartialEq`).
+fn commitment_bytes<C: CanonicalSerialize>(commitment: &C) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(commitment.compressed_size());
+    commitment
+        .serialize_compressed(&mut bytes)
+        .expect("serializing to a Vec cannot fail");
+    bytes
+}
+
 pub mod bytecode;
 pub mod instruction_lookups;
+pub mod observer;
 pub mod read_write_memory;
 pub mod rv32i_vm;
 pub mod timestamp_range_check;
@@ -0,0 +1,157 @@
+use std::fmt::{self, Display};
+use std::time::{Duration, Instant};
+
+/// A phase of `Jolt::prove`, in the order the prover runs them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProverPhase {
+    Polynomialize,
+    Commit,
+    R1CSWitness,
+    R1CSProof,
+    BytecodeProof,
+    InstructionLookupsProof,
+    ReadWriteMemoryProof,
+}
+
+impl ProverPhase {
+    pub const ALL: [ProverPhase; 7] = [
+        ProverPhase::Polynomialize,
+        ProverPhase::Commit,
+        ProverPhase::R1CSWitness,
+        ProverPhase::R1CSProof,
+        ProverPhase::BytecodeProof,
+        ProverPhase::InstructionLookupsProof,
+        ProverPhase::ReadWriteMemoryProof,
+    ];
+
+    /// Approximate share of total prover time spent in this phase, measured on
+    /// a 2^17-step RV32I trace. Every phase is roughly linear in the padded trace
+    /// length, so the shares carry over to other trace sizes.
+    fn weight(self) -> f64 {
+        match self {
+            ProverPhase::Polynomialize => 0.04,
+            ProverPhase::Commit => 0.14,
+            ProverPhase::R1CSWitness => 0.09,
+            ProverPhase::BytecodeProof => 0.01,
+            ProverPhase::InstructionLookupsProof => 0.25,
+            ProverPhase::ReadWriteMemoryProof => 0.11,
+            ProverPhase::R1CSProof => 0.36,
+        }
+    }
+
+    /// Estimated fraction of the total prover work left when this phase starts
+    /// (or, if `finished`, once it has finished).
+    fn remaining_work(self, finished: bool) -> f64 {
+        let total: f64 = Self::ALL.iter().map(|phase| phase.weight()).sum();
+        let remaining: f64 = Self::ALL
+            .iter()
+            .skip_while(|&&phase| phase != self)
+            .skip(finished as usize)
+            .map(|phase| phase.weight())
+            .sum();
+        remaining / total
+    }
+}
+
+impl Display for ProverPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ProverPhase::Polynomialize => "Polynomialize",
+            ProverPhase::Commit => "Commit",
+            ProverPhase::R1CSWitness => "R1CS witness",
+            ProverPhase::BytecodeProof => "Bytecode proof",
+            ProverPhase::InstructionLookupsProof => "Instruction lookups proof",
+            ProverPhase::ReadWriteMemoryProof => "Read-write memory proof",
+            ProverPhase::R1CSProof => "R1CS proof",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Progress reported by `Jolt::prove_with_observer`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProverEvent {
+    /// Emitted by `Jolt::prove_segmented` and `Jolt::prove_program_segmented` before proving each
+    /// segment, whose events follow.
+    SegmentStarted { segment: usize, num_segments: usize },
+    /// Emitted after each segment, by the same provers as `SegmentStarted`.
+    SegmentFinished {
+        segment: usize,
+        /// Peak physical memory of the process in bytes since proving started, or `None` if
+        /// it cannot be measured on this platform.
+        peak_memory: Option<usize>,
+    },
+    /// Emitted once per proof (or segment), before any phase starts.
+    TraceLength {
+        trace_length: usize,
+        padded_trace_length: usize,
+    },
+    PhaseStarted {
+        phase: ProverPhase,
+        /// Estimated fraction of the total prover work left, in `[0, 1]`.
+        remaining_work: f64,
+    },
+    PhaseFinished {
+        phase: ProverPhase,
+        elapsed: Duration,
+        /// Estimated fraction of the total prover work left, in `[0, 1]`.
+        remaining_work: f64,
+    },
+}
+
+/// Receives the events of a running proof. Implemented for any
+/// `FnMut(ProverEvent)`, so a closure can be passed directly.
+pub trait ProverObserver {
+    fn on_event(&mut self, event: ProverEvent);
+}
+
+impl<T: FnMut(ProverEvent)> ProverObserver for T {
+    fn on_event(&mut self, event: ProverEvent) {
+        self(event)
+    }
+}
+
+/// Runs `f` as `phase`, reporting its start and end to `observer`.
+pub(crate) fn observe_phase<T>(
+    observer: &mut impl ProverObserver,
+    phase: ProverPhase,
+    f: impl FnOnce() -> T,
+) -> T {
+    observer.on_event(ProverEvent::PhaseStarted {
+        phase,
+        remaining_work: phase.remaining_work(false),
+    });
+    let start = Instant::now();
+    let result = f();
+    observer.on_event(ProverEvent::PhaseFinished {
+        phase,
+        elapsed: start.elapsed(),
+        remaining_work: phase.remaining_work(true),
+    });
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn remaining_work_decreases_to_zero() {
+        let mut events = vec![];
+        for phase in ProverPhase::ALL {
+            observe_phase(&mut |event| events.push(event), phase, || ());
+        }
+
+        let remaining: Vec<f64> = events
+            .iter()
+            .map(|event| match event {
+                ProverEvent::PhaseStarted { remaining_work, .. }
+                | ProverEvent::PhaseFinished { remaining_work, .. } => *remaining_work,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert!((remaining[0] - 1.0).abs() < 1e-9);
+        assert!(remaining.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert_eq!(*remaining.last().unwrap(), 0.0);
+    }
+}
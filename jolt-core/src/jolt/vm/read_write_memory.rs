@@ -13,15 +13,20 @@ use crate::poly::commitment::commitment_scheme::{BatchType, CommitShape, Commitm
 use crate::utils::transcript::AppendToTranscript;
 use crate::{
     lasso::memory_checking::{
-        MemoryCheckingProof, MemoryCheckingProver, MemoryCheckingVerifier, MultisetHashes,
-        NoPreprocessing,
+        check_hash_count, MemoryCheckingProof, MemoryCheckingProver, MemoryCheckingVerifier,
+        MultisetHashes, NoPreprocessing,
     },
     poly::{
         dense_mlpoly::DensePolynomial, eq_poly::EqPolynomial, identity_poly::IdentityPolynomial,
         structured_poly::StructuredOpeningProof,
     },
     subprotocols::sumcheck::SumcheckInstanceProof,
-    utils::{errors::ProofVerifyError, math::Math, mul_0_optimized, transcript::ProofTranscript},
+    utils::{
+        errors::{ProofVerifyError, SubProtocol, VerificationStage},
+        math::Math,
+        mul_0_optimized,
+        transcript::ProofTranscript,
+    },
 };
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use common::constants::{
@@ -30,8 +35,8 @@ use common::constants::{
 };
 use common::rv_trace::{JoltDevice, MemoryLayout, MemoryOp};
 
-use super::JoltTraceStep;
 use super::{timestamp_range_check::TimestampValidityProof, JoltCommitments, JoltPolynomials};
+use super::{JoltTraceStep, SegmentPosition};
 
 pub fn random_memory_trace<F: JoltField>(
     memory_init: &Vec<(u64, u8)>,
@@ -173,16 +178,10 @@ pub fn random_memory_trace<F: JoltField>(
     )
 }
 
-#[derive(Clone)]
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct ReadWriteMemoryPreprocessing {
     min_bytecode_address: u64,
     pub bytecode_bytes: Vec<u8>,
-    // HACK: The verifier will populate this field by copying it
-    // over from the `ReadWriteMemoryProof`. Having `program_io` in
-    // this preprocessing struct allows the verifier to access it
-    // to compute the v_init and v_final openings, with no impact
-    // on existing function signatures.
-    pub program_io: Option<JoltDevice>,
 }
 
 impl ReadWriteMemoryPreprocessing {
@@ -211,11 +210,94 @@ impl ReadWriteMemoryPreprocessing {
         Self {
             min_bytecode_address,
             bytecode_bytes,
-            program_io: None,
         }
     }
 }
 
+/// The public data that determines a read-write memory-checking instance: the preprocessed
+/// bytecode, the program I/O of the execution being proven and the position of the proof in
+/// its segment chain. Used by the verifier to compute the `v_init` opening.
+pub struct ReadWriteMemoryInstance {
+    pub preprocessing: ReadWriteMemoryPreprocessing,
+    pub program_io: JoltDevice,
+    pub segment: SegmentPosition,
+}
+
+impl ReadWriteMemoryInstance {
+    /// Checks that the program I/O fits within its memory layout. The I/O is supplied by the
+    /// prover, so oversized I/O must be rejected here rather than indexing out of bounds later.
+    pub fn new(
+        preprocessing: &ReadWriteMemoryPreprocessing,
+        program_io: &JoltDevice,
+        segment: SegmentPosition,
+    ) -> Result<Self, ProofVerifyError> {
+        check_program_io_size(program_io)?;
+        Ok(Self {
+            preprocessing: preprocessing.clone(),
+            program_io: program_io.clone(),
+            segment,
+        })
+    }
+}
+
+/// The memory state (registers, program I/O and RAM) that the proven execution starts from.
+pub enum InitialMemory {
+    /// The program's bytecode and inputs, in a memory of `memory_size` cells. The verifier
+    /// computes this state itself.
+    Program { memory_size: usize },
+    /// The final memory state of the previous segment. The prover commits to it, and the
+    /// verifier checks that the commitment matches the previous segment's `v_final`.
+    Checkpoint(Vec<u64>),
+}
+
+/// The memory state of a program before it runs: its bytecode and inputs, in a memory of
+/// `memory_size` cells.
+fn program_memory_state(
+    preprocessing: &ReadWriteMemoryPreprocessing,
+    program_io: &JoltDevice,
+    memory_size: usize,
+) -> Vec<u64> {
+    let memory_layout = &program_io.memory_layout;
+    let mut v_init: Vec<u64> = vec![0; memory_size];
+    // Copy bytecode
+    let mut v_init_index = memory_address_to_witness_index(
+        preprocessing.min_bytecode_address,
+        memory_layout.ram_witness_offset,
+    );
+    for byte in preprocessing.bytecode_bytes.iter() {
+        v_init[v_init_index] = *byte as u64;
+        v_init_index += 1;
+    }
+    // Copy input bytes
+    v_init_index = memory_address_to_witness_index(
+        memory_layout.input_start,
+        memory_layout.ram_witness_offset,
+    );
+    for byte in program_io.inputs.iter() {
+        v_init[v_init_index] = *byte as u64;
+        v_init_index += 1;
+    }
+    v_init
+}
+
+fn check_program_io_size(program_io: &JoltDevice) -> Result<(), ProofVerifyError> {
+    let max_input_size = program_io.memory_layout.max_input_size as usize;
+    if program_io.inputs.len() > max_input_size {
+        return Err(ProofVerifyError::ProgramInputTooLarge(
+            program_io.inputs.len(),
+            max_input_size,
+        ));
+    }
+    let max_output_size = program_io.memory_layout.max_output_size as usize;
+    if program_io.outputs.len() > max_output_size {
+        return Err(ProofVerifyError::ProgramOutputTooLarge(
+            program_io.outputs.len(),
+            max_output_size,
+        ));
+    }
+    Ok(())
+}
+
 fn remap_address(a: u64, memory_layout: &MemoryLayout) -> u64 {
     if a >= memory_layout.input_start {
         memory_address_to_witness_index(a, memory_layout.ram_witness_offset) as u64
@@ -228,6 +310,36 @@ fn remap_address(a: u64, memory_layout: &MemoryLayout) -> u64 {
     }
 }
 
+/// The number of memory cells (registers, program I/O and RAM) covered by the
+/// memory-checking argument for `trace`, padded to a power of two.
+pub fn memory_size<InstructionSet: JoltInstructionSet>(
+    memory_layout: &MemoryLayout,
+    trace: &[JoltTraceStep<InstructionSet>],
+) -> usize {
+    let max_trace_address = trace
+        .iter()
+        .flat_map(|step| {
+            step.memory_ops.iter().map(|op| match op {
+                MemoryOp::Read(a) => remap_address(*a, memory_layout),
+                MemoryOp::Write(a, _) => remap_address(*a, memory_layout),
+            })
+        })
+        .max()
+        .unwrap_or(0);
+
+    (memory_layout.ram_witness_offset + max_trace_address).next_power_of_two() as usize
+}
+
+/// Upper bound on `memory_size` for any execution that only accesses the first `ram_size`
+/// bytes of RAM, e.g. the guest's memory as declared to the linker.
+pub fn max_memory_size(memory_layout: &MemoryLayout, ram_size: u64) -> usize {
+    let max_ram_address = memory_address_to_witness_index(
+        RAM_START_ADDRESS + ram_size,
+        memory_layout.ram_witness_offset,
+    ) as u64;
+    (memory_layout.ram_witness_offset + max_ram_address).next_power_of_two() as usize
+}
+
 fn remap_address_index(remapped_a: u64) -> usize {
     (remapped_a - REGISTER_COUNT) as usize
 }
@@ -252,8 +364,11 @@ where
     _group: PhantomData<C>,
     /// Size of entire address space (i.e. registers + IO + RAM)
     memory_size: usize,
-    /// MLE of initial memory values. RAM is initialized to contain the program bytecode and inputs.
+    /// MLE of initial memory values. RAM is initialized to contain the program bytecode and inputs,
+    /// or the final state of the previous segment.
     pub v_init: DensePolynomial<F>,
+    /// Whether `v_init` is committed to, i.e. the execution starts from a checkpoint.
+    pub v_init_committed: bool,
     /// MLE of read/write addresses. For offline memory checking, each read is paired with a "virtual" write
     /// and vice versa, so the read addresses and write addresses are the same.
     pub a_ram: DensePolynomial<F>,
@@ -306,45 +421,29 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> ReadWriteMemory<F, C> {
         load_store_flags: &[DensePolynomial<F>],
         preprocessing: &ReadWriteMemoryPreprocessing,
         trace: &Vec<JoltTraceStep<InstructionSet>>,
-    ) -> (Self, [Vec<u64>; MEMORY_OPS_PER_INSTRUCTION]) {
+        initial_memory: InitialMemory,
+    ) -> (Self, [Vec<u64>; MEMORY_OPS_PER_INSTRUCTION], Vec<u64>) {
         assert!(program_io.inputs.len() <= program_io.memory_layout.max_input_size as usize);
         assert!(program_io.outputs.len() <= program_io.memory_layout.max_output_size as usize);
 
         let m = trace.len();
         assert!(m.is_power_of_two());
 
-        let max_trace_address = trace
-            .iter()
-            .flat_map(|step| {
-                step.memory_ops.iter().map(|op| match op {
-                    MemoryOp::Read(a) => remap_address(*a, &program_io.memory_layout),
-                    MemoryOp::Write(a, _) => remap_address(*a, &program_io.memory_layout),
-                })
-            })
-            .max()
-            .unwrap_or(0);
-
-        let memory_size = (program_io.memory_layout.ram_witness_offset + max_trace_address)
-            .next_power_of_two() as usize;
-        let mut v_init: Vec<u64> = vec![0; memory_size];
-        // Copy bytecode
-        let mut v_init_index = memory_address_to_witness_index(
-            preprocessing.min_bytecode_address,
-            program_io.memory_layout.ram_witness_offset,
-        );
-        for byte in preprocessing.bytecode_bytes.iter() {
-            v_init[v_init_index] = *byte as u64;
-            v_init_index += 1;
-        }
-        // Copy input bytes
-        v_init_index = memory_address_to_witness_index(
-            program_io.memory_layout.input_start,
-            program_io.memory_layout.ram_witness_offset,
+        let min_memory_size = memory_size(&program_io.memory_layout, trace);
+        let (v_init, v_init_committed) = match initial_memory {
+            InitialMemory::Program { memory_size } => (
+                program_memory_state(preprocessing, program_io, memory_size),
+                false,
+            ),
+            InitialMemory::Checkpoint(state) => (state, true),
+        };
+        let memory_size = v_init.len();
+        assert!(
+            memory_size.is_power_of_two() && memory_size >= min_memory_size,
+            "memory of {} cells cannot hold the {} accessed by the trace",
+            memory_size,
+            min_memory_size
         );
-        for byte in program_io.inputs.iter() {
-            v_init[v_init_index] = *byte as u64;
-            v_init_index += 1;
-        }
 
         #[cfg(test)]
         let mut init_tuples: HashSet<(u64, u64, u64)> = HashSet::new();
@@ -871,6 +970,7 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> ReadWriteMemory<F, C> {
             assert_eq!(set_difference.len(), 0);
         }
 
+        let init_final_vals = [a_ram, v_write_rd, v_init, v_final, t_final];
         let (
             [a_ram, v_write_rd, v_init, v_final, t_final],
             v_read,
@@ -884,17 +984,19 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> ReadWriteMemory<F, C> {
             [DensePolynomial<F>; MEMORY_OPS_PER_INSTRUCTION],
             [DensePolynomial<F>; 4],
         ) = common::par_join_5!(
-            || map_to_polys(&[a_ram, v_write_rd, v_init, v_final, t_final]),
+            || map_to_polys(&init_final_vals),
             || map_to_polys(&v_read),
             || map_to_polys(&v_write_ram),
             || map_to_polys(&t_read),
             || map_to_polys(&t_write_ram)
         );
+        let [_, _, _, final_state, _] = init_final_vals;
         (
             Self {
                 _group: PhantomData,
                 memory_size,
                 v_init,
+                v_init_committed,
                 a_ram,
                 v_read,
                 v_write_rd,
@@ -905,6 +1007,7 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> ReadWriteMemory<F, C> {
                 t_final,
             },
             t_read,
+            final_state,
         )
     }
 
@@ -942,23 +1045,25 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> ReadWriteMemory<F, C> {
         let max_memory_address = max_memory_address.next_power_of_two();
         let max_trace_length = max_trace_length.next_power_of_two();
 
-        // { rs1, rs2, rd, ram_byte_1, ram_byte_2, ram_byte_3, ram_byte_4 }
-        let t_read_write_len = (max_trace_length * MEMORY_OPS_PER_INSTRUCTION).next_power_of_two();
-        let t_read_write_shape = CommitShape::new(t_read_write_len, BatchType::Big);
-
-        // { a_ram, v_read, v_write_rd, v_write_ram }
-        let r1cs_shape = CommitShape::new(max_trace_length, BatchType::Big);
-        // v_final, t_final
+        // a_ram, v_read, v_write_rd, v_write_ram, t_read, t_write_ram
+        let read_write_shape = CommitShape::new(
+            max_trace_length,
+            BatchType::Big,
+            2 + 2 * MEMORY_OPS_PER_INSTRUCTION + 2 * RAM_OPS_PER_INSTRUCTION,
+        );
+        // v_final, t_final (and v_init when starting from a checkpoint)
         let init_final_len = max_memory_address.next_power_of_two();
-        let init_final_shape = CommitShape::new(init_final_len, BatchType::Small);
+        let init_final_shape = CommitShape::new(init_final_len, BatchType::Small, 2);
 
-        vec![t_read_write_shape, r1cs_shape, init_final_shape]
+        vec![read_write_shape, init_final_shape]
     }
 }
 
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
 pub struct MemoryCommitment<C: CommitmentScheme> {
     pub trace_commitments: Vec<C::Commitment>,
+    /// Commitment to `v_init`, present iff the execution starts from a checkpoint.
+    pub v_init_commitment: Option<C::Commitment>,
     pub v_final_commitment: C::Commitment,
     pub t_final_commitment: C::Commitment,
 }
@@ -969,6 +1074,9 @@ impl<C: CommitmentScheme> AppendToTranscript for MemoryCommitment<C> {
         for commitment in &self.trace_commitments {
             commitment.append_to_transcript(b"trace_commit", transcript);
         }
+        if let Some(v_init_commitment) = &self.v_init_commitment {
+            v_init_commitment.append_to_transcript(b"v_init_commit", transcript);
+        }
         self.v_final_commitment
             .append_to_transcript(b"v_final_commit", transcript);
         self.t_final_commitment
@@ -1084,7 +1192,7 @@ where
 
     fn verify_openings(
         &self,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &JoltCommitments<C>,
         opening_point: &[F],
@@ -1119,7 +1227,8 @@ where
 {
     /// Evaluation of the a_init_final polynomial at the opening point. Computed by the verifier in `compute_verifier_openings`.
     a_init_final: Option<F>,
-    /// Evaluation of the v_init polynomial at the opening point. Computed by the verifier in `compute_verifier_openings`.
+    /// Evaluation of the v_init polynomial at the opening point. Computed by the verifier in `compute_verifier_openings`,
+    /// unless the execution starts from a (committed) checkpoint, in which case the prover opens it.
     v_init: Option<F>,
     /// Evaluation of the v_final polynomial at the opening point.
     v_final: F,
@@ -1142,19 +1251,29 @@ where
     C: CommitmentScheme<Field = F>,
 {
     type Proof = MemoryInitFinalOpeningProof<F, C>;
-    type Preprocessing = ReadWriteMemoryPreprocessing;
+    type Preprocessing = ReadWriteMemoryInstance;
 
     #[tracing::instrument(skip_all, name = "MemoryInitFinalOpenings::open")]
     fn open(polynomials: &JoltPolynomials<F, C>, opening_point: &[F]) -> Self {
         let chis = EqPolynomial::evals(opening_point);
-        let (v_final, t_final) = rayon::join(
-            || polynomials.read_write_memory.v_final.evaluate_at_chi(&chis),
-            || polynomials.read_write_memory.t_final.evaluate_at_chi(&chis),
+        let memory = &polynomials.read_write_memory;
+        let (v_init, (v_final, t_final)) = rayon::join(
+            || {
+                memory
+                    .v_init_committed
+                    .then(|| memory.v_init.evaluate_at_chi(&chis))
+            },
+            || {
+                rayon::join(
+                    || memory.v_final.evaluate_at_chi(&chis),
+                    || memory.t_final.evaluate_at_chi(&chis),
+                )
+            },
         );
 
         Self {
             a_init_final: None,
-            v_init: None,
+            v_init,
             v_final,
             t_final,
         }
@@ -1168,14 +1287,21 @@ where
         openings: &Self,
         transcript: &mut ProofTranscript,
     ) -> Self::Proof {
+        let memory = &polynomials.read_write_memory;
+        let (polys, evals): (Vec<_>, Vec<_>) = openings
+            .v_init
+            .map(|v_init| (&memory.v_init, v_init))
+            .into_iter()
+            .chain([
+                (&memory.v_final, openings.v_final),
+                (&memory.t_final, openings.t_final),
+            ])
+            .unzip();
         let v_t_opening_proof = C::batch_prove(
             generators,
-            &[
-                &polynomials.read_write_memory.v_final,
-                &polynomials.read_write_memory.t_final,
-            ],
+            &polys,
             opening_point,
-            &[openings.v_final, openings.t_final],
+            &evals,
             BatchType::Small,
             transcript,
         );
@@ -1183,58 +1309,52 @@ where
         Self::Proof { v_t_opening_proof }
     }
 
-    fn compute_verifier_openings(
-        &mut self,
-        preprocessing: &Self::Preprocessing,
-        opening_point: &[F],
-    ) {
+    fn compute_verifier_openings(&mut self, instance: &Self::Preprocessing, opening_point: &[F]) {
         self.a_init_final =
             Some(IdentityPolynomial::new(opening_point.len()).evaluate(opening_point));
 
-        let memory_layout = &preprocessing.program_io.as_ref().unwrap().memory_layout;
+        // Later segments start from a checkpoint, whose opening is proven against its commitment
+        if !instance.segment.first {
+            return;
+        }
 
         // TODO(moodlezoup): Compute opening without instantiating v_init polynomial itself
         let memory_size = opening_point.len().pow2();
-        let mut v_init: Vec<u64> = vec![0; memory_size];
-        // Copy bytecode
-        let mut v_init_index = memory_address_to_witness_index(
-            preprocessing.min_bytecode_address,
-            memory_layout.ram_witness_offset,
-        );
-        for byte in preprocessing.bytecode_bytes.iter() {
-            v_init[v_init_index] = *byte as u64;
-            v_init_index += 1;
-        }
-        // Copy input bytes
-        v_init_index = memory_address_to_witness_index(
-            memory_layout.input_start,
-            memory_layout.ram_witness_offset,
-        );
-        for byte in preprocessing.program_io.as_ref().unwrap().inputs.iter() {
-            v_init[v_init_index] = *byte as u64;
-            v_init_index += 1;
-        }
-
+        let v_init =
+            program_memory_state(&instance.preprocessing, &instance.program_io, memory_size);
         self.v_init = Some(DensePolynomial::from_u64(&v_init).evaluate(opening_point));
     }
 
     fn verify_openings(
         &self,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &JoltCommitments<C>,
         opening_point: &[F],
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
+        let memory = &commitment.read_write_memory;
+        let (commitments, evals): (Vec<_>, Vec<_>) = match &memory.v_init_commitment {
+            Some(v_init_commitment) => {
+                let v_init = self.v_init.ok_or(VerificationStage::OpeningProof {
+                    reason: "missing v_init opening".to_string(),
+                })?;
+                vec![(v_init_commitment, v_init)]
+            }
+            None => vec![],
+        }
+        .into_iter()
+        .chain([
+            (&memory.v_final_commitment, self.v_final),
+            (&memory.t_final_commitment, self.t_final),
+        ])
+        .unzip();
         C::batch_verify(
             &opening_proof.v_t_opening_proof,
             generators,
             opening_point,
-            &[self.v_final, self.t_final],
-            &[
-                &commitment.read_write_memory.v_final_commitment,
-                &commitment.read_write_memory.t_final_commitment,
-            ],
+            &evals,
+            &commitments,
             transcript,
         )?;
 
@@ -1247,7 +1367,7 @@ where
     F: JoltField,
     C: CommitmentScheme<Field = F>,
 {
-    type Preprocessing = ReadWriteMemoryPreprocessing;
+    type Preprocessing = ReadWriteMemoryInstance;
     type ReadWriteOpenings = MemoryReadWriteOpenings<F, C>;
     type InitFinalOpenings = MemoryInitFinalOpenings<F>;
 
@@ -1354,8 +1474,8 @@ where
         _preprocessing: &Self::Preprocessing,
         read_write_hashes: Vec<F>,
         init_final_hashes: Vec<F>,
-    ) -> MultisetHashes<F> {
-        assert_eq!(read_write_hashes.len(), 2 * MEMORY_OPS_PER_INSTRUCTION);
+    ) -> Result<MultisetHashes<F>, ProofVerifyError> {
+        check_hash_count(2 * MEMORY_OPS_PER_INSTRUCTION, read_write_hashes.len())?;
         let mut read_hashes = Vec::with_capacity(MEMORY_OPS_PER_INSTRUCTION);
         let mut write_hashes = Vec::with_capacity(MEMORY_OPS_PER_INSTRUCTION);
         for i in 0..MEMORY_OPS_PER_INSTRUCTION {
@@ -1363,43 +1483,34 @@ where
             write_hashes.push(read_write_hashes[2 * i + 1]);
         }
 
-        assert_eq!(init_final_hashes.len(), 2);
+        check_hash_count(2, init_final_hashes.len())?;
         let init_hash = init_final_hashes[0];
         let final_hash = init_final_hashes[1];
 
-        MultisetHashes {
+        Ok(MultisetHashes {
             read_hashes,
             write_hashes,
             init_hashes: vec![init_hash],
             final_hashes: vec![final_hash],
-        }
+        })
     }
 
     fn check_multiset_equality(
         _preprocessing: &Self::Preprocessing,
         multiset_hashes: &MultisetHashes<F>,
-    ) {
-        assert_eq!(
-            multiset_hashes.read_hashes.len(),
-            MEMORY_OPS_PER_INSTRUCTION
-        );
-        assert_eq!(
-            multiset_hashes.write_hashes.len(),
-            MEMORY_OPS_PER_INSTRUCTION
-        );
-        assert_eq!(multiset_hashes.init_hashes.len(), 1);
-        assert_eq!(multiset_hashes.final_hashes.len(), 1);
+    ) -> Result<(), ProofVerifyError> {
+        multiset_hashes.check_counts(MEMORY_OPS_PER_INSTRUCTION, 1, 1)?;
 
         let read_hash: F = multiset_hashes.read_hashes.iter().product();
         let write_hash: F = multiset_hashes.write_hashes.iter().product();
         let init_hash = multiset_hashes.init_hashes[0];
         let final_hash = multiset_hashes.final_hashes[0];
 
-        assert_eq!(
-            init_hash * write_hash,
-            final_hash * read_hash,
-            "Multiset hashes don't match"
-        );
+        // Registers and RAM are checked as a single memory
+        if init_hash * write_hash != final_hash * read_hash {
+            return Err(VerificationStage::MultisetHashEquality { memory: 0 }.into());
+        }
+        Ok(())
     }
 
     fn protocol_name() -> &'static [u8] {
@@ -1549,6 +1660,11 @@ where
             program_io.memory_layout.panic,
             program_io.memory_layout.ram_witness_offset,
         )] = program_io.panic as u64;
+        // Copy termination bit
+        v_io[memory_address_to_witness_index(
+            program_io.memory_layout.termination,
+            program_io.memory_layout.ram_witness_offset,
+        )] = program_io.termination as u64;
 
         let mut sumcheck_polys = vec![
             eq,
@@ -1583,9 +1699,9 @@ where
 
     fn verify(
         proof: &Self,
-        preprocessing: &ReadWriteMemoryPreprocessing,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         commitment: &MemoryCommitment<C>,
+        program_io: &JoltDevice,
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
         let r_eq = transcript.challenge_vector(b"output_sumcheck", proof.num_rounds);
@@ -1597,7 +1713,7 @@ where
 
         let eq_eval = EqPolynomial::new(r_eq.to_vec()).evaluate(&r_sumcheck);
 
-        let memory_layout = &preprocessing.program_io.as_ref().unwrap().memory_layout;
+        let memory_layout = &program_io.memory_layout;
 
         let nonzero_memory_size = memory_layout.ram_witness_offset as usize;
         let log_nonzero_memory_size = nonzero_memory_size.log_2();
@@ -1605,6 +1721,14 @@ where
             nonzero_memory_size.is_power_of_two(),
             "Ram witness offset must be a power of two"
         );
+        // The memory must at least span the I/O region
+        if proof.num_rounds < log_nonzero_memory_size {
+            return Err(VerificationStage::SumcheckRoundCount {
+                expected: log_nonzero_memory_size,
+                actual: proof.num_rounds,
+            }
+            .into());
+        }
 
         let io_witness_range: Vec<_> = (0..nonzero_memory_size as u64)
             .map(|i| {
@@ -1627,7 +1751,7 @@ where
             memory_layout.input_start,
             memory_layout.ram_witness_offset,
         );
-        for byte in preprocessing.program_io.as_ref().unwrap().inputs.iter() {
+        for byte in program_io.inputs.iter() {
             v_io[input_index] = *byte as u64;
             input_index += 1;
         }
@@ -1636,7 +1760,7 @@ where
             memory_layout.output_start,
             memory_layout.ram_witness_offset,
         );
-        for byte in preprocessing.program_io.as_ref().unwrap().outputs.iter() {
+        for byte in program_io.outputs.iter() {
             v_io[output_index] = *byte as u64;
             output_index += 1;
         }
@@ -1644,16 +1768,19 @@ where
         v_io[memory_address_to_witness_index(
             memory_layout.panic,
             memory_layout.ram_witness_offset,
-        )] = preprocessing.program_io.as_ref().unwrap().panic as u64;
+        )] = program_io.panic as u64;
+        // Copy termination bit
+        v_io[memory_address_to_witness_index(
+            memory_layout.termination,
+            memory_layout.ram_witness_offset,
+        )] = program_io.termination as u64;
         let mut v_io_eval =
             DensePolynomial::from_u64(&v_io).evaluate(&r_sumcheck[..log_nonzero_memory_size]);
         v_io_eval *= r_prod;
 
-        assert_eq!(
-            eq_eval * io_witness_range_eval * (proof.opening - v_io_eval),
-            sumcheck_claim,
-            "Output sumcheck check failed."
-        );
+        if eq_eval * io_witness_range_eval * (proof.opening - v_io_eval) != sumcheck_claim {
+            return Err(VerificationStage::SumcheckFinalClaim.into());
+        }
 
         C::verify(
             &proof.opening_proof,
@@ -1663,6 +1790,7 @@ where
             &proof.opening,
             &commitment.v_final_commitment,
         )
+        .map_err(|err| VerificationStage::opening_proof(err).into())
     }
 }
 
@@ -1680,7 +1808,8 @@ where
         MemoryInitFinalOpenings<F>,
     >,
     pub timestamp_validity_proof: TimestampValidityProof<F, C>,
-    pub output_proof: OutputSumcheckProof<F, C>,
+    /// Only the last segment of a chain checks the program outputs.
+    pub output_proof: Option<OutputSumcheckProof<F, C>>,
 }
 
 impl<F, C> ReadWriteMemoryProof<F, C>
@@ -1694,21 +1823,29 @@ where
         preprocessing: &ReadWriteMemoryPreprocessing,
         polynomials: &JoltPolynomials<F, C>,
         program_io: &JoltDevice,
+        segment: SegmentPosition,
         transcript: &mut ProofTranscript,
     ) -> Self {
+        let instance = ReadWriteMemoryInstance {
+            preprocessing: preprocessing.clone(),
+            program_io: program_io.clone(),
+            segment,
+        };
         let memory_checking_proof = ReadWriteMemoryProof::prove_memory_checking(
             generators,
-            preprocessing,
+            &instance,
             polynomials,
             transcript,
         );
 
-        let output_proof = OutputSumcheckProof::prove_outputs(
-            generators,
-            &polynomials.read_write_memory,
-            program_io,
-            transcript,
-        );
+        let output_proof = segment.last.then(|| {
+            OutputSumcheckProof::prove_outputs(
+                generators,
+                &polynomials.read_write_memory,
+                program_io,
+                transcript,
+            )
+        });
 
         let timestamp_validity_proof = TimestampValidityProof::prove(
             generators,
@@ -1726,25 +1863,40 @@ where
 
     pub fn verify(
         mut self,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         preprocessing: &ReadWriteMemoryPreprocessing,
         commitment: &JoltCommitments<C>,
+        program_io: &JoltDevice,
+        segment: SegmentPosition,
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
+        // Only the first segment starts from the program's own memory, which the verifier
+        // computes; only the last one has its outputs checked.
+        if commitment.read_write_memory.v_init_commitment.is_some() == segment.first
+            || self.output_proof.is_some() != segment.last
+        {
+            return Err(ProofVerifyError::SegmentShapeMismatch);
+        }
+
+        let instance = ReadWriteMemoryInstance::new(preprocessing, program_io, segment)?;
         ReadWriteMemoryProof::verify_memory_checking(
-            preprocessing,
+            &instance,
             generators,
             self.memory_checking_proof,
             commitment,
             transcript,
-        )?;
-        OutputSumcheckProof::verify(
-            &self.output_proof,
-            preprocessing,
-            generators,
-            &commitment.read_write_memory,
-            transcript,
-        )?;
+        )
+        .map_err(|e| e.in_sub_protocol(SubProtocol::ReadWriteMemory))?;
+        if let Some(output_proof) = &self.output_proof {
+            OutputSumcheckProof::verify(
+                output_proof,
+                generators,
+                &commitment.read_write_memory,
+                program_io,
+                transcript,
+            )
+            .map_err(|e| e.in_sub_protocol(SubProtocol::ReadWriteMemory))?;
+        }
         TimestampValidityProof::verify(
             &mut self.timestamp_validity_proof,
             generators,
@@ -1752,5 +1904,78 @@ where
             &commitment.read_write_memory,
             transcript,
         )
+        .map_err(|e| e.in_sub_protocol(SubProtocol::TimestampRange))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn oversized_program_io_is_rejected() {
+        let preprocessing = ReadWriteMemoryPreprocessing::preprocess(vec![(RAM_START_ADDRESS, 0)]);
+        let mut program_io = JoltDevice::new(8, 8);
+        program_io.inputs = vec![1; 8];
+        program_io.outputs = vec![2; 8];
+        assert!(ReadWriteMemoryInstance::new(
+            &preprocessing,
+            &program_io,
+            SegmentPosition::STANDALONE
+        )
+        .is_ok());
+
+        program_io.inputs.push(1);
+        assert!(matches!(
+            ReadWriteMemoryInstance::new(&preprocessing, &program_io, SegmentPosition::STANDALONE),
+            Err(ProofVerifyError::ProgramInputTooLarge(9, 8))
+        ));
+
+        program_io.inputs.pop();
+        program_io.outputs.push(2);
+        assert!(matches!(
+            ReadWriteMemoryInstance::new(&preprocessing, &program_io, SegmentPosition::STANDALONE),
+            Err(ProofVerifyError::ProgramOutputTooLarge(9, 8))
+        ));
+    }
+
+    #[test]
+    fn max_memory_size_bounds_ram_accesses() {
+        use crate::jolt::vm::{bytecode::BytecodeRow, rv32i_vm::RV32I};
+
+        let memory_layout = MemoryLayout::new(64, 64);
+        let ram_size = 1 << 16;
+        let step = |address: u64| JoltTraceStep::<RV32I> {
+            instruction_lookup: None,
+            bytecode_row: BytecodeRow::no_op(0),
+            memory_ops: [
+                MemoryOp::noop_read(),
+                MemoryOp::noop_read(),
+                MemoryOp::noop_write(),
+                MemoryOp::Write(address, 1),
+                MemoryOp::noop_read(),
+                MemoryOp::noop_read(),
+                MemoryOp::noop_read(),
+            ],
+        };
+        let trace = vec![
+            step(memory_layout.output_start),
+            step(RAM_START_ADDRESS),
+            step(RAM_START_ADDRESS + ram_size - 1),
+        ];
+        assert!(memory_size(&memory_layout, &trace) <= max_memory_size(&memory_layout, ram_size));
+    }
+
+    #[test]
+    fn console_is_remapped_between_termination_and_ram() {
+        use common::constants::CONSOLE_ADDRESS;
+
+        // 64 registers + 30 + 30 + 4 reserved bytes fill up 128 witness indices exactly
+        for (max_input_size, max_output_size) in [(30, 30), (64, 64), (4096, 4096)] {
+            let memory_layout = MemoryLayout::new(max_input_size, max_output_size);
+            let console = remap_address(CONSOLE_ADDRESS, &memory_layout);
+            assert!(console > remap_address(memory_layout.termination, &memory_layout));
+            assert!(console < remap_address(RAM_START_ADDRESS, &memory_layout));
+        }
     }
 }
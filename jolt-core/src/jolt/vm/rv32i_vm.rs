@@ -1,144 +1,175 @@
 use crate::field::JoltField;
-use enum_dispatch::enum_dispatch;
-use rand::{prelude::StdRng, RngCore};
-use serde::{Deserialize, Serialize};
-use std::any::TypeId;
-use strum::{EnumCount, IntoEnumIterator};
-use strum_macros::{EnumCount as EnumCountMacro, EnumIter};
-
-use super::{Jolt, JoltProof};
+use tracer::{ELFInstruction, RVTraceRow, RV32IM};
+
+use super::{Jolt, JoltProof, JoltSegmentedProof};
 use crate::jolt::instruction::{
-    add::ADDInstruction, and::ANDInstruction, beq::BEQInstruction, bge::BGEInstruction,
-    bgeu::BGEUInstruction, bne::BNEInstruction, lb::LBInstruction, lh::LHInstruction,
-    movsign::MOVSIGNInstruction, mul::MULInstruction, mulhu::MULHUInstruction,
-    mulu::MULUInstruction, or::ORInstruction, sb::SBInstruction, sh::SHInstruction,
-    sll::SLLInstruction, slt::SLTInstruction, sltu::SLTUInstruction, sra::SRAInstruction,
-    srl::SRLInstruction, sub::SUBInstruction, sw::SWInstruction, virtual_advice::ADVICEInstruction,
-    virtual_assert_eq_signs::ASSERTEQSIGNSInstruction,
-    virtual_assert_lt_abs::ASSERTLTABSInstruction, virtual_assert_lte::ASSERTLTEInstruction,
-    xor::XORInstruction, JoltInstruction, JoltInstructionSet, SubtableIndices,
-};
-use crate::jolt::subtable::{
-    and::AndSubtable, eq::EqSubtable, eq_abs::EqAbsSubtable, eq_msb::EqMSBSubtable,
-    gt_msb::GtMSBSubtable, identity::IdentitySubtable, lt_abs::LtAbsSubtable, ltu::LtuSubtable,
-    or::OrSubtable, sign_extend::SignExtendSubtable, sll::SllSubtable, sra_sign::SraSignSubtable,
-    srl::SrlSubtable, truncate_overflow::TruncateOverflowSubtable, xor::XorSubtable,
-    JoltSubtableSet, LassoSubtable, SubtableId,
+    amo::AMOInstruction, div::DIVInstruction, divu::DIVUInstruction, lr::LRInstruction,
+    mulh::MULHInstruction, mulhsu::MULHSUInstruction, rem::REMInstruction, remu::REMUInstruction,
+    sc::SCInstruction, with_remaining_counts, JoltInstructionSet, VirtualInstructionSequence,
 };
 use crate::poly::commitment::commitment_scheme::CommitmentScheme;
 
-/// Generates an enum out of a list of JoltInstruction types. All JoltInstruction methods
-/// are callable on the enum type via enum_dispatch.
-macro_rules! instruction_set {
-    ($enum_name:ident, $($alias:ident: $struct:ty),+) => {
-        #[allow(non_camel_case_types)]
-        #[repr(u8)]
-        #[derive(Copy, Clone, Debug, EnumIter, EnumCountMacro, Serialize, Deserialize)]
-        #[enum_dispatch(JoltInstruction)]
-        pub enum $enum_name { $($alias($struct)),+ }
-        impl JoltInstructionSet for $enum_name {}
-        impl $enum_name {
-            pub fn random_instruction(rng: &mut StdRng) -> Self {
-                let index = rng.next_u64() as usize % $enum_name::COUNT;
-                let instruction = $enum_name::iter()
-                    .enumerate()
-                    .filter(|(i, _)| *i == index)
-                    .map(|(_, x)| x)
-                    .next()
-                    .unwrap();
-                instruction.random(rng)
+pub const WORD_SIZE: usize = 32;
+
+/// Generates an instruction set consisting of the RV32I instructions followed by the given
+/// instructions, e.g. `rv32i_instruction_set!(MyInstructionSet, MY_OP: MyOpInstruction)`.
+/// The additional instruction types must be distinct from the RV32I ones. Also implements
+/// `From<RV32I>`, so the generated set's `TryFrom` impls can handle its custom opcodes and
+/// defer to RV32I for the rest:
+///
+/// ```ignore
+/// match instruction.opcode {
+///     RV32IM::CUSTOM_0 => Ok(MyOpInstruction::default().into()),
+///     _ => RV32I::try_from(instruction).map(Into::into),
+/// }
+/// ```
+#[macro_export]
+macro_rules! rv32i_instruction_set {
+    ($enum_name:ident $(, $alias:ident: $struct:ty)* $(,)?) => {
+        $crate::rv32i_instruction_set!(
+            @expand $enum_name
+            [
+                ADD: $crate::jolt::instruction::add::ADDInstruction<{ $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+                SUB: $crate::jolt::instruction::sub::SUBInstruction<{ $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+                AND: $crate::jolt::instruction::and::ANDInstruction,
+                OR: $crate::jolt::instruction::or::ORInstruction,
+                XOR: $crate::jolt::instruction::xor::XORInstruction,
+                LB: $crate::jolt::instruction::lb::LBInstruction,
+                LH: $crate::jolt::instruction::lh::LHInstruction,
+                SB: $crate::jolt::instruction::sb::SBInstruction,
+                SH: $crate::jolt::instruction::sh::SHInstruction,
+                SW: $crate::jolt::instruction::sw::SWInstruction,
+                BEQ: $crate::jolt::instruction::beq::BEQInstruction,
+                BGE: $crate::jolt::instruction::bge::BGEInstruction,
+                BGEU: $crate::jolt::instruction::bgeu::BGEUInstruction,
+                BNE: $crate::jolt::instruction::bne::BNEInstruction,
+                SLT: $crate::jolt::instruction::slt::SLTInstruction,
+                SLTU: $crate::jolt::instruction::sltu::SLTUInstruction,
+                SLL: $crate::jolt::instruction::sll::SLLInstruction<{ $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+                SRA: $crate::jolt::instruction::sra::SRAInstruction<{ $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+                SRL: $crate::jolt::instruction::srl::SRLInstruction<{ $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+                MOVSIGN: $crate::jolt::instruction::movsign::MOVSIGNInstruction<{ $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+                MUL: $crate::jolt::instruction::mul::MULInstruction<{ $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+                MULU: $crate::jolt::instruction::mulu::MULUInstruction<{ $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+                MULHU: $crate::jolt::instruction::mulhu::MULHUInstruction<{ $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+                VIRTUAL_ADVICE: $crate::jolt::instruction::virtual_advice::ADVICEInstruction<{ $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+                VIRTUAL_ASSERT_LTE: $crate::jolt::instruction::virtual_assert_lte::ASSERTLTEInstruction,
+                VIRTUAL_ASSERT_LT_ABS: $crate::jolt::instruction::virtual_assert_lt_abs::ASSERTLTABSInstruction<{ $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+                VIRTUAL_ASSERT_EQ_SIGNS: $crate::jolt::instruction::virtual_assert_eq_signs::ASSERTEQSIGNSInstruction,
+                VIRTUAL_ASSERT_VALID_UNSIGNED_REMAINDER: $crate::jolt::instruction::virtual_assert_valid_unsigned_remainder::ASSERTVALIDUNSIGNEDREMAINDERInstruction,
+                VIRTUAL_ASSERT_VALID_DIV0: $crate::jolt::instruction::virtual_assert_valid_div0::ASSERTVALIDDIV0Instruction<{ $crate::jolt::vm::rv32i_vm::WORD_SIZE }>
+            ]
+            [$($alias: $struct),*]
+        );
+    };
+    // RV32I itself; `From<RV32I>` would conflict with the blanket `From<T> for T`
+    (@expand RV32I [$($base_alias:ident: $base_struct:ty),+] []) => {
+        $crate::instruction_set!(RV32I, $($base_alias: $base_struct),+);
+    };
+    (@expand $enum_name:ident [$($base_alias:ident: $base_struct:ty),+] [$($alias:ident: $struct:ty),*]) => {
+        $crate::instruction_set!($enum_name, $($base_alias: $base_struct),+ $(, $alias: $struct)*);
+
+        impl From<$crate::jolt::vm::rv32i_vm::RV32I> for $enum_name {
+            fn from(instruction: $crate::jolt::vm::rv32i_vm::RV32I) -> Self {
+                match instruction {
+                    $($crate::jolt::vm::rv32i_vm::RV32I::$base_alias(instruction) => {
+                        $enum_name::$base_alias(instruction)
+                    })+
+                }
             }
         }
     };
 }
 
-/// Generates an enum out of a list of LassoSubtable types. All LassoSubtable methods
-/// are callable on the enum type via enum_dispatch.
-macro_rules! subtable_enum {
-    ($enum_name:ident, $($alias:ident: $struct:ty),+) => {
-        #[allow(non_camel_case_types)]
-        #[repr(usize)]
-        #[enum_dispatch(LassoSubtable<F>)]
-        #[derive(EnumCountMacro, EnumIter)]
-        pub enum $enum_name<F: JoltField> { $($alias($struct)),+ }
-        impl<F: JoltField> From<SubtableId> for $enum_name<F> {
-          fn from(subtable_id: SubtableId) -> Self {
-            $(
-              if subtable_id == TypeId::of::<$struct>() {
-                $enum_name::from(<$struct>::new())
-              } else
-            )+
-            { panic!("Unexpected subtable id {:?}", subtable_id) } // TODO(moodlezoup): better error handling
-          }
-        }
+/// Generates a subtable enum consisting of the RV32I subtables followed by the given
+/// subtables, e.g. `rv32i_subtable_enum!(MySubtables, MY_TABLE: MyTableSubtable<F>)`.
+#[macro_export]
+macro_rules! rv32i_subtable_enum {
+    ($enum_name:ident $(, $alias:ident: $struct:ty)* $(,)?) => {
+        $crate::subtable_enum!(
+            $enum_name,
+            AND: $crate::jolt::subtable::and::AndSubtable<F>,
+            DIV_BY_ZERO: $crate::jolt::subtable::div_by_zero::DivByZeroSubtable<F>,
+            EQ_ABS: $crate::jolt::subtable::eq_abs::EqAbsSubtable<F>,
+            EQ_MSB: $crate::jolt::subtable::eq_msb::EqMSBSubtable<F>,
+            EQ: $crate::jolt::subtable::eq::EqSubtable<F>,
+            GT_MSB: $crate::jolt::subtable::gt_msb::GtMSBSubtable<F>,
+            IDENTITY: $crate::jolt::subtable::identity::IdentitySubtable<F>,
+            LEFT_IS_ZERO: $crate::jolt::subtable::left_is_zero::LeftIsZeroSubtable<F>,
+            LT_ABS: $crate::jolt::subtable::lt_abs::LtAbsSubtable<F>,
+            LTU: $crate::jolt::subtable::ltu::LtuSubtable<F>,
+            OR: $crate::jolt::subtable::or::OrSubtable<F>,
+            RIGHT_IS_ZERO: $crate::jolt::subtable::right_is_zero::RightIsZeroSubtable<F>,
+            SIGN_EXTEND_8: $crate::jolt::subtable::sign_extend::SignExtendSubtable<F, 8>,
+            SIGN_EXTEND_16: $crate::jolt::subtable::sign_extend::SignExtendSubtable<F, 16>,
+            SLL0: $crate::jolt::subtable::sll::SllSubtable<F, 0, { $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+            SLL1: $crate::jolt::subtable::sll::SllSubtable<F, 1, { $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+            SLL2: $crate::jolt::subtable::sll::SllSubtable<F, 2, { $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+            SLL3: $crate::jolt::subtable::sll::SllSubtable<F, 3, { $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+            SRA_SIGN: $crate::jolt::subtable::sra_sign::SraSignSubtable<F, { $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+            SRL0: $crate::jolt::subtable::srl::SrlSubtable<F, 0, { $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+            SRL1: $crate::jolt::subtable::srl::SrlSubtable<F, 1, { $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+            SRL2: $crate::jolt::subtable::srl::SrlSubtable<F, 2, { $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+            SRL3: $crate::jolt::subtable::srl::SrlSubtable<F, 3, { $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+            TRUNCATE: $crate::jolt::subtable::truncate_overflow::TruncateOverflowSubtable<F, { $crate::jolt::vm::rv32i_vm::WORD_SIZE }>,
+            TRUNCATE_BYTE: $crate::jolt::subtable::truncate_overflow::TruncateOverflowSubtable<F, 8>,
+            XOR: $crate::jolt::subtable::xor::XorSubtable<F>
+            $(, $alias: $struct)*
+        );
+    };
+}
 
-        impl<F: JoltField> From<$enum_name<F>> for usize {
-            fn from(subtable: $enum_name<F>) -> usize {
-                unsafe { *<*const _>::from(&subtable).cast::<usize>() }
-            }
+rv32i_instruction_set!(RV32I);
+
+impl JoltInstructionSet for RV32I {
+    fn virtual_sequence(trace_row: RVTraceRow) -> Vec<RVTraceRow> {
+        with_remaining_counts(match trace_row.instruction.opcode {
+            RV32IM::MULH => MULHInstruction::<WORD_SIZE>::virtual_sequence(trace_row),
+            RV32IM::MULHSU => MULHSUInstruction::<WORD_SIZE>::virtual_sequence(trace_row),
+            RV32IM::DIV => DIVInstruction::<WORD_SIZE>::virtual_sequence(trace_row),
+            RV32IM::DIVU => DIVUInstruction::<WORD_SIZE>::virtual_sequence(trace_row),
+            RV32IM::REM => REMInstruction::<WORD_SIZE>::virtual_sequence(trace_row),
+            RV32IM::REMU => REMUInstruction::<WORD_SIZE>::virtual_sequence(trace_row),
+            RV32IM::LR_W => LRInstruction::virtual_sequence(trace_row),
+            RV32IM::SC_W => SCInstruction::virtual_sequence(trace_row),
+            RV32IM::AMOSWAP_W
+            | RV32IM::AMOADD_W
+            | RV32IM::AMOXOR_W
+            | RV32IM::AMOAND_W
+            | RV32IM::AMOOR_W
+            | RV32IM::AMOMIN_W
+            | RV32IM::AMOMAX_W
+            | RV32IM::AMOMINU_W
+            | RV32IM::AMOMAXU_W => AMOInstruction::virtual_sequence(trace_row),
+            _ => vec![trace_row],
+        })
+    }
+
+    fn bytecode_sequence(instruction: &ELFInstruction) -> Vec<ELFInstruction> {
+        match instruction.opcode {
+            RV32IM::MULH => MULHInstruction::<WORD_SIZE>::sequence_instructions(instruction),
+            RV32IM::MULHSU => MULHSUInstruction::<WORD_SIZE>::sequence_instructions(instruction),
+            RV32IM::DIV => DIVInstruction::<WORD_SIZE>::sequence_instructions(instruction),
+            RV32IM::DIVU => DIVUInstruction::<WORD_SIZE>::sequence_instructions(instruction),
+            RV32IM::REM => REMInstruction::<WORD_SIZE>::sequence_instructions(instruction),
+            RV32IM::REMU => REMUInstruction::<WORD_SIZE>::sequence_instructions(instruction),
+            RV32IM::LR_W => LRInstruction::sequence_instructions(instruction),
+            RV32IM::SC_W => SCInstruction::sequence_instructions(instruction),
+            RV32IM::AMOSWAP_W
+            | RV32IM::AMOADD_W
+            | RV32IM::AMOXOR_W
+            | RV32IM::AMOAND_W
+            | RV32IM::AMOOR_W
+            | RV32IM::AMOMIN_W
+            | RV32IM::AMOMAX_W
+            | RV32IM::AMOMINU_W
+            | RV32IM::AMOMAXU_W => AMOInstruction::sequence_instructions(instruction),
+            _ => vec![instruction.clone()],
         }
-        impl<F: JoltField> JoltSubtableSet<F> for $enum_name<F> {}
-    };
+    }
 }
 
-const WORD_SIZE: usize = 32;
-
-instruction_set!(
-  RV32I,
-  ADD: ADDInstruction<WORD_SIZE>,
-  SUB: SUBInstruction<WORD_SIZE>,
-  AND: ANDInstruction,
-  OR: ORInstruction,
-  XOR: XORInstruction,
-  LB: LBInstruction,
-  LH: LHInstruction,
-  SB: SBInstruction,
-  SH: SHInstruction,
-  SW: SWInstruction,
-  BEQ: BEQInstruction,
-  BGE: BGEInstruction,
-  BGEU: BGEUInstruction,
-  BNE: BNEInstruction,
-  SLT: SLTInstruction,
-  SLTU: SLTUInstruction,
-  SLL: SLLInstruction<WORD_SIZE>,
-  SRA: SRAInstruction<WORD_SIZE>,
-  SRL: SRLInstruction<WORD_SIZE>,
-  MOVSIGN: MOVSIGNInstruction<WORD_SIZE>,
-  MUL: MULInstruction<WORD_SIZE>,
-  MULU: MULUInstruction<WORD_SIZE>,
-  MULHU: MULHUInstruction<WORD_SIZE>,
-  VIRTUAL_ADVICE: ADVICEInstruction<WORD_SIZE>,
-  VIRTUAL_ASSERT_LTE: ASSERTLTEInstruction,
-  VIRTUAL_ASSERT_LT_ABS: ASSERTLTABSInstruction<WORD_SIZE>,
-  VIRTUAL_ASSERT_EQ_SIGNS: ASSERTEQSIGNSInstruction
-);
-subtable_enum!(
-  RV32ISubtables,
-  AND: AndSubtable<F>,
-  EQ_ABS: EqAbsSubtable<F>,
-  EQ_MSB: EqMSBSubtable<F>,
-  EQ: EqSubtable<F>,
-  GT_MSB: GtMSBSubtable<F>,
-  IDENTITY: IdentitySubtable<F>,
-  LT_ABS: LtAbsSubtable<F>,
-  LTU: LtuSubtable<F>,
-  OR: OrSubtable<F>,
-  SIGN_EXTEND_8: SignExtendSubtable<F, 8>,
-  SIGN_EXTEND_16: SignExtendSubtable<F, 16>,
-  SLL0: SllSubtable<F, 0, WORD_SIZE>,
-  SLL1: SllSubtable<F, 1, WORD_SIZE>,
-  SLL2: SllSubtable<F, 2, WORD_SIZE>,
-  SLL3: SllSubtable<F, 3, WORD_SIZE>,
-  SRA_SIGN: SraSignSubtable<F, WORD_SIZE>,
-  SRL0: SrlSubtable<F, 0, WORD_SIZE>,
-  SRL1: SrlSubtable<F, 1, WORD_SIZE>,
-  SRL2: SrlSubtable<F, 2, WORD_SIZE>,
-  SRL3: SrlSubtable<F, 3, WORD_SIZE>,
-  TRUNCATE: TruncateOverflowSubtable<F, WORD_SIZE>,
-  TRUNCATE_BYTE: TruncateOverflowSubtable<F, 8>,
-  XOR: XorSubtable<F>
-);
+rv32i_subtable_enum!(RV32ISubtables);
 
 // ==================== JOLT ====================
 
@@ -157,25 +188,50 @@ where
 }
 
 pub type RV32IJoltProof<F, CS> = JoltProof<C, M, F, CS, RV32I, RV32ISubtables<F>>;
+pub type RV32ISegmentedProof<F, CS> = JoltSegmentedProof<C, M, F, CS, RV32I, RV32ISubtables<F>>;
 
 // ==================== TEST ====================
 
 #[cfg(test)]
 mod tests {
     use ark_bn254::{Bn254, Fr, G1Projective};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 
     use std::collections::HashSet;
 
+    use common::constants::{
+        CONSOLE_ADDRESS, DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE, RAM_START_ADDRESS,
+    };
+    use common::rv_trace::{MemoryLayout, NUM_CIRCUIT_FLAGS};
+    use rand::{prelude::StdRng, SeedableRng};
+    use serde::{Deserialize, Serialize};
+    use tracer::test_utils::{
+        add, addi, amoadd_w, amoswap_w, c_addi, c_li, div, jal, li, lr_w, lw, sb, sc_w, sw,
+        terminate, words,
+    };
+    use tracer::{ELFInstruction, RVTraceRow, RV32IM};
+
     use crate::field::JoltField;
     use crate::host;
-    use crate::jolt::instruction::JoltInstruction;
-    use crate::jolt::vm::rv32i_vm::{Jolt, RV32IJoltVM, C, M};
+    use crate::jolt::instruction::{
+        add::ADDInstruction, beq::BEQInstruction, JoltInstruction, JoltInstructionSet,
+        SubtableIndices,
+    };
+    use crate::jolt::subtable::LassoSubtable;
+    use crate::jolt::vm::bytecode::BytecodeRow;
+    use crate::jolt::vm::observer::ProverEvent;
+    use crate::jolt::vm::rv32i_vm::{Jolt, RV32IJoltProof, RV32IJoltVM, C, M, RV32I};
+    use crate::jolt::vm::{
+        segment_lengths, JoltCommitments, JoltProverKey, JoltTraceStep, JoltVerifierKey,
+    };
     use crate::poly::commitment::commitment_scheme::CommitmentScheme;
     use crate::poly::commitment::hyperkzg::HyperKZG;
     use crate::poly::commitment::hyrax::HyraxScheme;
     use crate::poly::commitment::mock::MockCommitScheme;
     use crate::poly::commitment::zeromorph::Zeromorph;
-    use std::sync::Mutex;
+    use crate::r1cs::constraints::R1CSBuilder;
+    use crate::utils::errors::{HostError, ProofVerifyError};
+    use std::sync::{Arc, Mutex};
     use strum::{EnumCount, IntoEnumIterator};
 
     // If multiple tests try to read the same trace artifacts simultaneously, they will fail
@@ -184,28 +240,214 @@ mod tests {
         static ref SHA3_FILE_LOCK: Mutex<()> = Mutex::new(());
     }
 
-    fn test_instruction_set_subtables<PCS: CommitmentScheme>() {
+    fn test_instruction_set_subtables<
+        PCS: CommitmentScheme<Field = Fr>,
+        VM: Jolt<Fr, PCS, C, M>,
+    >() {
         let mut subtable_set: HashSet<_> = HashSet::new();
-        for instruction in <RV32IJoltVM as Jolt<_, PCS, C, M>>::InstructionSet::iter() {
+        for instruction in VM::InstructionSet::iter() {
             for (subtable, _) in instruction.subtables::<Fr>(C, M) {
                 // panics if subtable cannot be cast to enum variant
-                let _ =
-                    <RV32IJoltVM as Jolt<_, PCS, C, M>>::Subtables::from(subtable.subtable_id());
+                let _ = VM::Subtables::from(subtable.subtable_id());
                 subtable_set.insert(subtable.subtable_id());
             }
         }
         assert_eq!(
             subtable_set.len(),
-            <RV32IJoltVM as Jolt<_, PCS, C, M>>::Subtables::COUNT,
+            VM::Subtables::COUNT,
             "Unused enum variants in Subtables"
         );
     }
 
     #[test]
     fn instruction_set_subtables() {
-        test_instruction_set_subtables::<HyraxScheme<G1Projective>>();
-        test_instruction_set_subtables::<Zeromorph<Bn254>>();
-        test_instruction_set_subtables::<HyperKZG<Bn254>>();
+        test_instruction_set_subtables::<HyraxScheme<G1Projective>, RV32IJoltVM>();
+        test_instruction_set_subtables::<Zeromorph<Bn254>, RV32IJoltVM>();
+        test_instruction_set_subtables::<HyperKZG<Bn254>, RV32IJoltVM>();
+    }
+
+    /// Sets rd to 1 if rs1 == rs2, encoded as a custom-0 instruction with funct3 = 0.
+    #[derive(Copy, Clone, Default, Debug, Serialize, Deserialize)]
+    pub struct SEQInstruction(u64, u64);
+
+    impl JoltInstruction for SEQInstruction {
+        fn operands(&self) -> (u64, u64) {
+            (self.0, self.1)
+        }
+
+        fn combine_lookups<F: JoltField>(&self, vals: &[F], c: usize, m: usize) -> F {
+            BEQInstruction(self.0, self.1).combine_lookups(vals, c, m)
+        }
+
+        fn g_poly_degree(&self, c: usize) -> usize {
+            BEQInstruction(self.0, self.1).g_poly_degree(c)
+        }
+
+        fn subtables<F: JoltField>(
+            &self,
+            c: usize,
+            m: usize,
+        ) -> Vec<(Box<dyn LassoSubtable<F>>, SubtableIndices)> {
+            BEQInstruction(self.0, self.1).subtables(c, m)
+        }
+
+        fn to_indices(&self, c: usize, log_m: usize) -> Vec<usize> {
+            BEQInstruction(self.0, self.1).to_indices(c, log_m)
+        }
+
+        fn lookup_entry(&self) -> u64 {
+            BEQInstruction(self.0, self.1).lookup_entry()
+        }
+
+        fn random(&self, rng: &mut StdRng) -> Self {
+            let BEQInstruction(x, y) = BEQInstruction::default().random(rng);
+            Self(x, y)
+        }
+    }
+
+    rv32i_instruction_set!(RV32IWithSEQ, SEQ: SEQInstruction);
+    rv32i_subtable_enum!(RV32IWithSEQSubtables);
+
+    impl TryFrom<&ELFInstruction> for RV32IWithSEQ {
+        type Error = &'static str;
+
+        fn try_from(instruction: &ELFInstruction) -> Result<Self, Self::Error> {
+            match (instruction.opcode, instruction.custom_funct()) {
+                (RV32IM::CUSTOM_0, Some((0, 0))) => Ok(SEQInstruction::default().into()),
+                _ => RV32I::try_from(instruction).map(Into::into),
+            }
+        }
+    }
+
+    impl TryFrom<&RVTraceRow> for RV32IWithSEQ {
+        type Error = &'static str;
+
+        fn try_from(row: &RVTraceRow) -> Result<Self, Self::Error> {
+            match (row.instruction.opcode, row.instruction.custom_funct()) {
+                (RV32IM::CUSTOM_0, Some((0, 0))) => Ok(SEQInstruction(
+                    row.register_state.rs1_val.unwrap(),
+                    row.register_state.rs2_val.unwrap(),
+                )
+                .into()),
+                _ => RV32I::try_from(row).map(Into::into),
+            }
+        }
+    }
+
+    impl JoltInstructionSet for RV32IWithSEQ {
+        fn virtual_sequence(trace_row: RVTraceRow) -> Vec<RVTraceRow> {
+            RV32I::virtual_sequence(trace_row)
+        }
+
+        fn bytecode_sequence(instruction: &ELFInstruction) -> Vec<ELFInstruction> {
+            RV32I::bytecode_sequence(instruction)
+        }
+    }
+
+    enum RV32IWithSEQJoltVM {}
+
+    impl<F, CS> Jolt<F, CS, C, M> for RV32IWithSEQJoltVM
+    where
+        F: JoltField,
+        CS: CommitmentScheme<Field = F>,
+    {
+        type InstructionSet = RV32IWithSEQ;
+        type Subtables = RV32IWithSEQSubtables<F>;
+    }
+
+    #[test]
+    fn custom_instruction_set() {
+        test_instruction_set_subtables::<HyraxScheme<G1Projective>, RV32IWithSEQJoltVM>();
+        let mut rng = StdRng::seed_from_u64(1234567890);
+        for _ in 0..32 {
+            let _ = RV32IWithSEQ::random_instruction(&mut rng);
+        }
+
+        // RV32I instructions keep their flag index
+        let add = RV32I::ADD(ADDInstruction::default());
+        assert_eq!(
+            RV32I::enum_index(&add),
+            RV32IWithSEQ::enum_index(&add.into())
+        );
+
+        // The custom instruction gets the last instruction flag, and is a concat query
+        let seq = ELFInstruction {
+            address: 0,
+            opcode: RV32IM::CUSTOM_0,
+            rs1: Some(1),
+            rs2: Some(2),
+            rd: Some(3),
+            imm: Some(0),
+            virtual_sequence_index: None,
+            virtual_sequence_remaining: None,
+            is_compressed: false,
+        };
+        assert_eq!(RV32IWithSEQ::COUNT, RV32I::COUNT + 1);
+        let bitflags = BytecodeRow::bitflags::<RV32IWithSEQ>(&seq);
+        assert_eq!(bitflags & ((1 << RV32IWithSEQ::COUNT) - 1), 1);
+        assert!(seq.to_circuit_flags()[8]);
+        let unsupported = ELFInstruction {
+            imm: Some(1),
+            ..seq.clone()
+        };
+        assert!(RV32IWithSEQ::try_from(&unsupported).is_err());
+
+        // The R1CS has one more instruction flag (and its binary check)
+        let mut rv32i_shape = R1CSBuilder::<RV32I>::default();
+        R1CSBuilder::jolt_r1cs_matrices(&mut rv32i_shape, RAM_START_ADDRESS);
+        let mut custom_shape = R1CSBuilder::<RV32IWithSEQ>::default();
+        R1CSBuilder::jolt_r1cs_matrices(&mut custom_shape, RAM_START_ADDRESS);
+        assert_eq!(custom_shape.num_variables, rv32i_shape.num_variables + 1);
+        assert_eq!(
+            custom_shape.num_constraints,
+            rv32i_shape.num_constraints + 1
+        );
+    }
+
+    #[test]
+    fn verifier_key_serialization() {
+        let add = ELFInstruction {
+            address: RAM_START_ADDRESS,
+            opcode: RV32IM::ADD,
+            rs1: Some(1),
+            rs2: Some(2),
+            rd: Some(3),
+            imm: None,
+            virtual_sequence_index: None,
+            virtual_sequence_remaining: None,
+            is_compressed: false,
+        };
+        let memory_init = (0..4).map(|i| (RAM_START_ADDRESS + i, 0)).collect();
+        let prover_key: JoltProverKey<Fr, HyraxScheme<G1Projective>> = RV32IJoltVM::preprocess(
+            vec![add],
+            memory_init,
+            MemoryLayout::new(DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE),
+            1 << 10,
+            1 << 10,
+            1 << 10,
+        );
+        let verifier_key = prover_key.verifier_key();
+
+        // The verifier key doesn't contain the materialized subtables
+        assert!(verifier_key.compressed_size() < prover_key.compressed_size() / 100);
+
+        let mut bytes = vec![];
+        verifier_key.serialize_compressed(&mut bytes).unwrap();
+        let deserialized =
+            JoltVerifierKey::<Fr, HyraxScheme<G1Projective>>::deserialize_compressed(&bytes[..])
+                .unwrap();
+        let mut reserialized = vec![];
+        deserialized
+            .serialize_compressed(&mut reserialized)
+            .unwrap();
+        assert_eq!(bytes, reserialized);
+
+        let mut bytes = vec![];
+        prover_key.serialize_compressed(&mut bytes).unwrap();
+        assert!(
+            JoltProverKey::<Fr, HyraxScheme<G1Projective>>::deserialize_compressed(&bytes[..])
+                .is_ok()
+        );
     }
 
     #[test]
@@ -231,19 +473,28 @@ mod tests {
 
         let mut program = host::Program::new("fibonacci-guest");
         program.set_input(&9u32);
-        let (bytecode, memory_init) = program.decode();
-        let (io_device, trace, circuit_flags) = program.trace();
+        let (bytecode, memory_init) = program.decode().unwrap();
+        let (io_device, trace, circuit_flags) = program.trace().unwrap();
 
-        let preprocessing =
-            RV32IJoltVM::preprocess(bytecode.clone(), memory_init, 1 << 20, 1 << 20, 1 << 20);
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        );
         let (proof, commitments) =
             <RV32IJoltVM as Jolt<Fr, HyraxScheme<G1Projective>, C, M>>::prove(
                 io_device,
                 trace,
                 circuit_flags,
-                preprocessing.clone(),
+                &preprocessing,
             );
-        let verification_result = RV32IJoltVM::verify(preprocessing, proof, commitments);
+        assert_eq!(proof.size().total(), proof.compressed_size());
+        assert_eq!(commitments.size().total(), commitments.compressed_size());
+        let verification_result =
+            RV32IJoltVM::verify(&preprocessing.verifier_key(), proof, commitments);
         assert!(
             verification_result.is_ok(),
             "Verification failed with error: {:?}",
@@ -251,24 +502,520 @@ mod tests {
         );
     }
 
+    /// Proves a program built by hand from `code`, see `host::test_program`.
+    fn prove_test_program(
+        name: &str,
+        code: &[u32],
+    ) -> (
+        JoltProverKey<Fr, HyraxScheme<G1Projective>>,
+        RV32IJoltProof<Fr, HyraxScheme<G1Projective>>,
+        JoltCommitments<HyraxScheme<G1Projective>>,
+    ) {
+        prove_test_text(name, &words(code))
+    }
+
+    /// Same as `prove_test_program`, but takes the program's text as bytes, see
+    /// `host::test_program_text`.
+    fn prove_test_text(
+        name: &str,
+        text: &[u8],
+    ) -> (
+        JoltProverKey<Fr, HyraxScheme<G1Projective>>,
+        RV32IJoltProof<Fr, HyraxScheme<G1Projective>>,
+        JoltCommitments<HyraxScheme<G1Projective>>,
+    ) {
+        let mut program = host::test_program_text(name, text);
+        let (bytecode, memory_init) = program.decode().unwrap();
+        let (io_device, trace, circuit_flags) = program.trace().unwrap();
+
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode,
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 10,
+            1 << 16,
+            1 << 10,
+        );
+        let (proof, commitments) =
+            RV32IJoltVM::prove(io_device, trace, circuit_flags, &preprocessing);
+        (preprocessing, proof, commitments)
+    }
+
+    #[test]
+    fn proof_and_commitment_sizes() {
+        let termination =
+            MemoryLayout::new(DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE).termination;
+        let code = [vec![addi(5, 0, 1), add(6, 5, 5)], terminate(termination)].concat();
+        let (_, proof, commitments) = prove_test_program("sizes", &code);
+
+        let proof_size = proof.size();
+        assert_eq!(proof_size.total(), proof.compressed_size());
+        for component in [
+            proof_size.program_io,
+            proof_size.bytecode,
+            proof_size.read_write_memory,
+            proof_size.output_sumcheck,
+            proof_size.timestamp_range_check,
+            proof_size.instruction_lookups,
+            proof_size.r1cs,
+        ] {
+            assert!(component > 0);
+        }
+        let printed = proof_size.to_string();
+        assert_eq!(printed.lines().count(), 9);
+        assert!(printed.ends_with(&format!("{:<24} {:>12} B\n", "total", proof_size.total())));
+
+        let commitment_size = commitments.size();
+        assert_eq!(commitment_size.total(), commitments.compressed_size());
+        for component in [
+            commitment_size.bytecode,
+            commitment_size.read_write_memory,
+            commitment_size.timestamp_range_check,
+            commitment_size.instruction_lookups,
+            commitment_size.r1cs,
+        ] {
+            assert!(component > 0);
+        }
+        let printed = commitment_size.to_string();
+        assert_eq!(printed.lines().count(), 6);
+        assert!(printed.ends_with(&format!(
+            "{:<24} {:>12} B\n",
+            "total",
+            commitment_size.total()
+        )));
+    }
+
+    #[test]
+    fn commitment_shapes_count_committed_polynomials() {
+        let termination =
+            MemoryLayout::new(DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE).termination;
+        let code = [vec![addi(5, 0, 1), add(6, 5, 5)], terminate(termination)].concat();
+        let (preprocessing, _, commitments) = prove_test_program("shapes", &code);
+
+        let shapes = <RV32IJoltVM as Jolt<Fr, HyraxScheme<G1Projective>, C, M>>::commitment_shapes(
+            &preprocessing.instruction_lookups,
+            1 << 10,
+            1 << 16,
+            1 << 10,
+        );
+        let num_polys: usize = shapes.iter().map(|shape| shape.num_polys).sum();
+        let num_commitments = commitments.bytecode.trace_commitments.len()
+            + 1
+            + commitments.read_write_memory.trace_commitments.len()
+            + 2
+            + commitments.timestamp_range_check.commitments.len()
+            + commitments.instruction_lookups.trace_commitment.len()
+            + commitments.instruction_lookups.final_commitment.len();
+        assert_eq!(num_polys, num_commitments);
+    }
+
+    #[test]
+    fn compressed_e2e() {
+        let termination =
+            MemoryLayout::new(DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE).termination;
+        // A full instruction at a 2-byte aligned address, between two compressed ones
+        let text = [
+            c_li(5, 1).to_le_bytes().to_vec(),
+            words(&[addi(6, 5, 1)]),
+            c_addi(6, -3).to_le_bytes().to_vec(),
+            words(&[add(7, 6, 5)]),
+            words(&terminate(termination)),
+        ]
+        .concat();
+        let (preprocessing, proof, commitments) = prove_test_text("compressed", &text);
+
+        let verification_result =
+            RV32IJoltVM::verify(&preprocessing.verifier_key(), proof, commitments);
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+    }
+
+    #[test]
+    fn atomics_e2e() {
+        let termination =
+            MemoryLayout::new(DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE).termination;
+        let address = RAM_START_ADDRESS as u32 + 0x100;
+        let code = [
+            li(5, address),
+            vec![
+                addi(6, 0, 7),
+                sw(6, 5, 0),
+                addi(7, 0, 3),
+                amoadd_w(10, 5, 7),
+                amoswap_w(11, 5, 7),
+                lr_w(12, 5),
+                sc_w(13, 5, 6),
+                // The reservation was used up by the previous SC.W, so this one fails
+                sc_w(14, 5, 7),
+                lw(15, 5, 0),
+            ],
+            terminate(termination),
+        ]
+        .concat();
+        let (preprocessing, proof, commitments) = prove_test_program("atomics", &code);
+
+        let verification_result =
+            RV32IJoltVM::verify(&preprocessing.verifier_key(), proof, commitments);
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+    }
+
+    /// Divides -7 by 2, which is proven as DIV's virtual sequence.
+    fn div_program() -> Vec<u32> {
+        let termination =
+            MemoryLayout::new(DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE).termination;
+        [
+            li(5, -7i32 as u32),
+            li(6, 2),
+            vec![div(7, 5, 6)],
+            terminate(termination),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn div_e2e() {
+        let (preprocessing, proof, commitments) = prove_test_program("div", &div_program());
+
+        let verification_result =
+            RV32IJoltVM::verify(&preprocessing.verifier_key(), proof, commitments);
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "witness is not a satisfying assignment")]
+    fn skipped_virtual_assert_is_rejected() {
+        let mut program = host::test_program("div-skipped-assert", &div_program());
+        let (bytecode, memory_init) = program.decode().unwrap();
+        let (io_device, mut trace, _) = program.trace::<Fr, RV32I>().unwrap();
+
+        // Drop one of the asserts of DIV's virtual sequence, as a prover could if the steps of a
+        // virtual sequence weren't constrained to follow each other
+        let assert_flag = 1 << (RV32I::COUNT + NUM_CIRCUIT_FLAGS - 1 - 10);
+        let assert_step = trace
+            .iter()
+            .position(|step| step.bytecode_row.bitflags & assert_flag != 0)
+            .unwrap();
+        trace.remove(assert_step);
+        let circuit_flags = JoltTraceStep::circuit_flags(&trace);
+
+        let preprocessing: JoltProverKey<Fr, HyraxScheme<G1Projective>> = RV32IJoltVM::preprocess(
+            bytecode,
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 10,
+            1 << 16,
+            1 << 10,
+        );
+        let _ = RV32IJoltVM::prove(io_device, trace, circuit_flags, &preprocessing);
+    }
+
+    #[test]
+    fn overlong_trace_is_rejected() {
+        let termination =
+            MemoryLayout::new(DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE).termination;
+        let (preprocessing, mut proof, commitments) =
+            prove_test_program("overlong-trace", &terminate(termination));
+
+        // The Spartan key would otherwise be derived from the prover's claim
+        proof.trace_length = preprocessing.max_trace_length + 1;
+        assert!(matches!(
+            RV32IJoltVM::verify(&preprocessing.verifier_key(), proof, commitments),
+            Err(ProofVerifyError::TraceTooLong(length, max))
+                if length == max + 1 && max == 1 << 10
+        ));
+    }
+
+    #[test]
+    fn fib_e2e_segmented() {
+        let _guard = FIB_FILE_LOCK.lock().unwrap();
+
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let (bytecode, memory_init) = program.decode().unwrap();
+        let (io_device, trace, _) = program.trace::<Fr, RV32I>().unwrap();
+
+        let segment_length = 64;
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 20,
+            1 << 20,
+            segment_length,
+        );
+        let verifier_key = preprocessing.verifier_key();
+        let prove = |trace| {
+            <RV32IJoltVM as Jolt<Fr, HyraxScheme<G1Projective>, C, M>>::prove_segmented(
+                io_device.clone(),
+                trace,
+                segment_length,
+                &preprocessing,
+                &mut |_| {},
+            )
+        };
+
+        let proof = prove(trace.clone());
+        assert!(proof.segments.len() >= trace.len().div_ceil(segment_length));
+        let verification_result = RV32IJoltVM::verify_segmented(&verifier_key, proof);
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+
+        // Dropping a segment breaks the chain of memory states
+        let mut proof = prove(trace);
+        proof.segments.remove(1);
+        assert!(matches!(
+            RV32IJoltVM::verify_segmented(&verifier_key, proof),
+            Err(ProofVerifyError::SegmentNotLinked(1))
+        ));
+    }
+
+    #[test]
+    fn fib_e2e_program_segmented() {
+        let _guard = FIB_FILE_LOCK.lock().unwrap();
+
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let (bytecode, memory_init) = program.decode().unwrap();
+        let (io_device, trace, _) = program.clone().trace::<Fr, RV32I>().unwrap();
+
+        let segment_length = 64;
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 20,
+            1 << 20,
+            segment_length,
+        );
+        let mut finished_segments = vec![];
+        let proof =
+            <RV32IJoltVM as Jolt<Fr, HyraxScheme<G1Projective>, C, M>>::prove_program_segmented(
+                &mut program,
+                segment_length,
+                &preprocessing,
+                &mut |event| {
+                    if let ProverEvent::SegmentFinished { segment, .. } = event {
+                        finished_segments.push(segment);
+                    }
+                },
+            )
+            .unwrap();
+
+        let num_segments = proof.segments.len();
+        assert!(num_segments >= trace.len().div_ceil(segment_length));
+        assert_eq!(finished_segments, (0..num_segments).collect::<Vec<_>>());
+        assert_eq!(proof.segments.last().unwrap().0.program_io, io_device);
+        let verification_result =
+            RV32IJoltVM::verify_segmented(&preprocessing.verifier_key(), proof);
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+
+        assert!(matches!(
+            <RV32IJoltVM as Jolt<Fr, HyraxScheme<G1Projective>, C, M>>::prove_program_segmented(
+                &mut program,
+                segment_length - 1,
+                &preprocessing,
+                &mut |_| {},
+            ),
+            Err(HostError::SegmentLength(63))
+        ));
+    }
+
+    #[test]
+    fn console_e2e() {
+        let termination =
+            MemoryLayout::new(DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE).termination;
+        let print = |message: &[u8]| {
+            message
+                .iter()
+                .flat_map(|&byte| [addi(6, 0, byte as u32), sb(6, 5, 0)])
+                .collect::<Vec<_>>()
+        };
+        let code = [
+            li(5, CONSOLE_ADDRESS as u32),
+            print(b"hello\0"),
+            terminate(termination),
+        ]
+        .concat();
+        let mut program = host::test_program("console", &code);
+        let messages = Arc::new(Mutex::new(vec![]));
+        let console = messages.clone();
+        program.set_console(move |message| console.lock().unwrap().push(message.to_vec()));
+        let (bytecode, memory_init) = program.decode().unwrap();
+
+        let segment_length = 8;
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode,
+            memory_init,
+            program.memory_layout(),
+            1 << 10,
+            1 << 16,
+            segment_length,
+        );
+        // The guest runs twice, but prints once
+        let proof =
+            <RV32IJoltVM as Jolt<Fr, HyraxScheme<G1Projective>, C, M>>::prove_program_segmented(
+                &mut program,
+                segment_length,
+                &preprocessing,
+                &mut |_| {},
+            )
+            .unwrap();
+        assert_eq!(*messages.lock().unwrap(), [b"hello".to_vec()]);
+
+        // The message is not part of the program I/O
+        let program_io = &proof.segments.last().unwrap().0.program_io;
+        assert!(program_io.inputs.is_empty() && program_io.outputs.is_empty());
+        let verification_result =
+            RV32IJoltVM::verify_segmented(&preprocessing.verifier_key(), proof);
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+    }
+
+    #[test]
+    fn segments_are_linked_by_pc() {
+        let termination =
+            MemoryLayout::new(DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE).termination;
+        // Segments of 8 steps: the first ends with a jump over `addi x5, x0, 1`, the second
+        // only runs no-ops, so that dropping it keeps the chain of memory states intact.
+        let code = [
+            vec![addi(0, 0, 0); 7],
+            vec![jal(0, 8), addi(5, 0, 1)],
+            vec![addi(0, 0, 0); 8],
+            terminate(termination),
+        ]
+        .concat();
+        let mut program = host::test_program("segments-pc", &code);
+        let (bytecode, memory_init) = program.decode().unwrap();
+        let (io_device, trace, _) = program.trace::<Fr, RV32I>().unwrap();
+
+        let segment_length = 8;
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode,
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 10,
+            1 << 16,
+            segment_length,
+        );
+        let verifier_key = preprocessing.verifier_key();
+        let prove = || {
+            <RV32IJoltVM as Jolt<Fr, HyraxScheme<G1Projective>, C, M>>::prove_segmented(
+                io_device.clone(),
+                trace.clone(),
+                segment_length,
+                &preprocessing,
+                &mut |_| {},
+            )
+        };
+
+        let proof = prove();
+        assert_eq!(proof.segments.len(), 3);
+        let verification_result = RV32IJoltVM::verify_segmented(&verifier_key, proof);
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+
+        let mut proof = prove();
+        proof.segments.remove(1);
+        assert!(matches!(
+            RV32IJoltVM::verify_segmented(&verifier_key, proof),
+            Err(ProofVerifyError::SegmentPCNotLinked(1))
+        ));
+
+        // The next PC commitment is taken from the step shape, not from the number of
+        // commitments the proof brings: an extra one is rejected rather than opened at the exit
+        let mut proof = prove();
+        let r1cs = proof.segments[0].1.r1cs.as_mut().unwrap();
+        r1cs.aux.push(HyraxScheme::<G1Projective>::commit_slice(
+            &[Fr::from(0u64); 8],
+            &preprocessing.generators,
+        ));
+        assert!(matches!(
+            RV32IJoltVM::verify_segmented(&verifier_key, proof),
+            Err(ProofVerifyError::SegmentShapeMismatch)
+        ));
+
+        let mut proof = prove();
+        proof.segments[0].1.r1cs.as_mut().unwrap().aux.clear();
+        assert!(matches!(
+            RV32IJoltVM::verify_segmented(&verifier_key, proof),
+            Err(ProofVerifyError::SegmentShapeMismatch)
+        ));
+
+        // Missing R1CS commitments are rejected before they are appended to the transcript
+        let mut proof = prove();
+        proof.segments[0].1.r1cs = None;
+        assert!(matches!(
+            RV32IJoltVM::verify_segmented(&verifier_key, proof),
+            Err(ProofVerifyError::SegmentShapeMismatch)
+        ));
+    }
+
+    #[test]
+    fn segments_end_between_instructions() {
+        // Steps of a virtual sequence share the address of the instruction they expand
+        let trace: Vec<JoltTraceStep<RV32I>> = [0, 1, 1, 1, 2, 3, 4, 4]
+            .into_iter()
+            .map(|address| JoltTraceStep {
+                bytecode_row: BytecodeRow::no_op(address),
+                ..JoltTraceStep::no_op()
+            })
+            .collect();
+        assert_eq!(segment_lengths(&trace, 3), vec![1, 3, 2, 2]);
+        assert_eq!(segment_lengths(&trace, 8), vec![8]);
+        // The last segment has more than a single step
+        assert_eq!(segment_lengths(&trace, 7), vec![6, 2]);
+        assert_eq!(segment_lengths::<RV32I>(&[], 8), vec![0]);
+    }
+
     #[test]
     fn fib_e2e_zeromorph() {
         let _guard = FIB_FILE_LOCK.lock().unwrap();
 
         let mut program = host::Program::new("fibonacci-guest");
         program.set_input(&9u32);
-        let (bytecode, memory_init) = program.decode();
-        let (io_device, trace, circuit_flags) = program.trace();
+        let (bytecode, memory_init) = program.decode().unwrap();
+        let (io_device, trace, circuit_flags) = program.trace().unwrap();
 
-        let preprocessing =
-            RV32IJoltVM::preprocess(bytecode.clone(), memory_init, 1 << 20, 1 << 20, 1 << 20);
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        );
         let (proof, commitments) = <RV32IJoltVM as Jolt<Fr, Zeromorph<Bn254>, C, M>>::prove(
             io_device,
             trace,
             circuit_flags,
-            preprocessing.clone(),
+            &preprocessing,
         );
-        let verification_result = RV32IJoltVM::verify(preprocessing, proof, commitments);
+        let verification_result =
+            RV32IJoltVM::verify(&preprocessing.verifier_key(), proof, commitments);
         assert!(
             verification_result.is_ok(),
             "Verification failed with error: {:?}",
@@ -282,18 +1029,25 @@ mod tests {
 
         let mut program = host::Program::new("fibonacci-guest");
         program.set_input(&9u32);
-        let (bytecode, memory_init) = program.decode();
-        let (io_device, trace, circuit_flags) = program.trace();
+        let (bytecode, memory_init) = program.decode().unwrap();
+        let (io_device, trace, circuit_flags) = program.trace().unwrap();
 
-        let preprocessing =
-            RV32IJoltVM::preprocess(bytecode.clone(), memory_init, 1 << 20, 1 << 20, 1 << 20);
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        );
         let (proof, commitments) = <RV32IJoltVM as Jolt<Fr, HyperKZG<Bn254>, C, M>>::prove(
             io_device,
             trace,
             circuit_flags,
-            preprocessing.clone(),
+            &preprocessing,
         );
-        let verification_result = RV32IJoltVM::verify(preprocessing, proof, commitments);
+        let verification_result =
+            RV32IJoltVM::verify(&preprocessing.verifier_key(), proof, commitments);
         assert!(
             verification_result.is_ok(),
             "Verification failed with error: {:?}",
@@ -307,20 +1061,27 @@ mod tests {
 
         let mut program = host::Program::new("sha3-guest");
         program.set_input(&[5u8; 32]);
-        let (bytecode, memory_init) = program.decode();
-        let (io_device, trace, circuit_flags) = program.trace();
+        let (bytecode, memory_init) = program.decode().unwrap();
+        let (io_device, trace, circuit_flags) = program.trace().unwrap();
 
-        let preprocessing =
-            RV32IJoltVM::preprocess(bytecode.clone(), memory_init, 1 << 20, 1 << 20, 1 << 20);
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        );
         let (jolt_proof, jolt_commitments) =
             <RV32IJoltVM as Jolt<_, HyraxScheme<G1Projective>, C, M>>::prove(
                 io_device,
                 trace,
                 circuit_flags,
-                preprocessing.clone(),
+                &preprocessing,
             );
 
-        let verification_result = RV32IJoltVM::verify(preprocessing, jolt_proof, jolt_commitments);
+        let verification_result =
+            RV32IJoltVM::verify(&preprocessing.verifier_key(), jolt_proof, jolt_commitments);
         assert!(
             verification_result.is_ok(),
             "Verification failed with error: {:?}",
@@ -334,20 +1095,27 @@ mod tests {
 
         let mut program = host::Program::new("sha3-guest");
         program.set_input(&[5u8; 32]);
-        let (bytecode, memory_init) = program.decode();
-        let (io_device, trace, circuit_flags) = program.trace();
+        let (bytecode, memory_init) = program.decode().unwrap();
+        let (io_device, trace, circuit_flags) = program.trace().unwrap();
 
-        let preprocessing =
-            RV32IJoltVM::preprocess(bytecode.clone(), memory_init, 1 << 20, 1 << 20, 1 << 20);
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        );
         let (jolt_proof, jolt_commitments) =
             <RV32IJoltVM as Jolt<_, Zeromorph<Bn254>, C, M>>::prove(
                 io_device,
                 trace,
                 circuit_flags,
-                preprocessing.clone(),
+                &preprocessing,
             );
 
-        let verification_result = RV32IJoltVM::verify(preprocessing, jolt_proof, jolt_commitments);
+        let verification_result =
+            RV32IJoltVM::verify(&preprocessing.verifier_key(), jolt_proof, jolt_commitments);
         assert!(
             verification_result.is_ok(),
             "Verification failed with error: {:?}",
@@ -361,19 +1129,26 @@ mod tests {
 
         let mut program = host::Program::new("sha3-guest");
         program.set_input(&[5u8; 32]);
-        let (bytecode, memory_init) = program.decode();
-        let (io_device, trace, circuit_flags) = program.trace();
+        let (bytecode, memory_init) = program.decode().unwrap();
+        let (io_device, trace, circuit_flags) = program.trace().unwrap();
 
-        let preprocessing =
-            RV32IJoltVM::preprocess(bytecode.clone(), memory_init, 1 << 20, 1 << 20, 1 << 20);
+        let preprocessing = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        );
         let (jolt_proof, jolt_commitments) = <RV32IJoltVM as Jolt<_, HyperKZG<Bn254>, C, M>>::prove(
             io_device,
             trace,
             circuit_flags,
-            preprocessing.clone(),
+            &preprocessing,
         );
 
-        let verification_result = RV32IJoltVM::verify(preprocessing, jolt_proof, jolt_commitments);
+        let verification_result =
+            RV32IJoltVM::verify(&preprocessing.verifier_key(), jolt_proof, jolt_commitments);
         assert!(
             verification_result.is_ok(),
             "Verification failed with error: {:?}",
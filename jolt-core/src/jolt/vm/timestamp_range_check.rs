@@ -18,8 +18,8 @@ use crate::poly::commitment::commitment_scheme::{BatchType, CommitShape, Commitm
 use crate::utils::transcript::AppendToTranscript;
 use crate::{
     lasso::memory_checking::{
-        MemoryCheckingProof, MemoryCheckingProver, MemoryCheckingVerifier, MultisetHashes,
-        NoPreprocessing,
+        check_hash_count, MemoryCheckingProof, MemoryCheckingProver, MemoryCheckingVerifier,
+        MultisetHashes, NoPreprocessing,
     },
     poly::{
         dense_mlpoly::DensePolynomial,
@@ -27,7 +27,11 @@ use crate::{
         identity_poly::IdentityPolynomial,
         structured_poly::{StructuredCommitment, StructuredOpeningProof},
     },
-    utils::{errors::ProofVerifyError, mul_0_1_optimized, transcript::ProofTranscript},
+    utils::{
+        errors::{ProofVerifyError, VerificationStage},
+        mul_0_1_optimized,
+        transcript::ProofTranscript,
+    },
 };
 
 use super::read_write_memory::MemoryCommitment;
@@ -245,7 +249,7 @@ where
 
     fn verify_openings(
         &self,
-        _generators: &C::Setup,
+        _generators: &C::VerifierSetup,
         _opening_proof: &Self::Proof,
         _commitment: &RangeCheckCommitment<C>,
         _opening_point: &[F],
@@ -409,10 +413,10 @@ where
         _: &NoPreprocessing,
         read_write_hashes: Vec<F>,
         init_final_hashes: Vec<F>,
-    ) -> MultisetHashes<F> {
+    ) -> Result<MultisetHashes<F>, ProofVerifyError> {
         let num_memories = 2 * MEMORY_OPS_PER_INSTRUCTION;
 
-        assert_eq!(read_write_hashes.len(), 2 * num_memories);
+        check_hash_count(2 * num_memories, read_write_hashes.len())?;
         let mut read_hashes = Vec::with_capacity(num_memories);
         let mut write_hashes = Vec::with_capacity(num_memories);
         for i in 0..num_memories {
@@ -420,36 +424,35 @@ where
             write_hashes.push(read_write_hashes[2 * i + 1]);
         }
 
-        assert_eq!(init_final_hashes.len(), num_memories + 1);
+        check_hash_count(num_memories + 1, init_final_hashes.len())?;
         let mut final_hashes = init_final_hashes;
         let init_hash = final_hashes.pop().unwrap();
 
-        MultisetHashes {
+        Ok(MultisetHashes {
             read_hashes,
             write_hashes,
             init_hashes: vec![init_hash],
             final_hashes,
-        }
+        })
     }
 
-    fn check_multiset_equality(_: &NoPreprocessing, multiset_hashes: &MultisetHashes<F>) {
+    fn check_multiset_equality(
+        _: &NoPreprocessing,
+        multiset_hashes: &MultisetHashes<F>,
+    ) -> Result<(), ProofVerifyError> {
         let num_memories = 2 * MEMORY_OPS_PER_INSTRUCTION;
-        assert_eq!(multiset_hashes.read_hashes.len(), num_memories);
-        assert_eq!(multiset_hashes.write_hashes.len(), num_memories);
-        assert_eq!(multiset_hashes.final_hashes.len(), num_memories);
-        assert_eq!(multiset_hashes.init_hashes.len(), 1);
+        multiset_hashes.check_counts(num_memories, 1, num_memories)?;
 
-        (0..num_memories).into_par_iter().for_each(|i| {
+        for i in 0..num_memories {
             let read_hash = multiset_hashes.read_hashes[i];
             let write_hash = multiset_hashes.write_hashes[i];
             let init_hash = multiset_hashes.init_hashes[0];
             let final_hash = multiset_hashes.final_hashes[i];
-            assert_eq!(
-                init_hash * write_hash,
-                final_hash * read_hash,
-                "Multiset hashes don't match"
-            );
-        });
+            if init_hash * write_hash != final_hash * read_hash {
+                return Err(VerificationStage::MultisetHashEquality { memory: i }.into());
+            }
+        }
+        Ok(())
     }
 
     fn protocol_name() -> &'static [u8] {
@@ -465,7 +468,7 @@ where
 {
     fn verify_memory_checking(
         _: &NoPreprocessing,
-        _: &C::Setup,
+        _: &C::VerifierSetup,
         mut _proof: MemoryCheckingProof<
             F,
             C,
@@ -589,7 +592,7 @@ impl<F: JoltField> BatchedGrandProduct<F> for NoopGrandProduct {
         _proof: &BatchedGrandProductProof<F>,
         _claims: &Vec<F>,
         _transcript: &mut ProofTranscript,
-    ) -> (Vec<F>, Vec<F>) {
+    ) -> Result<(Vec<F>, Vec<F>), ProofVerifyError> {
         unimplemented!("init/final grand products are batched with read/write grand products")
     }
 }
@@ -694,8 +697,10 @@ where
             &NoPreprocessing,
             read_write_hashes.to_vec(),
             init_final_hashes.to_vec(),
-        );
-        TimestampValidityProof::<F, C>::check_multiset_equality(&NoPreprocessing, &multiset_hashes);
+        )
+        .expect("Grand product claims don't match the memories");
+        TimestampValidityProof::<F, C>::check_multiset_equality(&NoPreprocessing, &multiset_hashes)
+            .expect("Multiset hashes don't match");
         multiset_hashes.append_to_transcript(transcript);
 
         let (batched_grand_product, r_grand_product) =
@@ -708,7 +713,7 @@ where
 
     pub fn verify(
         &mut self,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         range_check_commitment: &RangeCheckCommitment<C>,
         memory_commitment: &MemoryCommitment<C>,
         transcript: &mut ProofTranscript,
@@ -723,7 +728,7 @@ where
         TimestampValidityProof::<F, C>::check_multiset_equality(
             &NoPreprocessing,
             &self.multiset_hashes,
-        );
+        )?;
         self.multiset_hashes.append_to_transcript(transcript);
 
         let (read_write_hashes, init_final_hashes) =
@@ -737,7 +742,7 @@ where
                 &self.batched_grand_product,
                 &concatenated_hashes,
                 transcript,
-            );
+            )?;
 
         let openings: Vec<_> = self
             .openings
@@ -765,7 +770,8 @@ where
             &openings,
             &commitments,
             transcript,
-        )?;
+        )
+        .map_err(VerificationStage::opening_proof)?;
 
         self.openings
             .compute_verifier_openings(&NoPreprocessing, &r_grand_product);
@@ -791,10 +797,8 @@ where
                 .map(|tuple| TimestampValidityProof::<F, C>::fingerprint(tuple, &gamma, &tau))
                 .collect();
 
-        assert_eq!(
-            grand_product_claims.len(),
-            6 * MEMORY_OPS_PER_INSTRUCTION + 1
-        );
+        let num_claims = 6 * MEMORY_OPS_PER_INSTRUCTION + 1;
+        check_hash_count(num_claims, grand_product_claims.len())?;
         let (read_write_claims, init_final_claims) =
             grand_product_claims.split_at(4 * MEMORY_OPS_PER_INSTRUCTION);
 
@@ -807,11 +811,12 @@ where
         let (read_write_hashes, init_final_hashes) =
             TimestampValidityProof::<F, C>::interleave_hashes(&NoPreprocessing, &multiset_hashes);
 
-        for (claim, fingerprint) in zip(read_write_claims, read_write_hashes) {
-            assert_eq!(*claim, fingerprint);
-        }
-        for (claim, fingerprint) in zip(init_final_claims, init_final_hashes) {
-            assert_eq!(*claim, fingerprint);
+        let claims = read_write_claims.iter().chain(init_final_claims);
+        let fingerprints = read_write_hashes.into_iter().chain(init_final_hashes);
+        for (index, (claim, fingerprint)) in zip(claims, fingerprints).enumerate() {
+            if *claim != fingerprint {
+                return Err(VerificationStage::Fingerprint { index }.into());
+            }
         }
 
         Ok(())
@@ -821,7 +826,12 @@ where
     pub fn commitment_shapes(max_trace_length: usize) -> Vec<CommitShape> {
         let max_trace_length = max_trace_length.next_power_of_two();
 
-        vec![CommitShape::new(max_trace_length, BatchType::Big)]
+        // Read and final counts of both range checks
+        vec![CommitShape::new(
+            max_trace_length,
+            BatchType::Big,
+            4 * MEMORY_OPS_PER_INSTRUCTION,
+        )]
     }
 
     fn protocol_name() -> &'static [u8] {
@@ -2,7 +2,7 @@
 #![allow(clippy::type_complexity)]
 
 use crate::subprotocols::grand_product::BatchedDenseGrandProduct;
-use crate::utils::errors::ProofVerifyError;
+use crate::utils::errors::{ProofVerifyError, VerificationStage};
 use crate::utils::thread::drop_in_background_thread;
 use crate::utils::transcript::ProofTranscript;
 use crate::{
@@ -16,7 +16,6 @@ use crate::{
 use crate::field::JoltField;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use itertools::interleave;
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use std::iter::zip;
 use std::marker::PhantomData;
 
@@ -39,6 +38,34 @@ impl<F: JoltField> MultisetHashes<F> {
         transcript.append_scalars(b"Init multiset hashes", &self.init_hashes);
         transcript.append_scalars(b"Final multiset hashes", &self.final_hashes);
     }
+
+    /// Checks that the proof contains the expected number of hashes of each kind.
+    pub fn check_counts(
+        &self,
+        num_read_write: usize,
+        num_init: usize,
+        num_final: usize,
+    ) -> Result<(), ProofVerifyError> {
+        let counts = [
+            (num_read_write, self.read_hashes.len()),
+            (num_read_write, self.write_hashes.len()),
+            (num_init, self.init_hashes.len()),
+            (num_final, self.final_hashes.len()),
+        ];
+        for (expected, actual) in counts {
+            check_hash_count(expected, actual)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that `actual` grand product claims or multiset hashes were given where `expected`
+/// were needed.
+pub fn check_hash_count(expected: usize, actual: usize) -> Result<(), ProofVerifyError> {
+    if expected != actual {
+        return Err(VerificationStage::MultisetHashCount { expected, actual }.into());
+    }
+    Ok(())
 }
 
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
@@ -171,8 +198,10 @@ where
             Self::init_final_grand_product(preprocessing, polynomials, init_final_leaves);
 
         let multiset_hashes =
-            Self::uninterleave_hashes(preprocessing, read_write_hashes, init_final_hashes);
-        Self::check_multiset_equality(preprocessing, &multiset_hashes);
+            Self::uninterleave_hashes(preprocessing, read_write_hashes, init_final_hashes)
+                .expect("Grand product claims don't match the memories");
+        Self::check_multiset_equality(preprocessing, &multiset_hashes)
+            .expect("Multiset hashes don't match");
         multiset_hashes.append_to_transcript(transcript);
 
         let (read_write_grand_product, r_read_write) =
@@ -240,9 +269,10 @@ where
         _preprocessing: &Self::Preprocessing,
         read_write_hashes: Vec<F>,
         init_final_hashes: Vec<F>,
-    ) -> MultisetHashes<F> {
-        assert_eq!(read_write_hashes.len() % 2, 0);
+    ) -> Result<MultisetHashes<F>, ProofVerifyError> {
         let num_memories = read_write_hashes.len() / 2;
+        check_hash_count(2 * num_memories, read_write_hashes.len())?;
+        check_hash_count(2 * num_memories, init_final_hashes.len())?;
 
         let mut read_hashes = Vec::with_capacity(num_memories);
         let mut write_hashes = Vec::with_capacity(num_memories);
@@ -258,34 +288,31 @@ where
             final_hashes.push(init_final_hashes[2 * i + 1]);
         }
 
-        MultisetHashes {
+        Ok(MultisetHashes {
             read_hashes,
             write_hashes,
             init_hashes,
             final_hashes,
-        }
+        })
     }
 
     fn check_multiset_equality(
         _preprocessing: &Self::Preprocessing,
         multiset_hashes: &MultisetHashes<F>,
-    ) {
+    ) -> Result<(), ProofVerifyError> {
         let num_memories = multiset_hashes.read_hashes.len();
-        assert_eq!(multiset_hashes.final_hashes.len(), num_memories);
-        assert_eq!(multiset_hashes.write_hashes.len(), num_memories);
-        assert_eq!(multiset_hashes.init_hashes.len(), num_memories);
+        multiset_hashes.check_counts(num_memories, num_memories, num_memories)?;
 
-        (0..num_memories).into_par_iter().for_each(|i| {
+        for i in 0..num_memories {
             let read_hash = multiset_hashes.read_hashes[i];
             let write_hash = multiset_hashes.write_hashes[i];
             let init_hash = multiset_hashes.init_hashes[i];
             let final_hash = multiset_hashes.final_hashes[i];
-            assert_eq!(
-                init_hash * write_hash,
-                final_hash * read_hash,
-                "Multiset hashes don't match"
-            );
-        });
+            if init_hash * write_hash != final_hash * read_hash {
+                return Err(VerificationStage::MultisetHashEquality { memory: i }.into());
+            }
+        }
+        Ok(())
     }
 
     /// Computes the MLE of the leaves of the read, write, init, and final grand product circuits,
@@ -319,7 +346,7 @@ where
     /// Verifies a memory checking proof, given its associated polynomial `commitment`.
     fn verify_memory_checking(
         preprocessing: &Self::Preprocessing,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         mut proof: MemoryCheckingProof<
             F,
             C,
@@ -336,7 +363,7 @@ where
 
         transcript.append_protocol_name(Self::protocol_name());
 
-        Self::check_multiset_equality(preprocessing, &proof.multiset_hashes);
+        Self::check_multiset_equality(preprocessing, &proof.multiset_hashes)?;
         proof.multiset_hashes.append_to_transcript(transcript);
 
         let (read_write_hashes, init_final_hashes) =
@@ -346,28 +373,34 @@ where
             &proof.read_write_grand_product,
             &read_write_hashes,
             transcript,
-        );
+        )?;
         let (claims_init_final, r_init_final) = Self::InitFinalGrandProduct::verify_grand_product(
             &proof.init_final_grand_product,
             &init_final_hashes,
             transcript,
-        );
-
-        proof.read_write_openings.verify_openings(
-            generators,
-            &proof.read_write_opening_proof,
-            commitments,
-            &r_read_write,
-            transcript,
-        )?;
-        proof.init_final_openings.verify_openings(
-            generators,
-            &proof.init_final_opening_proof,
-            commitments,
-            &r_init_final,
-            transcript,
         )?;
 
+        proof
+            .read_write_openings
+            .verify_openings(
+                generators,
+                &proof.read_write_opening_proof,
+                commitments,
+                &r_read_write,
+                transcript,
+            )
+            .map_err(VerificationStage::opening_proof)?;
+        proof
+            .init_final_openings
+            .verify_openings(
+                generators,
+                &proof.init_final_opening_proof,
+                commitments,
+                &r_init_final,
+                transcript,
+            )
+            .map_err(VerificationStage::opening_proof)?;
+
         proof
             .read_write_openings
             .compute_verifier_openings(&NoPreprocessing, &r_read_write);
@@ -383,9 +416,7 @@ where
             &proof.init_final_openings,
             &gamma,
             &tau,
-        );
-
-        Ok(())
+        )
     }
 
     /// Computes "read" memory tuples (one per memory) from the given `openings`.
@@ -419,7 +450,7 @@ where
         init_final_openings: &Self::InitFinalOpenings,
         gamma: &F,
         tau: &F,
-    ) {
+    ) -> Result<(), ProofVerifyError> {
         let read_hashes: Vec<_> = Self::read_tuples(preprocessing, read_write_openings)
             .iter()
             .map(|tuple| Self::fingerprint(tuple, gamma, tau))
@@ -436,14 +467,20 @@ where
             .iter()
             .map(|tuple| Self::fingerprint(tuple, gamma, tau))
             .collect();
-        assert_eq!(
-            read_hashes.len() + write_hashes.len(),
-            claims_read_write.len()
-        );
-        assert_eq!(
-            init_hashes.len() + final_hashes.len(),
-            claims_init_final.len()
-        );
+        for (expected, actual) in [
+            (
+                read_hashes.len() + write_hashes.len(),
+                claims_read_write.len(),
+            ),
+            (
+                init_hashes.len() + final_hashes.len(),
+                claims_init_final.len(),
+            ),
+        ] {
+            if expected != actual {
+                return Err(VerificationStage::MultisetHashCount { expected, actual }.into());
+            }
+        }
 
         let multiset_hashes = MultisetHashes {
             read_hashes,
@@ -454,11 +491,13 @@ where
         let (read_write_hashes, init_final_hashes) =
             Self::interleave_hashes(preprocessing, &multiset_hashes);
 
-        for (claim, fingerprint) in zip(claims_read_write, read_write_hashes) {
-            assert_eq!(claim, fingerprint);
-        }
-        for (claim, fingerprint) in zip(claims_init_final, init_final_hashes) {
-            assert_eq!(claim, fingerprint);
+        let claims = claims_read_write.into_iter().chain(claims_init_final);
+        let fingerprints = read_write_hashes.into_iter().chain(init_final_hashes);
+        for (index, (claim, fingerprint)) in zip(claims, fingerprints).enumerate() {
+            if claim != fingerprint {
+                return Err(VerificationStage::Fingerprint { index }.into());
+            }
         }
+        Ok(())
     }
 }
@@ -15,7 +15,12 @@ use crate::{
         structured_poly::{StructuredCommitment, StructuredOpeningProof},
     },
     subprotocols::sumcheck::SumcheckInstanceProof,
-    utils::{errors::ProofVerifyError, math::Math, mul_0_1_optimized, transcript::ProofTranscript},
+    utils::{
+        errors::{ProofVerifyError, VerificationStage},
+        math::Math,
+        mul_0_1_optimized,
+        transcript::ProofTranscript,
+    },
 };
 
 pub struct SurgePolys<F, PCS>
@@ -108,7 +113,7 @@ where
 
     fn verify_openings(
         &self,
-        generators: &PCS::Setup,
+        generators: &PCS::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &SurgeCommitment<PCS>,
         opening_point: &[F],
@@ -186,7 +191,7 @@ where
 
     fn verify_openings(
         &self,
-        generators: &PCS::Setup,
+        generators: &PCS::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &SurgeCommitment<PCS>,
         opening_point: &[F],
@@ -283,7 +288,7 @@ where
 
     fn verify_openings(
         &self,
-        generators: &PCS::Setup,
+        generators: &PCS::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &SurgeCommitment<PCS>,
         opening_point: &[F],
@@ -631,7 +636,7 @@ where
 
     pub fn verify(
         preprocessing: &SurgePreprocessing<F, Instruction, C, M>,
-        generators: &PCS::Setup,
+        generators: &PCS::VerifierSetup,
         proof: SurgeProof<F, PCS, Instruction, C, M>,
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
@@ -654,19 +659,23 @@ where
         )?;
 
         let eq_eval = EqPolynomial::new(r_primary_sumcheck.to_vec()).evaluate(&r_z);
-        assert_eq!(
-            eq_eval * instruction.combine_lookups(&proof.primary_sumcheck.openings, C, M),
-            claim_last,
-            "Primary sumcheck check failed."
-        );
+        if eq_eval * instruction.combine_lookups(&proof.primary_sumcheck.openings, C, M)
+            != claim_last
+        {
+            return Err(VerificationStage::SumcheckFinalClaim.into());
+        }
 
-        proof.primary_sumcheck.openings.verify_openings(
-            generators,
-            &proof.primary_sumcheck.opening_proof,
-            &proof.commitment,
-            &r_z,
-            transcript,
-        )?;
+        proof
+            .primary_sumcheck
+            .openings
+            .verify_openings(
+                generators,
+                &proof.primary_sumcheck.opening_proof,
+                &proof.commitment,
+                &r_z,
+                transcript,
+            )
+            .map_err(VerificationStage::opening_proof)?;
 
         Self::verify_memory_checking(
             preprocessing,
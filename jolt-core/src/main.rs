@@ -4,6 +4,7 @@ use jolt_core::benches::{
     bench::{benchmarks, BenchType, PCSType},
     sum_timer::CumulativeTimingLayer,
 };
+use jolt_core::poly::commitment::kzg::{INSECURE_SRS_ENV, SRS_PATH_ENV};
 
 use std::any::Any;
 
@@ -75,6 +76,10 @@ enum Format {
 }
 
 fn main() {
+    // Benchmarks don't need a trusted SRS for Zeromorph and HyperKZG
+    if std::env::var_os(SRS_PATH_ENV).is_none() {
+        std::env::set_var(INSECURE_SRS_ENV, "1");
+    }
     let cli = Cli::parse();
     match cli.command {
         Commands::Trace(args) => trace(args),
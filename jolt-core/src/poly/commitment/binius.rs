@@ -26,12 +26,13 @@ pub struct BiniusProof {}
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
 pub struct BiniusBatchedProof {}
 
-#[derive(Clone)]
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct None {}
 
 impl CommitmentScheme for Binius128Scheme {
     type Field = crate::field::binius::BiniusField<binius_field::BinaryField128bPolyval>;
     type Setup = None;
+    type VerifierSetup = None;
     type Commitment = BiniusCommitment;
     type Proof = BiniusProof;
     type BatchedProof = BiniusBatchedProof;
@@ -39,6 +40,9 @@ impl CommitmentScheme for Binius128Scheme {
     fn setup(_shapes: &[CommitShape]) -> Self::Setup {
         None {}
     }
+    fn verifier_setup(_setup: &Self::Setup) -> Self::VerifierSetup {
+        None {}
+    }
     fn commit(_poly: &DensePolynomial<Self::Field>, _setup: &Self::Setup) -> Self::Commitment {
         todo!()
     }
@@ -73,7 +77,7 @@ impl CommitmentScheme for Binius128Scheme {
 
     fn verify(
         _proof: &Self::Proof,
-        _setup: &Self::Setup,
+        _setup: &Self::VerifierSetup,
         _transcript: &mut ProofTranscript,
         _opening_point: &[Self::Field],
         _opening: &Self::Field,
@@ -84,7 +88,7 @@ impl CommitmentScheme for Binius128Scheme {
 
     fn batch_verify(
         _batch_proof: &Self::BatchedProof,
-        _setup: &Self::Setup,
+        _setup: &Self::VerifierSetup,
         _opening_point: &[Self::Field],
         _openings: &[Self::Field],
         _commitments: &[&Self::Commitment],
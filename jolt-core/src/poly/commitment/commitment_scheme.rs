@@ -1,4 +1,5 @@
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     field::JoltField,
@@ -9,22 +10,27 @@ use crate::{
     },
 };
 
-#[derive(Clone, Debug)]
+/// Shape of a group of committed polynomials, which all have the same length.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CommitShape {
     pub input_length: usize,
     pub batch_type: BatchType,
+    /// Number of polynomials of this shape. Doesn't affect the setup, which only depends on
+    /// the largest polynomial.
+    pub num_polys: usize,
 }
 
 impl CommitShape {
-    pub fn new(input_length: usize, batch_type: BatchType) -> Self {
+    pub fn new(input_length: usize, batch_type: BatchType, num_polys: usize) -> Self {
         Self {
             input_length,
             batch_type,
+            num_polys,
         }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum BatchType {
     Big,
     Small,
@@ -34,12 +40,15 @@ pub enum BatchType {
 
 pub trait CommitmentScheme: Clone + Sync + Send + 'static {
     type Field: JoltField + Sized;
-    type Setup: Clone + Sync + Send;
+    type Setup: Clone + Sync + Send + CanonicalSerialize + CanonicalDeserialize;
+    /// The part of `Setup` needed to verify openings.
+    type VerifierSetup: Clone + Sync + Send + CanonicalSerialize + CanonicalDeserialize;
     type Commitment: Sync + Send + CanonicalSerialize + CanonicalDeserialize + AppendToTranscript;
     type Proof: Sync + Send + CanonicalSerialize + CanonicalDeserialize;
     type BatchedProof: Sync + Send + CanonicalSerialize + CanonicalDeserialize;
 
     fn setup(shapes: &[CommitShape]) -> Self::Setup;
+    fn verifier_setup(setup: &Self::Setup) -> Self::VerifierSetup;
     fn commit(poly: &DensePolynomial<Self::Field>, setup: &Self::Setup) -> Self::Commitment;
     fn batch_commit(
         evals: &[&[Self::Field]],
@@ -80,7 +89,7 @@ pub trait CommitmentScheme: Clone + Sync + Send + 'static {
 
     fn verify(
         proof: &Self::Proof,
-        setup: &Self::Setup,
+        setup: &Self::VerifierSetup,
         transcript: &mut ProofTranscript,
         opening_point: &[Self::Field], // point at which the polynomial is evaluated
         opening: &Self::Field,         // evaluation \widetilde{Z}(r)
@@ -89,7 +98,7 @@ pub trait CommitmentScheme: Clone + Sync + Send + 'static {
 
     fn batch_verify(
         batch_proof: &Self::BatchedProof,
-        setup: &Self::Setup,
+        setup: &Self::VerifierSetup,
         opening_point: &[Self::Field],
         openings: &[Self::Field],
         commitments: &[&Self::Commitment],
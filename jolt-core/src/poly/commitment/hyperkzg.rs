@@ -26,8 +26,7 @@ use crate::{
 use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::{One, Zero};
-use rand_chacha::ChaCha20Rng;
-use rand_core::{CryptoRng, RngCore, SeedableRng};
+use rand_core::{CryptoRng, RngCore};
 use rayon::iter::{
     IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
     IntoParallelRefMutIterator, ParallelIterator,
@@ -48,12 +47,12 @@ impl<P: Pairing> HyperKZGSRS<P> {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct HyperKZGProverKey<P: Pairing> {
     pub kzg_pk: KZGProverKey<P>,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct HyperKZGVerifierKey<P: Pairing> {
     pub kzg_vk: KZGVerifierKey<P>,
 }
@@ -525,6 +524,7 @@ where
 {
     type Field = P::ScalarField;
     type Setup = (HyperKZGProverKey<P>, HyperKZGVerifierKey<P>);
+    type VerifierSetup = HyperKZGVerifierKey<P>;
     type Commitment = HyperKZGCommitment<P>;
     type Proof = HyperKZGProof<P>;
     type BatchedProof = HyperKZGProof<P>;
@@ -532,11 +532,14 @@ where
     fn setup(shapes: &[CommitShape]) -> Self::Setup {
         let max_len = shapes.iter().map(|shape| shape.input_length).max().unwrap();
 
-        HyperKZGSRS(Arc::new(SRS::setup(
-            &mut ChaCha20Rng::from_seed(*b"HyperKZG_POLY_COMMITMENT_SCHEMEE"),
-            max_len,
-        )))
-        .trim(max_len)
+        // `setup` can't fail, so a missing or unusable SRS is fatal
+        let srs = SRS::load_or_setup(*b"HyperKZG_POLY_COMMITMENT_SCHEMEE", max_len)
+            .unwrap_or_else(|err| panic!("{}", err));
+        HyperKZGSRS(srs).trim(max_len)
+    }
+
+    fn verifier_setup(setup: &Self::Setup) -> Self::VerifierSetup {
+        setup.1
     }
 
     fn commit(poly: &DensePolynomial<Self::Field>, setup: &Self::Setup) -> Self::Commitment {
@@ -603,32 +606,25 @@ where
 
     fn verify(
         proof: &Self::Proof,
-        setup: &Self::Setup,
+        setup: &Self::VerifierSetup,
         transcript: &mut ProofTranscript,
         opening_point: &[Self::Field], // point at which the polynomial is evaluated
         opening: &Self::Field,         // evaluation \widetilde{Z}(r)
         commitment: &Self::Commitment,
     ) -> Result<(), ProofVerifyError> {
-        HyperKZG::<P>::verify(
-            &setup.1,
-            commitment,
-            opening_point,
-            opening,
-            proof,
-            transcript,
-        )
+        HyperKZG::<P>::verify(setup, commitment, opening_point, opening, proof, transcript)
     }
 
     fn batch_verify(
         batch_proof: &Self::BatchedProof,
-        setup: &Self::Setup,
+        setup: &Self::VerifierSetup,
         opening_point: &[Self::Field],
         openings: &[Self::Field],
         commitments: &[&Self::Commitment],
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
         HyperKZG::<P>::batch_verify(
-            &setup.1,
+            setup,
             commitments,
             opening_point,
             openings,
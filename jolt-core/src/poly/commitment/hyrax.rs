@@ -50,6 +50,7 @@ pub fn matrix_dimensions(num_vars: usize, ratio: usize) -> (usize, usize) {
 impl<F: JoltField, G: CurveGroup<ScalarField = F>> CommitmentScheme for HyraxScheme<G> {
     type Field = G::ScalarField;
     type Setup = PedersenGenerators<G>;
+    type VerifierSetup = PedersenGenerators<G>;
     type Commitment = HyraxCommitment<G>;
     type Proof = HyraxOpeningProof<G>;
     type BatchedProof = BatchedHyraxOpeningProof<G>;
@@ -68,6 +69,9 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>> CommitmentScheme for HyraxSch
         }
         PedersenGenerators::new(max_len, b"Jolt v1 Hyrax generators")
     }
+    fn verifier_setup(setup: &Self::Setup) -> Self::VerifierSetup {
+        setup.clone()
+    }
     fn commit(poly: &DensePolynomial<Self::Field>, gens: &Self::Setup) -> Self::Commitment {
         HyraxCommitment::commit(poly, gens)
     }
@@ -108,7 +112,7 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>> CommitmentScheme for HyraxSch
     }
     fn verify(
         proof: &Self::Proof,
-        generators: &Self::Setup,
+        generators: &Self::VerifierSetup,
         transcript: &mut ProofTranscript,
         opening_point: &[Self::Field],
         opening: &Self::Field,
@@ -127,7 +131,7 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>> CommitmentScheme for HyraxSch
     }
     fn batch_verify(
         batch_proof: &Self::BatchedProof,
-        generators: &Self::Setup,
+        generators: &Self::VerifierSetup,
         opening_point: &[Self::Field],
         openings: &[Self::Field],
         commitments: &[&Self::Commitment],
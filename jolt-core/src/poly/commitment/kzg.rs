@@ -5,15 +5,52 @@ use crate::utils::errors::ProofVerifyError;
 use ark_ec::scalar_mul::fixed_base::FixedBase;
 use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
 use ark_ff::PrimeField;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+    Write,
+};
 use ark_std::UniformRand;
-use rand_core::{CryptoRng, RngCore};
+use rand_chacha::ChaCha20Rng;
+use rand_core::{CryptoRng, RngCore, SeedableRng};
+use std::fs::File;
+use std::io::BufReader;
 use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use thiserror::Error;
 
 #[cfg(feature = "ark-msm")]
 use ark_ec::VariableBaseMSM;
 
-#[derive(Clone, Debug)]
+/// Path to a structured reference string, serialized compressed with `CanonicalSerialize`,
+/// which KZG-based commitment schemes load instead of sampling one from a public seed.
+pub const SRS_PATH_ENV: &str = "JOLT_KZG_SRS";
+/// If set while `JOLT_KZG_SRS` is not, KZG-based commitment schemes sample their SRS from a
+/// public seed instead. Anyone knowing the seed can forge proofs: only fit for tests and
+/// benchmarks.
+pub const INSECURE_SRS_ENV: &str = "JOLT_KZG_INSECURE_SRS";
+
+#[derive(Debug, Error)]
+pub enum SrsError {
+    #[error(
+        "{SRS_PATH_ENV} is not set; set {INSECURE_SRS_ENV} to sample an insecure SRS for testing"
+    )]
+    Missing,
+    #[error("failed to read SRS from {path:?}: {source}")]
+    Read {
+        path: PathBuf,
+        source: SerializationError,
+    },
+    #[error("SRS in {path:?} has {g1_powers} G1 and {g2_powers} G2 powers, but {needed} and 2 are needed")]
+    TooShort {
+        path: PathBuf,
+        g1_powers: usize,
+        g2_powers: usize,
+        needed: usize,
+    },
+}
+
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct SRS<P: Pairing> {
     pub g1_powers: Vec<P::G1Affine>,
     pub g2_powers: Vec<P::G2Affine>,
@@ -58,6 +95,46 @@ impl<P: Pairing> SRS<P> {
         }
     }
 
+    /// Reads an SRS, e.g. one converted from a powers-of-tau ceremony, from `path`.
+    pub fn from_file(path: &Path) -> Result<Self, SerializationError> {
+        let reader = BufReader::new(File::open(path)?);
+        Self::deserialize_compressed(reader)
+    }
+
+    /// Loads the SRS from the file in `JOLT_KZG_SRS`. If it is unset and `JOLT_KZG_INSECURE_SRS`
+    /// is set (or in tests), samples one from `seed` instead, which is public, so anyone knowing
+    /// it can forge proofs.
+    pub fn load_or_setup(seed: [u8; 32], max_degree: usize) -> Result<Arc<Self>, SrsError> {
+        let Some(path) = std::env::var_os(SRS_PATH_ENV).map(PathBuf::from) else {
+            if !cfg!(test) && std::env::var_os(INSECURE_SRS_ENV).is_none() {
+                return Err(SrsError::Missing);
+            }
+            tracing::warn!("{} is not set, sampling an insecure SRS", SRS_PATH_ENV);
+            return Ok(Arc::new(Self::setup(
+                &mut ChaCha20Rng::from_seed(seed),
+                max_degree,
+            )));
+        };
+        Self::load(path, max_degree).map(Arc::new)
+    }
+
+    /// Reads the SRS from `path`, checking it supports polynomials of degree `max_degree`.
+    pub fn load(path: PathBuf, max_degree: usize) -> Result<Self, SrsError> {
+        let srs = Self::from_file(&path).map_err(|source| SrsError::Read {
+            path: path.clone(),
+            source,
+        })?;
+        if max_degree >= srs.g1_powers.len() || srs.g2_powers.len() < 2 {
+            return Err(SrsError::TooShort {
+                path,
+                g1_powers: srs.g1_powers.len(),
+                g2_powers: srs.g2_powers.len(),
+                needed: max_degree + 1,
+            });
+        }
+        Ok(srs)
+    }
+
     pub fn trim(params: Arc<Self>, max_degree: usize) -> (KZGProverKey<P>, KZGVerifierKey<P>) {
         assert!(!params.g1_powers.is_empty(), "max_degree is 0");
         assert!(
@@ -75,17 +152,62 @@ impl<P: Pairing> SRS<P> {
 
 #[derive(Clone, Debug)]
 pub struct KZGProverKey<P: Pairing> {
-    srs: Arc<SRS<P>>,
+    pub(crate) srs: Arc<SRS<P>>,
     // offset to read into SRS
-    offset: usize,
+    pub(crate) offset: usize,
     // max size of srs
-    supported_size: usize,
+    pub(crate) supported_size: usize,
+}
+
+// `Arc` isn't serializable, so the SRS is serialized by value
+impl<P: Pairing> CanonicalSerialize for KZGProverKey<P> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.srs.serialize_with_mode(&mut writer, compress)?;
+        self.offset.serialize_with_mode(&mut writer, compress)?;
+        self.supported_size
+            .serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.srs.serialized_size(compress)
+            + self.offset.serialized_size(compress)
+            + self.supported_size.serialized_size(compress)
+    }
+}
+
+impl<P: Pairing> Valid for KZGProverKey<P> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.srs.check()
+    }
+}
+
+impl<P: Pairing> CanonicalDeserialize for KZGProverKey<P> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let srs = SRS::deserialize_with_mode(&mut reader, compress, validate)?;
+        let offset = usize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let supported_size = usize::deserialize_with_mode(&mut reader, compress, validate)?;
+        match offset.checked_add(supported_size) {
+            Some(end) if end <= srs.g1_powers.len() => {}
+            _ => return Err(SerializationError::InvalidData),
+        }
+        Ok(Self::new(Arc::new(srs), offset, supported_size))
+    }
 }
 
 impl<P: Pairing> KZGProverKey<P> {
     pub fn new(srs: Arc<SRS<P>>, offset: usize, supported_size: usize) -> Self {
         assert!(
-            srs.g1_powers.len() >= offset + supported_size,
+            offset
+                .checked_add(supported_size)
+                .is_some_and(|end| end <= srs.g1_powers.len()),
             "not enough powers (req: {} from offset {}) in the SRS (length: {})",
             supported_size,
             offset,
@@ -103,7 +225,7 @@ impl<P: Pairing> KZGProverKey<P> {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct KZGVerifierKey<P: Pairing> {
     pub g1: P::G1Affine,
     pub g2: P::G2Affine,
@@ -227,4 +349,50 @@ mod test {
         }
         Ok(())
     }
+
+    #[test]
+    fn prover_key_rejects_out_of_range_powers() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let srs = SRS::<Bn254>::setup(&mut rng, 4);
+
+        // `offset + supported_size` overflows, and merely exceeds the SRS, respectively
+        for (offset, supported_size) in [(usize::MAX, 2), (2, srs.g1_powers.len())] {
+            let mut bytes = Vec::new();
+            srs.serialize_compressed(&mut bytes).unwrap();
+            offset.serialize_compressed(&mut bytes).unwrap();
+            supported_size.serialize_compressed(&mut bytes).unwrap();
+            assert!(matches!(
+                KZGProverKey::<Bn254>::deserialize_compressed(&bytes[..]),
+                Err(SerializationError::InvalidData)
+            ));
+        }
+    }
+
+    #[test]
+    fn srs_from_file() {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let srs = SRS::<Bn254>::setup(&mut rng, 4);
+        let path = std::env::temp_dir().join("jolt-kzg-srs-test.bin");
+        let mut bytes = Vec::new();
+        srs.serialize_compressed(&mut bytes).unwrap();
+        std::fs::write(&path, &bytes).unwrap();
+
+        let loaded = SRS::<Bn254>::load(path.clone(), 4).unwrap();
+        let too_short = SRS::<Bn254>::load(path.clone(), 5);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.g1_powers, srs.g1_powers);
+        assert_eq!(loaded.g2_powers, srs.g2_powers);
+        assert!(matches!(
+            too_short,
+            Err(SrsError::TooShort {
+                g1_powers: 5,
+                needed: 6,
+                ..
+            })
+        ));
+        assert!(matches!(
+            SRS::<Bn254>::load(path, 4),
+            Err(SrsError::Read { .. })
+        ));
+    }
 }
@@ -37,6 +37,7 @@ pub struct MockProof<F: JoltField> {
 impl<F: JoltField> CommitmentScheme for MockCommitScheme<F> {
     type Field = F;
     type Setup = ();
+    type VerifierSetup = ();
     type Commitment = MockCommitment<F>;
     type Proof = MockProof<F>;
     type BatchedProof = MockProof<F>;
@@ -44,6 +45,7 @@ impl<F: JoltField> CommitmentScheme for MockCommitScheme<F> {
     fn setup(_shapes: &[CommitShape]) -> Self::Setup {
         ()
     }
+    fn verifier_setup(_setup: &Self::Setup) -> Self::VerifierSetup {}
     fn commit(poly: &DensePolynomial<Self::Field>, _setup: &Self::Setup) -> Self::Commitment {
         MockCommitment {
             poly: poly.to_owned(),
@@ -94,7 +96,7 @@ impl<F: JoltField> CommitmentScheme for MockCommitScheme<F> {
 
     fn verify(
         proof: &Self::Proof,
-        _setup: &Self::Setup,
+        _setup: &Self::VerifierSetup,
         _transcript: &mut ProofTranscript,
         opening_point: &[Self::Field],
         opening: &Self::Field,
@@ -108,7 +110,7 @@ impl<F: JoltField> CommitmentScheme for MockCommitScheme<F> {
 
     fn batch_verify(
         batch_proof: &Self::BatchedProof,
-        _setup: &Self::Setup,
+        _setup: &Self::VerifierSetup,
         opening_point: &[Self::Field],
         openings: &[Self::Field],
         commitments: &[&Self::Commitment],
@@ -14,10 +14,12 @@ use crate::utils::{
 };
 use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
 use ark_ff::{batch_inversion, Field};
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+    Write,
+};
 use ark_std::{One, Zero};
 use itertools::izip;
-use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
 use rand_core::{CryptoRng, RngCore};
 use std::sync::Arc;
 use tracing::trace_span;
@@ -39,6 +41,10 @@ impl<P: Pairing> ZeromorphSRS<P> {
     pub fn trim(self, max_degree: usize) -> (ZeromorphProverKey<P>, ZeromorphVerifierKey<P>) {
         let (commit_pp, kzg_vk) = SRS::trim(self.0.clone(), max_degree);
         let offset = self.0.g1_powers.len() - max_degree;
+        assert!(
+            offset < self.0.g2_powers.len(),
+            "Zeromorph needs a G2 power for every G1 power in the SRS"
+        );
         let tau_N_max_sub_2_N = self.0.g2_powers[offset];
         let open_pp = KZGProverKey::new(self.0, offset, max_degree);
         (
@@ -58,7 +64,55 @@ pub struct ZeromorphProverKey<P: Pairing> {
     pub open_pp: KZGProverKey<P>,
 }
 
-#[derive(Copy, Clone, Debug)]
+// Both keys are trimmed from the same SRS, which is only serialized once
+impl<P: Pairing> CanonicalSerialize for ZeromorphProverKey<P> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        debug_assert!(Arc::ptr_eq(&self.commit_pp.srs, &self.open_pp.srs));
+        self.commit_pp.serialize_with_mode(&mut writer, compress)?;
+        self.open_pp
+            .offset
+            .serialize_with_mode(&mut writer, compress)?;
+        self.open_pp
+            .supported_size
+            .serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.commit_pp.serialized_size(compress)
+            + self.open_pp.offset.serialized_size(compress)
+            + self.open_pp.supported_size.serialized_size(compress)
+    }
+}
+
+impl<P: Pairing> Valid for ZeromorphProverKey<P> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.commit_pp.check()
+    }
+}
+
+impl<P: Pairing> CanonicalDeserialize for ZeromorphProverKey<P> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let commit_pp = KZGProverKey::deserialize_with_mode(&mut reader, compress, validate)?;
+        let offset = usize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let supported_size = usize::deserialize_with_mode(&mut reader, compress, validate)?;
+        match offset.checked_add(supported_size) {
+            Some(end) if end <= commit_pp.srs.g1_powers.len() => {}
+            _ => return Err(SerializationError::InvalidData),
+        }
+        let open_pp = KZGProverKey::new(commit_pp.srs.clone(), offset, supported_size);
+        Ok(Self { commit_pp, open_pp })
+    }
+}
+
+#[derive(Copy, Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct ZeromorphVerifierKey<P: Pairing> {
     pub kzg_vk: KZGVerifierKey<P>,
     pub tau_N_max_sub_2_N: P::G2Affine,
@@ -478,6 +532,7 @@ where
 {
     type Field = P::ScalarField;
     type Setup = (ZeromorphProverKey<P>, ZeromorphVerifierKey<P>);
+    type VerifierSetup = ZeromorphVerifierKey<P>;
     type Commitment = ZeromorphCommitment<P>;
     type Proof = ZeromorphProof<P>;
     type BatchedProof = ZeromorphProof<P>;
@@ -485,11 +540,14 @@ where
     fn setup(shapes: &[CommitShape]) -> Self::Setup {
         let max_len = shapes.iter().map(|shape| shape.input_length).max().unwrap();
 
-        ZeromorphSRS(Arc::new(SRS::setup(
-            &mut ChaCha20Rng::from_seed(*b"ZEROMORPH_POLY_COMMITMENT_SCHEME"),
-            max_len,
-        )))
-        .trim(max_len)
+        // `setup` can't fail, so a missing or unusable SRS is fatal
+        let srs = SRS::load_or_setup(*b"ZEROMORPH_POLY_COMMITMENT_SCHEME", max_len)
+            .unwrap_or_else(|err| panic!("{}", err));
+        ZeromorphSRS(srs).trim(max_len)
+    }
+
+    fn verifier_setup(setup: &Self::Setup) -> Self::VerifierSetup {
+        setup.1
     }
 
     fn commit(poly: &DensePolynomial<Self::Field>, setup: &Self::Setup) -> Self::Commitment {
@@ -558,32 +616,25 @@ where
 
     fn verify(
         proof: &Self::Proof,
-        setup: &Self::Setup,
+        setup: &Self::VerifierSetup,
         transcript: &mut ProofTranscript,
         opening_point: &[Self::Field], // point at which the polynomial is evaluated
         opening: &Self::Field,         // evaluation \widetilde{Z}(r)
         commitment: &Self::Commitment,
     ) -> Result<(), ProofVerifyError> {
-        Zeromorph::<P>::verify(
-            &setup.1,
-            commitment,
-            opening_point,
-            opening,
-            proof,
-            transcript,
-        )
+        Zeromorph::<P>::verify(setup, commitment, opening_point, opening, proof, transcript)
     }
 
     fn batch_verify(
         batch_proof: &Self::BatchedProof,
-        setup: &Self::Setup,
+        setup: &Self::VerifierSetup,
         opening_point: &[Self::Field],
         openings: &[Self::Field],
         commitments: &[&Self::Commitment],
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
         Zeromorph::<P>::batch_verify(
-            &setup.1,
+            setup,
             commitments,
             opening_point,
             openings,
@@ -974,4 +1025,12 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn verifier_setup_leaves_out_srs() {
+        let setup = Zeromorph::<Bn254>::setup(&[CommitShape::new(1 << 10, BatchType::Big, 1)]);
+        let verifier_setup = Zeromorph::<Bn254>::verifier_setup(&setup);
+
+        assert!(verifier_setup.compressed_size() * 100 < setup.compressed_size());
+    }
 }
@@ -3,28 +3,29 @@
 /// As the constraint system involved in Jolt is very simple, it's easy to generate the matrices directly
 /// and avoids the need for using the circom library.
 use crate::field::JoltField;
-use common::{constants::RAM_START_ADDRESS, rv_trace::NUM_CIRCUIT_FLAGS};
+use common::{
+    constants::{INSTRUCTION_ALIGNMENT, RAM_START_ADDRESS},
+    rv_trace::{ELFInstruction, NUM_CIRCUIT_FLAGS, RV32IM},
+};
 use rayon::prelude::*;
 use smallvec::{smallvec, SmallVec};
-use strum::EnumCount;
-
-use crate::jolt::{
-    instruction::{
-        add::ADDInstruction, sll::SLLInstruction, sra::SRAInstruction, srl::SRLInstruction,
-        sub::SUBInstruction, JoltInstructionSet,
-    },
-    vm::rv32i_vm::RV32I,
-};
+use std::marker::PhantomData;
+
+use crate::jolt::instruction::JoltInstructionSet;
 
 use super::snark::R1CSStepInputs;
 
 /* Compiler Variables */
 const C: usize = 4;
-const N_FLAGS: usize = NUM_CIRCUIT_FLAGS + RV32I::COUNT;
 const LOG_M: usize = 16;
 const PC_START_ADDRESS: u64 = RAM_START_ADDRESS;
 const MOPS: usize = 7; // "memory ops per step"
-const PC_NOOP_SHIFT: usize = 4;
+/// The PC is the compressed bytecode address, i.e. 1 + (address - PC_START_ADDRESS) / PC_UNIT
+const PC_UNIT: i64 = INSTRUCTION_ALIGNMENT as i64;
+const PC_NOOP_SHIFT: usize = INSTRUCTION_ALIGNMENT;
+/// The state of a step that continues a virtual sequence is its bytecode virtual address
+/// shifted by this offset, which no compressed PC reaches.
+const VIRTUAL_STATE_OFFSET: i64 = 1 << 40;
 /* End of Compiler Variables */
 
 const L_CHUNK: usize = LOG_M / 2;
@@ -66,7 +67,9 @@ const INPUT_SIZES: &[(InputType, usize)] = &[
     (InputType::ChunksQuery, C),
     (InputType::LookupOutput, 1),
     (InputType::OpFlags, NUM_CIRCUIT_FLAGS),
-    (InputType::InstrFlags, RV32I::COUNT),
+    // One flag per instruction in the instruction set, so it is sized by `R1CSBuilder`.
+    // Must come last, so that the offsets of the other inputs don't depend on it.
+    (InputType::InstrFlags, 0),
 ];
 
 const INPUT_OFFSETS: [usize; INPUT_SIZES.len()] = {
@@ -97,8 +100,10 @@ const fn GET_INDEX(input_type: InputType, offset: usize) -> usize {
 
 const SMALLVEC_SIZE: usize = 4;
 
+/// Builds the R1CS constraints for a single step of a Jolt VM using the given instruction set,
+/// which determines the instruction flags that are part of the step's inputs.
 #[derive(Debug)]
-pub struct R1CSBuilder {
+pub struct R1CSBuilder<InstructionSet: JoltInstructionSet> {
     pub A: Vec<(usize, usize, i64)>,
     pub B: Vec<(usize, usize, i64)>,
     pub C: Vec<(usize, usize, i64)>,
@@ -107,6 +112,7 @@ pub struct R1CSBuilder {
     pub num_inputs: usize,
     pub num_aux: usize,
     pub num_internal: usize, // aux that isn't inputs
+    _instruction_set: PhantomData<InstructionSet>,
 }
 
 fn subtract_vectors(
@@ -158,22 +164,70 @@ fn i64_to_f<F: JoltField>(num: i64) -> F {
     }
 }
 
-impl Default for R1CSBuilder {
+// Returns the index of the instruction flag that `opcode` maps to in `InstructionSet`.
+fn instruction_flag_index<InstructionSet: JoltInstructionSet>(opcode: RV32IM) -> usize {
+    let instruction = ELFInstruction {
+        address: 0,
+        opcode,
+        rs1: None,
+        rs2: None,
+        rd: None,
+        imm: None,
+        virtual_sequence_index: None,
+        virtual_sequence_remaining: None,
+        is_compressed: false,
+    };
+    let lookup = InstructionSet::try_from(&instruction)
+        .unwrap_or_else(|_| panic!("Instruction set does not support {:?}", opcode));
+    InstructionSet::enum_index(&lookup)
+}
+
+/// Returns the indices of the instruction flags of the shift instructions, which use a
+/// different chunking of their second operand.
+pub fn shift_flag_indices<InstructionSet: JoltInstructionSet>() -> [usize; 3] {
+    [RV32IM::SLL, RV32IM::SRL, RV32IM::SRA].map(instruction_flag_index::<InstructionSet>)
+}
+
+/// Value of the next PC wire of a step after which the execution continues in state `state`
+/// (see `step_state`). The next PC wire is the last internal auxiliary variable.
+pub fn next_pc_wire_value<F: JoltField>(state: F) -> F {
+    state * F::from_u64(PC_UNIT as u64).unwrap() + F::from_u64(PC_START_ADDRESS).unwrap()
+}
+
+/// State a step starts in: the compressed PC `pc` of its instruction, or its bytecode
+/// `virtual_address` shifted by `VIRTUAL_STATE_OFFSET` if it continues a virtual sequence.
+pub fn step_state<F: JoltField>(pc: F, virtual_address: F, continues_sequence: bool) -> F {
+    if continues_sequence {
+        virtual_address + F::from_u64(VIRTUAL_STATE_OFFSET as u64).unwrap()
+    } else {
+        pc
+    }
+}
+
+impl<InstructionSet: JoltInstructionSet> Default for R1CSBuilder<InstructionSet> {
     fn default() -> Self {
+        let num_inputs = GET_TOTAL_LEN() + InstructionSet::COUNT;
         R1CSBuilder {
             A: Vec::with_capacity(100),
             B: Vec::with_capacity(100),
             C: Vec::with_capacity(100),
             num_constraints: 0,
-            num_variables: GET_TOTAL_LEN(), // includes ("constant", 1) and ("output_state", ..)
-            num_inputs: 0,                  // technically inputs are also aux, so keep this 0
-            num_aux: GET_TOTAL_LEN() - 1,   // dont' include the constant
+            num_variables: num_inputs, // includes ("constant", 1) and ("output_state", ..)
+            num_inputs: 0,             // technically inputs are also aux, so keep this 0
+            num_aux: num_inputs - 1,   // dont' include the constant
             num_internal: 0,
+            _instruction_set: PhantomData,
         }
     }
 }
 
-impl R1CSBuilder {
+impl<InstructionSet: JoltInstructionSet> R1CSBuilder<InstructionSet> {
+    /// Number of witness variables of a step that are inputs of the circuit. The internal
+    /// auxiliary variables come after them.
+    pub fn num_input_vars() -> usize {
+        GET_TOTAL_LEN() - 1 + InstructionSet::COUNT
+    }
+
     fn new_constraint(
         &mut self,
         a: SmallVec<[(usize, i64); SMALLVEC_SIZE]>,
@@ -319,9 +373,11 @@ impl R1CSBuilder {
 
     /* This is the main function that generates the Jolt R1CS constraint matrices.
      */
-    pub fn jolt_r1cs_matrices(instance: &mut R1CSBuilder, memory_start: u64) {
+    pub fn jolt_r1cs_matrices(instance: &mut Self, memory_start: u64) {
         // Obtain the indices of various inputs to the circuit.
-        let PC_mapped = GET_INDEX(InputType::InputState, 0);
+        let state = GET_INDEX(InputType::InputState, 0);
+        let virtual_address = GET_INDEX(InputType::ProgARW, 0);
+        let PC_mapped = GET_INDEX(InputType::ProgVRW, 0);
         let op_flags_packed = GET_INDEX(InputType::ProgVRW, 1);
         let rd = GET_INDEX(InputType::ProgVRW, 2);
         let _rs1 = GET_INDEX(InputType::ProgVRW, 3);
@@ -336,39 +392,25 @@ impl R1CSBuilder {
         let if_update_rd_with_lookup_output: usize = GET_INDEX(InputType::OpFlags, 6);
         let sign_imm_flag: usize = GET_INDEX(InputType::OpFlags, 7);
         let is_concat: usize = GET_INDEX(InputType::OpFlags, 8);
+        let do_not_update_pc: usize = GET_INDEX(InputType::OpFlags, 9);
+        let is_assert_instr: usize = GET_INDEX(InputType::OpFlags, 10);
+        let is_compressed_instr: usize = GET_INDEX(InputType::OpFlags, 11);
+        let continues_sequence: usize = GET_INDEX(InputType::OpFlags, 12);
 
         // These flags indicate the type of lookup employed and are obtained using the instruction flags.
         let is_add_instr: usize = GET_INDEX(
             InputType::InstrFlags,
-            RV32I::enum_index(&RV32I::ADD(ADDInstruction::default())),
+            instruction_flag_index::<InstructionSet>(RV32IM::ADD),
         );
         let is_sub_instr: usize = GET_INDEX(
             InputType::InstrFlags,
-            RV32I::enum_index(&RV32I::SUB(SUBInstruction::default())),
+            instruction_flag_index::<InstructionSet>(RV32IM::SUB),
         );
-        let is_shift_instr = smallvec![
-            (
-                GET_INDEX(
-                    InputType::InstrFlags,
-                    RV32I::enum_index(&RV32I::SLL(SLLInstruction::default()))
-                ),
-                1
-            ),
-            (
-                GET_INDEX(
-                    InputType::InstrFlags,
-                    RV32I::enum_index(&RV32I::SRL(SRLInstruction::default()))
-                ),
-                1
-            ),
-            (
-                GET_INDEX(
-                    InputType::InstrFlags,
-                    RV32I::enum_index(&RV32I::SRA(SRAInstruction::default()))
-                ),
-                1
-            ),
-        ];
+        let is_shift_instr: SmallVec<[(usize, i64); SMALLVEC_SIZE]> =
+            shift_flag_indices::<InstructionSet>()
+                .into_iter()
+                .map(|index| (GET_INDEX(InputType::InstrFlags, index), 1))
+                .collect();
 
         // Constraints: binary checks for the input circuit and instruction flags
         for i in 0..NUM_CIRCUIT_FLAGS {
@@ -379,7 +421,7 @@ impl R1CSBuilder {
                 smallvec![],
             );
         }
-        for i in 0..RV32I::COUNT {
+        for i in 0..InstructionSet::COUNT {
             R1CSBuilder::constr_abc(
                 instance,
                 smallvec![(GET_INDEX(InputType::InstrFlags, i), 1)],
@@ -388,20 +430,12 @@ impl R1CSBuilder {
             );
         }
 
-        // Constraint: ensure that the bytecode read address (prog_v_rw) is the same as the input PC.
-        R1CSBuilder::constr_abc(
-            instance,
-            smallvec![(GET_INDEX(InputType::ProgVRW, 0), 1), (PC_mapped, -1)],
-            smallvec![(PC_mapped, 1)],
-            smallvec![],
-        );
-
         // Constraint: combine flag_bits and check that they equal op_flags_packed.
         R1CSBuilder::combine_be_existing(
             instance,
             GET_INDEX(InputType::OpFlags, 0),
             1,
-            N_FLAGS,
+            NUM_CIRCUIT_FLAGS + InstructionSet::COUNT,
             op_flags_packed,
         );
 
@@ -425,7 +459,7 @@ impl R1CSBuilder {
             smallvec![(GET_INDEX(InputType::OpFlags, 0), 1)],
             smallvec![(rs1_val, 1)],
             smallvec![
-                (PC_mapped, 4),
+                (PC_mapped, PC_UNIT),
                 (0, PC_START_ADDRESS as i64 - PC_NOOP_SHIFT as i64)
             ],
         );
@@ -481,6 +515,14 @@ impl R1CSBuilder {
             smallvec![],
         );
 
+        // Constraint: asserts hold, i.e. their lookup output is 1.
+        R1CSBuilder::constr_abc(
+            instance,
+            smallvec![(is_assert_instr, 1)],
+            smallvec![(GET_INDEX(InputType::LookupOutput, 0), 1), (0, -1)],
+            smallvec![],
+        );
+
         /* Create the lookup query (z = query)
         - First, obtain combined_z_chunks
         - Verify that the z is structured correctly, based on the instruction.
@@ -576,7 +618,8 @@ impl R1CSBuilder {
 
         /* Constraints for storing value in register rd.
         - the flag, if_update_rd_with_lookup_output is used here.
-        - If the instruction is a jump, then the value stored in rd is current PC + 4
+        - If the instruction is a jump, then the value stored in rd is the address of the next
+          instruction: current PC + 4, or + 2 if the instruction is compressed
         */
         let rd_val = GET_INDEX(InputType::MemregVWrites, 0);
         R1CSBuilder::constr_prod_0(
@@ -591,15 +634,44 @@ impl R1CSBuilder {
             smallvec![(is_jump_instr, 1)],
             smallvec![
                 (rd_val, -1),
-                (PC_mapped, 4),
-                (0, PC_START_ADDRESS as i64 + 4 - PC_NOOP_SHIFT as i64)
-            ], // NOTE: the PC value is shifted by +4 already after pre-pending no-op
+                (PC_mapped, PC_UNIT),
+                (0, PC_START_ADDRESS as i64 + 4 - PC_NOOP_SHIFT as i64),
+                (is_compressed_instr, -2)
+            ], // NOTE: the PC value is shifted by one unit already after pre-pending no-op
+        );
+
+        /*  Constraints for the state a step starts in, which is the next PC of the step before.
+            - The first instruction of a virtual sequence (or an instruction that is not part
+              of one) starts in its PC, the bytecode read address (prog_v_rw)
+            - An instruction continuing a virtual sequence starts in its virtual address
+              (prog_a_rw), shifted by VIRTUAL_STATE_OFFSET
+            As a step of a virtual sequence sets the next state to its own shifted virtual
+            address + 1, a virtual sequence can only be entered at its first instruction, and
+            its instructions are executed in order, one by one.
+        */
+        R1CSBuilder::constr_abc(
+            instance,
+            smallvec![(0, 1), (continues_sequence, -1)],
+            smallvec![(PC_mapped, 1), (state, -1)],
+            smallvec![],
+        );
+        R1CSBuilder::constr_abc(
+            instance,
+            smallvec![(continues_sequence, 1)],
+            smallvec![
+                (state, 1),
+                (virtual_address, -1),
+                (0, -VIRTUAL_STATE_OFFSET)
+            ],
+            smallvec![],
         );
 
         /*  Constraints for setting the next PC.
-            - Default: increment by 4
+            - Default: increment by the instruction size (4, or 2 if compressed)
             - Jump: set PC to lookup output
             - Branch: PC + immediate_signed if the lookup output is 1
+            - Virtual sequence: move on to the next instruction of the sequence (see above) until
+              its last instruction
         */
         let is_branch_times_lookup_output = R1CSBuilder::multiply(
             instance,
@@ -609,26 +681,47 @@ impl R1CSBuilder {
         let next_pc_j = R1CSBuilder::if_else(
             instance,
             smallvec![(is_jump_instr, 1)],
-            smallvec![(PC_mapped, 4), (0, PC_START_ADDRESS as i64 + 4)],
-            smallvec![(GET_INDEX(InputType::LookupOutput, 0), 1), (0, 4)], // NOTE: +4 because jump instruction outputs are to the original addresses unshifted by no-ops
+            smallvec![
+                (PC_mapped, PC_UNIT),
+                (0, PC_START_ADDRESS as i64 + 4),
+                (is_compressed_instr, -2)
+            ],
+            smallvec![
+                (GET_INDEX(InputType::LookupOutput, 0), 1),
+                (0, PC_NOOP_SHIFT as i64)
+            ], // NOTE: shifted because jump instruction outputs are to the original addresses unshifted by no-ops
         );
         let next_pc_j_b = R1CSBuilder::if_else(
             instance,
             smallvec![(is_branch_times_lookup_output, 1)],
             smallvec![(next_pc_j, 1)],
             smallvec![
-                (PC_mapped, 4),
+                (PC_mapped, PC_UNIT),
                 (0, PC_START_ADDRESS as i64),
                 (immediate_signed, 1)
             ],
         );
+        let next_pc = R1CSBuilder::if_else(
+            instance,
+            smallvec![(do_not_update_pc, 1)],
+            smallvec![(next_pc_j_b, 1)],
+            smallvec![
+                (virtual_address, PC_UNIT),
+                (
+                    0,
+                    PC_START_ADDRESS as i64 + (VIRTUAL_STATE_OFFSET + 1) * PC_UNIT
+                )
+            ],
+        );
+        // Segmented proofs open it from the commitments to the auxiliary variables
+        debug_assert_eq!(next_pc + 1, instance.num_variables);
 
-        // Constraint: check the claimed output PC value, except when it is set to 0 (as is for the padded parts of the trace)
+        // Constraint: check the claimed output state, except when it is set to 0 (as is for the padded parts of the trace)
         R1CSBuilder::constr_abc(
             instance,
             smallvec![
-                (next_pc_j_b, -1),
-                (GET_INDEX(InputType::OutputState, 0), 4),
+                (next_pc, -1),
+                (GET_INDEX(InputType::OutputState, 0), PC_UNIT),
                 (0, PC_START_ADDRESS as i64)
             ],
             smallvec![(GET_INDEX(InputType::OutputState, 0), 1)],
@@ -641,17 +734,26 @@ impl R1CSBuilder {
     /* Given the inputs to a step of the Jolt circuit, this function returns the internal
        "auxiliary" wires values.
        The wires are built sequentially, indicating the constraint that creates it in the comments.
+       `shift_flags` are the `shift_flag_indices` of the instruction set.
     */
-    pub fn calculate_jolt_aux<F: JoltField>(inputs: R1CSStepInputs<F>, num_aux: usize) -> Vec<F> {
-        let four = F::from_u64(4).unwrap();
+    pub fn calculate_jolt_aux<F: JoltField>(
+        inputs: R1CSStepInputs<F>,
+        shift_flags: &[usize; 3],
+        num_aux: usize,
+    ) -> Vec<F> {
+        let pc_unit = F::from_u64(PC_UNIT as u64).unwrap();
+        let pc_noop_shift = F::from_u64(PC_NOOP_SHIFT as u64).unwrap();
 
         // Indices of values within their respective input vector variables.
+        const PC: usize = 0;
         const RD: usize = 2;
         const IMM: usize = 5;
         const IS_JUMP: usize = 4;
         const IS_BRANCH: usize = 5;
         const IF_UPDATE_RD_WITH_LOOKUP_OUTPUT: usize = 6;
         const SIGN_IMM_FLAG: usize = 7;
+        const DO_NOT_UPDATE_PC: usize = 9;
+        const IS_COMPRESSED: usize = 11;
 
         let mut aux: Vec<F> = Vec::with_capacity(num_aux);
 
@@ -670,8 +772,7 @@ impl R1CSBuilder {
         aux.push(if inputs.circuit_flags_bits[0].is_zero() {
             inputs.memreg_v_reads[0]
         } else {
-            inputs.input_pc * four + F::from_u64(PC_START_ADDRESS).unwrap()
-                - F::from_u64(PC_NOOP_SHIFT as u64).unwrap()
+            inputs.bytecode_v[PC] * pc_unit + F::from_u64(PC_START_ADDRESS).unwrap() - pc_noop_shift
         });
 
         // 3. let _y = R1CSBuilder::if_else_simple(instance, GET_INDEX(InputType::OpFlags, 1), rs2_val, immediate);
@@ -701,15 +802,9 @@ impl R1CSBuilder {
         });
 
         // 6-9. let chunk_y_used_i = R1CSBuilder::if_else_simple(&mut instance, is_shift, GET_INDEX(InputType::ChunksY, i), GET_INDEX(InputType::ChunksY, C-1));
-        let is_shift = inputs.instruction_flags_bits
-            [RV32I::enum_index(&RV32I::SLL(SLLInstruction::default()))]
-        .is_one()
-            || inputs.instruction_flags_bits
-                [RV32I::enum_index(&RV32I::SRL(SRLInstruction::default()))]
-            .is_one()
-            || inputs.instruction_flags_bits
-                [RV32I::enum_index(&RV32I::SRA(SRAInstruction::default()))]
-            .is_one();
+        let is_shift = shift_flags
+            .iter()
+            .any(|&index| inputs.instruction_flags_bits[index].is_one());
         for i in 0..C {
             aux.push(if is_shift {
                 inputs.chunks_y[C - 1]
@@ -730,22 +825,33 @@ impl R1CSBuilder {
         let is_branch_times_lookup_output = aux.len();
         aux.push(inputs.circuit_flags_bits[IS_BRANCH] * inputs.lookup_outputs[0]);
 
-        // 13. let next_pc_j = R1CSBuilder::if_else(instance, smallvec![(is_jump_instr, 1)], smallvec![(PC_mapped, 4), (0, PC_START_ADDRESS as i64 + 4)], smallvec![(GET_INDEX(InputType::LookupOutput, 0), 1), (0, 4)] // NOTE: +4 because jump instruction outputs are to the original addresses unshifted by no-ops);
+        // 13. let next_pc_j = R1CSBuilder::if_else(instance, smallvec![(is_jump_instr, 1)], smallvec![(PC_mapped, PC_UNIT), (0, PC_START_ADDRESS as i64 + 4), (is_compressed_instr, -2)], smallvec![(GET_INDEX(InputType::LookupOutput, 0), 1), (0, PC_NOOP_SHIFT as i64)] // NOTE: shifted because jump instruction outputs are to the original addresses unshifted by no-ops);
         let next_pc_j = aux.len();
         aux.push(if inputs.circuit_flags_bits[IS_JUMP].is_zero() {
-            inputs.input_pc * four
+            inputs.bytecode_v[PC] * pc_unit
                 + F::from_u64(PC_START_ADDRESS).unwrap()
                 + F::from_u64(4).unwrap()
+                - inputs.circuit_flags_bits[IS_COMPRESSED] * F::from_u64(2).unwrap()
         } else {
-            inputs.lookup_outputs[0] + four
+            inputs.lookup_outputs[0] + pc_noop_shift
         });
 
-        // 14. let next_pc_j_b = R1CSBuilder::if_else(instance, smallvec![(is_branch_times_lookup_output, 1)], smallvec![(next_pc_j, 1)], smallvec![(PC_mapped, 4), (0, PC_START_ADDRESS as i64), (immediate_signed, 1)]);
-        let _next_pc_j_b = aux.len();
+        // 14. let next_pc_j_b = R1CSBuilder::if_else(instance, smallvec![(is_branch_times_lookup_output, 1)], smallvec![(next_pc_j, 1)], smallvec![(PC_mapped, PC_UNIT), (0, PC_START_ADDRESS as i64), (immediate_signed, 1)]);
+        let next_pc_j_b = aux.len();
         aux.push(if aux[is_branch_times_lookup_output].is_zero() {
             aux[next_pc_j]
         } else {
-            inputs.input_pc * four + F::from_u64(PC_START_ADDRESS).unwrap() + aux[imm_signed_index]
+            inputs.bytecode_v[PC] * pc_unit
+                + F::from_u64(PC_START_ADDRESS).unwrap()
+                + aux[imm_signed_index]
+        });
+
+        // 15. let next_pc = R1CSBuilder::if_else(instance, smallvec![(do_not_update_pc, 1)], smallvec![(next_pc_j_b, 1)], smallvec![(virtual_address, PC_UNIT), (0, PC_START_ADDRESS as i64 + (VIRTUAL_STATE_OFFSET + 1) * PC_UNIT)]);
+        aux.push(if inputs.circuit_flags_bits[DO_NOT_UPDATE_PC].is_zero() {
+            aux[next_pc_j_b]
+        } else {
+            (inputs.bytecode_a + F::from_u64(VIRTUAL_STATE_OFFSET as u64 + 1).unwrap()) * pc_unit
+                + F::from_u64(PC_START_ADDRESS).unwrap()
         });
 
         aux
@@ -375,6 +375,19 @@ impl<F: JoltField> R1CSShape<F> {
         }
     }
 
+    /// Number of variables the constraints refer to, leaving out the constant and the
+    /// variables added by padding.
+    pub fn num_used_vars(&self) -> usize {
+        self.A
+            .iter()
+            .chain(&self.B)
+            .chain(&self.C)
+            .map(|(_, col, _)| *col)
+            .filter(|&col| col < self.num_vars)
+            .max()
+            .map_or(0, |col| col + 1)
+    }
+
     // TODO(sragss / arasuarun): Fix and use for single step unit testing.
     // Checks if the R1CS instance is satisfiable given a witness and its shape
     // pub fn is_sat<G: CurveGroup<ScalarField = F>>(
@@ -7,16 +7,17 @@
 use crate::poly::commitment::commitment_scheme::{BatchType, CommitmentScheme};
 use crate::utils::transcript::AppendToTranscript;
 use crate::{
-    jolt::vm::{rv32i_vm::RV32I, JoltCommitments},
+    jolt::{instruction::JoltInstructionSet, vm::JoltCommitments},
     r1cs::r1cs_shape::R1CSShape,
     utils::{
+        errors::ProofVerifyError,
         thread::{drop_in_background_thread, unsafe_allocate_zero_vec},
         transcript::ProofTranscript,
     },
 };
 
 use super::{
-    constraints::R1CSBuilder,
+    constraints::{shift_flag_indices, step_state, R1CSBuilder},
     spartan::{SpartanError, UniformShapeBuilder, UniformSpartanKey, UniformSpartanProof},
 };
 
@@ -25,22 +26,23 @@ use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use common::{constants::MEMORY_OPS_PER_INSTRUCTION, rv_trace::NUM_CIRCUIT_FLAGS};
 use rayon::prelude::*;
 use std::borrow::Borrow;
-use strum::EnumCount;
 
 #[tracing::instrument(name = "synthesize_witnesses", skip_all)]
 /// Returns (io, aux) = (pc_out, pc, aux)
-fn synthesize_witnesses<F: JoltField>(
+fn synthesize_witnesses<F: JoltField, InstructionSet: JoltInstructionSet>(
     inputs: &R1CSInputs<F>,
     num_aux: usize,
 ) -> (Vec<F>, Vec<F>, Vec<Vec<F>>) {
     let span = tracing::span!(tracing::Level::TRACE, "synthesize_witnesses");
     let _enter = span.enter();
+    let shift_flags = shift_flag_indices::<InstructionSet>();
     let triples_stepwise: Vec<(Vec<F>, F, F)> = (0..inputs.padded_trace_len)
         .into_par_iter()
         .map(|i| {
             let step = inputs.clone_step(i);
-            let pc_cur = step.input_pc;
-            let aux = R1CSBuilder::calculate_jolt_aux(step, num_aux);
+            let pc_cur = step.input_state;
+            let aux =
+                R1CSBuilder::<InstructionSet>::calculate_jolt_aux(step, &shift_flags, num_aux);
             (aux, pc_cur, F::zero())
         })
         .collect();
@@ -104,7 +106,8 @@ pub struct R1CSInputs<'a, F: JoltField> {
 #[derive(Clone, Debug, Default)]
 pub struct R1CSStepInputs<F: JoltField> {
     pub padded_trace_len: usize,
-    pub input_pc: F,
+    pub input_state: F,
+    pub bytecode_a: F,
     pub bytecode_v: Vec<F>,
     pub memreg_v_reads: Vec<F>,
     pub memreg_v_writes: Vec<F>,
@@ -167,15 +170,19 @@ impl<'a, F: JoltField> R1CSInputs<'a, F> {
     }
 
     pub fn clone_step(&self, step_index: usize) -> R1CSStepInputs<F> {
-        let program_counter = if step_index > 0 && self.bytecode_a[step_index].is_zero() {
-            F::zero()
-        } else {
-            self.bytecode_a[step_index]
-        };
+        // The PC is the (compressed) address of the instruction read from the bytecode, which
+        // `bytecode_v` starts with. It is not the bytecode index `bytecode_a`, as instructions
+        // differ in size. Steps continuing a virtual sequence start in their bytecode index
+        // instead, see `step_state`.
+        let program_counter = self.bytecode_v[step_index];
+        let virtual_address = self.bytecode_a[step_index];
+        let continues_sequence =
+            self.circuit_flags_bits[12 * self.padded_trace_len + step_index].is_one();
 
         let mut output = R1CSStepInputs {
             padded_trace_len: self.padded_trace_len,
-            input_pc: program_counter,
+            input_state: step_state(program_counter, virtual_address, continues_sequence),
+            bytecode_a: virtual_address,
             bytecode_v: Vec::with_capacity(6),
             memreg_v_reads: Vec::with_capacity(7),
             memreg_v_writes: Vec::with_capacity(7),
@@ -183,7 +190,9 @@ impl<'a, F: JoltField> R1CSInputs<'a, F> {
             chunks_query: Vec::with_capacity(4),
             lookup_outputs: Vec::with_capacity(2),
             circuit_flags_bits: Vec::with_capacity(NUM_CIRCUIT_FLAGS),
-            instruction_flags_bits: Vec::with_capacity(RV32I::COUNT),
+            instruction_flags_bits: Vec::with_capacity(
+                self.instruction_flags_bits.len() / self.padded_trace_len,
+            ),
         };
         self.push_to_step(&self.bytecode_v, &mut output.bytecode_v, step_index);
         self.push_to_step(&self.memreg_v_reads, &mut output.memreg_v_reads, step_index);
@@ -295,12 +304,62 @@ impl<'a, F: JoltField> R1CSInputs<'a, F> {
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
 pub struct R1CSCommitment<C: CommitmentScheme> {
     io: Vec<C::Commitment>,
-    aux: Vec<C::Commitment>,
+    pub(crate) aux: Vec<C::Commitment>,
     /// Operand chunks { x, y }
     chunks: Vec<C::Commitment>,
     circuit_flags: Vec<C::Commitment>,
 }
 
+impl<C: CommitmentScheme> R1CSCommitment<C> {
+    /// Commitment to the state each step starts in (see `step_state`).
+    pub fn state(&self) -> Result<&C::Commitment, ProofVerifyError> {
+        self.io
+            .first()
+            .ok_or(ProofVerifyError::SegmentShapeMismatch)
+    }
+
+    /// Number of internal auxiliary variables of `key`'s step shape, one commitment each.
+    fn num_aux<F: JoltField, InstructionSet: JoltInstructionSet>(
+        key: &UniformSpartanKey<F>,
+    ) -> Result<usize, ProofVerifyError> {
+        key.num_witness_segments()
+            .checked_sub(R1CSBuilder::<InstructionSet>::num_input_vars())
+            .filter(|&num_aux| num_aux > 0)
+            .ok_or(ProofVerifyError::SegmentShapeMismatch)
+    }
+
+    /// Checks that there is one commitment per variable of `key`'s step shape in each group,
+    /// `num_chunks` being the number of operand chunks.
+    fn check_shape<F: JoltField, InstructionSet: JoltInstructionSet>(
+        &self,
+        key: &UniformSpartanKey<F>,
+        num_chunks: usize,
+    ) -> Result<(), ProofVerifyError> {
+        let num_aux = Self::num_aux::<F, InstructionSet>(key)?;
+        if self.io.len() != 2
+            || self.aux.len() != num_aux
+            || self.chunks.len() != 2 * num_chunks
+            || self.circuit_flags.len() != NUM_CIRCUIT_FLAGS
+        {
+            return Err(ProofVerifyError::SegmentShapeMismatch);
+        }
+        Ok(())
+    }
+
+    /// Commitment to the next PC wire of each step (see `next_pc_wire_value`), which is the
+    /// last internal auxiliary variable of `key`'s step shape.
+    pub fn next_pc<F: JoltField, InstructionSet: JoltInstructionSet>(
+        &self,
+        key: &UniformSpartanKey<F>,
+    ) -> Result<&C::Commitment, ProofVerifyError> {
+        let num_aux = Self::num_aux::<F, InstructionSet>(key)?;
+        if self.aux.len() != num_aux {
+            return Err(ProofVerifyError::SegmentShapeMismatch);
+        }
+        Ok(&self.aux[num_aux - 1])
+    }
+}
+
 impl<C: CommitmentScheme> AppendToTranscript for R1CSCommitment<C> {
     fn append_to_transcript(&self, label: &'static [u8], transcript: &mut ProofTranscript) {
         transcript.append_message(label, b"R1CSCommitment_begin");
@@ -322,34 +381,29 @@ impl<C: CommitmentScheme> AppendToTranscript for R1CSCommitment<C> {
 
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
 pub struct R1CSProof<F: JoltField, C: CommitmentScheme<Field = F>> {
-    pub key: UniformSpartanKey<F>,
     proof: UniformSpartanProof<F, C>,
 }
 
 impl<F: JoltField, C: CommitmentScheme<Field = F>> R1CSProof<F, C> {
     /// Computes the full witness in segments of len `padded_trace_len`, commits to new required intermediary variables.
     #[tracing::instrument(skip_all, name = "R1CSProof::compute_witness_commit")]
-    pub fn compute_witness_commit(
+    pub fn compute_witness_commit<InstructionSet: JoltInstructionSet>(
         _W: usize,
         _C: usize,
         padded_trace_len: usize,
         memory_start: u64,
         inputs: &R1CSInputs<F>,
         generators: &C::Setup,
-    ) -> Result<(UniformSpartanKey<F>, Vec<Vec<F>>, R1CSCommitment<C>), SpartanError> {
+    ) -> (Vec<Vec<F>>, R1CSCommitment<C>) {
         let span = tracing::span!(tracing::Level::TRACE, "shape_stuff");
         let _enter = span.enter();
-        let mut jolt_shape = R1CSBuilder::default();
+        let mut jolt_shape = R1CSBuilder::<InstructionSet>::default();
         R1CSBuilder::jolt_r1cs_matrices(&mut jolt_shape, memory_start);
-        let key = UniformSpartanProof::<F, C>::setup_precommitted(
-            &jolt_shape,
-            padded_trace_len,
-            memory_start,
-        )?;
         drop(_enter);
         drop(span);
 
-        let (pc_out, pc, aux) = synthesize_witnesses(inputs, jolt_shape.num_internal);
+        let (pc_out, pc, aux) =
+            synthesize_witnesses::<F, InstructionSet>(inputs, jolt_shape.num_internal);
         let io_segments = vec![pc_out, pc];
         let io_segments_ref = vec![io_segments[0].as_slice(), io_segments[1].as_slice()];
         let aux_ref: Vec<&[F]> = aux.iter().map(AsRef::as_ref).collect();
@@ -394,27 +448,26 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> R1CSProof<F, C> {
         drop(_enter);
         drop(cloning_stuff_span);
 
-        Ok((key, w_segments, r1cs_commitments))
+        (w_segments, r1cs_commitments)
     }
 
     #[tracing::instrument(skip_all, name = "R1CSProof::prove")]
     pub fn prove(
         generators: &C::Setup,
-        key: UniformSpartanKey<F>,
+        key: &UniformSpartanKey<F>,
         witness_segments: Vec<Vec<F>>,
         transcript: &mut ProofTranscript,
     ) -> Result<Self, SpartanError> {
         // TODO(sragss): Fiat shamir (relevant) commitments
-        let proof = UniformSpartanProof::prove_precommitted(
-            generators,
-            &key,
-            witness_segments,
-            transcript,
-        )?;
-        Ok(R1CSProof::<F, C> { proof, key })
+        let proof =
+            UniformSpartanProof::prove_precommitted(generators, key, witness_segments, transcript)?;
+        Ok(R1CSProof::<F, C> { proof })
     }
 
-    fn format_commitments(jolt_commitments: &JoltCommitments<C>, C: usize) -> Vec<&C::Commitment> {
+    fn format_commitments<InstructionSet: JoltInstructionSet>(
+        jolt_commitments: &JoltCommitments<C>,
+        C: usize,
+    ) -> Vec<&C::Commitment> {
         let r1cs_commitments = &jolt_commitments.r1cs;
         let bytecode_trace_commitments = &jolt_commitments.bytecode.trace_commitments;
         let memory_trace_commitments = &jolt_commitments.read_write_memory.trace_commitments
@@ -422,7 +475,7 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> R1CSProof<F, C> {
         let instruction_lookup_indices_commitments =
             &jolt_commitments.instruction_lookups.trace_commitment[..C];
         let instruction_flag_commitments = &jolt_commitments.instruction_lookups.trace_commitment
-            [jolt_commitments.instruction_lookups.trace_commitment.len() - RV32I::COUNT - 1
+            [jolt_commitments.instruction_lookups.trace_commitment.len() - InstructionSet::COUNT - 1
                 ..jolt_commitments.instruction_lookups.trace_commitment.len() - 1];
 
         let mut combined_commitments: Vec<&C::Commitment> = Vec::new();
@@ -459,18 +512,25 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> R1CSProof<F, C> {
         combined_commitments
     }
 
-    pub fn verify(
+    pub fn verify<InstructionSet: JoltInstructionSet>(
         &self,
-        generators: &C::Setup,
-        jolt_commitments: JoltCommitments<C>,
+        key: &UniformSpartanKey<F>,
+        generators: &C::VerifierSetup,
+        jolt_commitments: &JoltCommitments<C>,
         C: usize,
         transcript: &mut ProofTranscript,
-    ) -> Result<(), SpartanError> {
+    ) -> Result<(), ProofVerifyError> {
         // TODO(sragss): Fiat shamir (relevant) commitments
-        let witness_segment_commitments = Self::format_commitments(&jolt_commitments, C);
+        jolt_commitments
+            .r1cs
+            .as_ref()
+            .ok_or(ProofVerifyError::SegmentShapeMismatch)?
+            .check_shape::<F, InstructionSet>(key, C)?;
+        let witness_segment_commitments =
+            Self::format_commitments::<InstructionSet>(jolt_commitments, C);
         self.proof.verify_precommitted(
             witness_segment_commitments,
-            &self.key,
+            key,
             &[],
             generators,
             transcript,
@@ -478,9 +538,11 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> R1CSProof<F, C> {
     }
 }
 
-impl<F: JoltField> UniformShapeBuilder<F> for R1CSBuilder {
+impl<F: JoltField, InstructionSet: JoltInstructionSet> UniformShapeBuilder<F>
+    for R1CSBuilder<InstructionSet>
+{
     fn single_step_shape(&self, memory_start: u64) -> R1CSShape<F> {
-        let mut jolt_shape = R1CSBuilder::default();
+        let mut jolt_shape = R1CSBuilder::<InstructionSet>::default();
         R1CSBuilder::jolt_r1cs_matrices(&mut jolt_shape, memory_start);
         let constraints_F = jolt_shape.convert_to_field();
         let shape_single = R1CSShape::<F> {
@@ -18,6 +18,7 @@ use super::r1cs_shape::R1CSShape;
 use crate::{
     poly::{dense_mlpoly::DensePolynomial, eq_poly::EqPolynomial},
     subprotocols::sumcheck::SumcheckInstanceProof,
+    utils::errors::{ProofVerifyError, VerificationStage},
 };
 
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
@@ -26,10 +27,38 @@ pub struct UniformSpartanKey<F: JoltField> {
     num_cons_total: usize,           // Number of constraints
     num_vars_total: usize,           // Number of variables
     num_steps: usize,                // Padded number of steps
+    num_witness_segments: usize,     // Number of witness variables, each committed on its own
     pub(crate) vk_digest: F,         // digest of the verifier's key
 }
 
 impl<F: JoltField> UniformSpartanKey<F> {
+    /// Key for `padded_num_steps` uniform copies of the single step shape.
+    pub fn new(shape_single_step: R1CSShape<F>, padded_num_steps: usize) -> Self {
+        let num_constraints_total = shape_single_step.num_cons * padded_num_steps;
+        let num_aux_total = shape_single_step.num_vars * padded_num_steps;
+
+        let pad_num_constraints = num_constraints_total.next_power_of_two();
+        let pad_num_aux = num_aux_total.next_power_of_two();
+
+        let vk_digest = UniformSpartanKey::compute_digest(&shape_single_step, padded_num_steps);
+        let num_witness_segments = shape_single_step.num_used_vars();
+
+        UniformSpartanKey {
+            shape_single_step,
+            num_cons_total: pad_num_constraints,
+            num_vars_total: pad_num_aux,
+            num_steps: padded_num_steps,
+            num_witness_segments,
+            vk_digest,
+        }
+    }
+
+    /// Number of witness segments, i.e. of variables of the single step shape other than the
+    /// constant and padding. A proof must commit to exactly this many.
+    pub fn num_witness_segments(&self) -> usize {
+        self.num_witness_segments
+    }
+
     /// Returns the digest of the r1cs shape
     pub fn compute_digest(shape_single_step: &R1CSShape<F>, num_steps: usize) -> F {
         let mut compressed_bytes = Vec::new();
@@ -72,29 +101,9 @@ pub enum SpartanError {
     #[error("InvalidSumcheckProof")]
     InvalidSumcheckProof,
 
-    /// returned when the recusive sumcheck proof fails
-    #[error("InvalidOuterSumcheckProof")]
-    InvalidOuterSumcheckProof,
-
-    /// returned when the final sumcheck opening proof fails
-    #[error("InvalidOuterSumcheckClaim")]
-    InvalidOuterSumcheckClaim,
-
-    /// returned when the recusive sumcheck proof fails
-    #[error("InvalidInnerSumcheckProof")]
-    InvalidInnerSumcheckProof,
-
-    /// returned when the final sumcheck opening proof fails
-    #[error("InvalidInnerSumcheckClaim")]
-    InvalidInnerSumcheckClaim,
-
     /// returned if the supplied witness is not of the right length
     #[error("InvalidWitnessLength")]
     InvalidWitnessLength,
-
-    /// returned when an invalid PCS proof is provided
-    #[error("InvalidPCSProof")]
-    InvalidPCSProof,
 }
 
 // Trait which will kick out a small and big R1CS shape
@@ -199,24 +208,7 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> UniformSpartanProof<F, C> {
         memory_start: u64,
     ) -> Result<UniformSpartanKey<F>, SpartanError> {
         let shape_single_step = circuit.single_step_shape(memory_start);
-
-        let num_constraints_total = shape_single_step.num_cons * padded_num_steps;
-        let num_aux_total = shape_single_step.num_vars * padded_num_steps;
-
-        let pad_num_constraints = num_constraints_total.next_power_of_two();
-        let pad_num_aux = num_aux_total.next_power_of_two();
-
-        let vk_digest = UniformSpartanKey::compute_digest(&shape_single_step, padded_num_steps);
-
-        let key = UniformSpartanKey {
-            shape_single_step,
-            num_cons_total: pad_num_constraints,
-            num_vars_total: pad_num_aux,
-            num_steps: padded_num_steps,
-            vk_digest,
-        };
-
-        Ok(key)
+        Ok(UniformSpartanKey::new(shape_single_step, padded_num_steps))
     }
 
     /// produces a succinct proof of satisfiability of a `RelaxedR1CS` instance
@@ -481,12 +473,24 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> UniformSpartanProof<F, C> {
         witness_segment_commitments: Vec<&C::Commitment>,
         key: &UniformSpartanKey<F>,
         io: &[F],
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         transcript: &mut ProofTranscript,
-    ) -> Result<(), SpartanError> {
+    ) -> Result<(), ProofVerifyError> {
         assert_eq!(io.len(), 0); // Currently not using io
 
-        let N_SEGMENTS = witness_segment_commitments.len();
+        // Commitments beyond the shape's variables would be folded into padding slots that no
+        // constraint reads
+        let N_SEGMENTS = key.num_witness_segments;
+        if witness_segment_commitments.len() != N_SEGMENTS {
+            return Err(ProofVerifyError::SegmentShapeMismatch);
+        }
+        if self.claimed_witnesss_evals.len() != N_SEGMENTS {
+            return Err(VerificationStage::OpeningCount {
+                expected: N_SEGMENTS,
+                actual: self.claimed_witnesss_evals.len(),
+            }
+            .into());
+        }
 
         let (num_rounds_x, num_rounds_y) = (
             usize::try_from(key.num_cons_total.ilog2()).unwrap(),
@@ -498,17 +502,16 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> UniformSpartanProof<F, C> {
             .map(|_i| transcript.challenge_scalar(b"t"))
             .collect::<Vec<F>>();
 
-        let (claim_outer_final, r_x) = self
-            .outer_sumcheck_proof
-            .verify(F::zero(), num_rounds_x, 3, transcript)
-            .map_err(|_| SpartanError::InvalidOuterSumcheckProof)?;
+        let (claim_outer_final, r_x) =
+            self.outer_sumcheck_proof
+                .verify(F::zero(), num_rounds_x, 3, transcript)?;
 
         // verify claim_outer_final
         let (claim_Az, claim_Bz, claim_Cz) = self.outer_sumcheck_claims;
         let taus_bound_rx = EqPolynomial::new(tau).evaluate(&r_x);
         let claim_outer_final_expected = taus_bound_rx * (claim_Az * claim_Bz - claim_Cz);
         if claim_outer_final != claim_outer_final_expected {
-            return Err(SpartanError::InvalidOuterSumcheckClaim);
+            return Err(VerificationStage::SumcheckFinalClaim.into());
         }
 
         transcript.append_scalars(
@@ -527,10 +530,9 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> UniformSpartanProof<F, C> {
             + r_inner_sumcheck_RLC * self.outer_sumcheck_claims.1
             + r_inner_sumcheck_RLC * r_inner_sumcheck_RLC * self.outer_sumcheck_claims.2;
 
-        let (claim_inner_final, inner_sumcheck_r) = self
-            .inner_sumcheck_proof
-            .verify(claim_inner_joint, num_rounds_y, 2, transcript)
-            .map_err(|_| SpartanError::InvalidInnerSumcheckProof)?;
+        let (claim_inner_final, inner_sumcheck_r) =
+            self.inner_sumcheck_proof
+                .verify(claim_inner_joint, num_rounds_y, 2, transcript)?;
 
         // n_prefix = n_segments + 1
         let n_prefix = (key.num_vars_total.ilog2() as usize - key.num_steps.ilog2() as usize) + 1;
@@ -649,7 +651,7 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> UniformSpartanProof<F, C> {
         let right_expected = eval_Z;
         let claim_inner_final_expected = left_expected * right_expected;
         if claim_inner_final != claim_inner_final_expected {
-            return Err(SpartanError::InvalidInnerSumcheckClaim);
+            return Err(VerificationStage::SumcheckFinalClaim.into());
         }
 
         let r_y_point = &inner_sumcheck_r[n_prefix..];
@@ -661,7 +663,7 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> UniformSpartanProof<F, C> {
             &witness_segment_commitments,
             transcript,
         )
-        .map_err(|_| SpartanError::InvalidPCSProof)?;
+        .map_err(VerificationStage::opening_proof)?;
 
         Ok(())
     }
@@ -2,6 +2,7 @@ use super::sumcheck::{BatchedCubicSumcheck, SumcheckInstanceProof};
 use crate::field::JoltField;
 use crate::poly::eq_poly::EqPolynomial;
 use crate::poly::{dense_mlpoly::DensePolynomial, unipoly::UniPoly};
+use crate::utils::errors::{ProofVerifyError, VerificationStage};
 use crate::utils::math::Math;
 use crate::utils::thread::drop_in_background_thread;
 use crate::utils::transcript::ProofTranscript;
@@ -24,10 +25,9 @@ impl<F: JoltField> BatchedGrandProductLayerProof<F> {
         num_rounds: usize,
         degree_bound: usize,
         transcript: &mut ProofTranscript,
-    ) -> (F, Vec<F>) {
+    ) -> Result<(F, Vec<F>), ProofVerifyError> {
         self.proof
             .verify(claim, num_rounds, degree_bound, transcript)
-            .unwrap()
     }
 }
 
@@ -90,13 +90,15 @@ pub trait BatchedGrandProduct<F: JoltField>: Sized {
         grand_product_claims: &mut Vec<F>,
         r_grand_product: &mut Vec<F>,
         transcript: &mut ProofTranscript,
-    ) {
+    ) -> Result<(), ProofVerifyError> {
         let layer_proof = &layer_proofs[layer_index];
         let expected_sumcheck_claim: F = (0..grand_product_claims.len())
             .map(|i| coeffs[i] * layer_proof.left_claims[i] * layer_proof.right_claims[i] * eq_eval)
             .sum();
 
-        assert_eq!(expected_sumcheck_claim, sumcheck_claim);
+        if expected_sumcheck_claim != sumcheck_claim {
+            return Err(VerificationStage::GrandProductLayer { layer: layer_index }.into());
+        }
 
         // produce a random challenge to condense two claims into a single claim
         let r_layer = transcript.challenge_scalar(b"challenge_r_layer");
@@ -109,6 +111,7 @@ pub trait BatchedGrandProduct<F: JoltField>: Sized {
             .collect();
 
         r_grand_product.push(r_layer);
+        Ok(())
     }
 
     /// Verifies the given grand product proof.
@@ -116,7 +119,7 @@ pub trait BatchedGrandProduct<F: JoltField>: Sized {
         proof: &BatchedGrandProductProof<F>,
         claims: &Vec<F>,
         transcript: &mut ProofTranscript,
-    ) -> (Vec<F>, Vec<F>) {
+    ) -> Result<(Vec<F>, Vec<F>), ProofVerifyError> {
         let mut r_grand_product: Vec<F> = Vec::new();
         let mut claims_to_verify = claims.to_owned();
 
@@ -131,10 +134,22 @@ pub trait BatchedGrandProduct<F: JoltField>: Sized {
                 .map(|(&claim, &coeff)| claim * coeff)
                 .sum();
 
-            let (sumcheck_claim, r_sumcheck) =
-                layer_proof.verify(claim, layer_index, 3, transcript);
-            assert_eq!(claims.len(), layer_proof.left_claims.len());
-            assert_eq!(claims.len(), layer_proof.right_claims.len());
+            let layer_rejected = VerificationStage::GrandProductLayer { layer: layer_index };
+            let (sumcheck_claim, r_sumcheck) = layer_proof
+                .verify(claim, layer_index, 3, transcript)
+                .map_err(|err| match err {
+                    ProofVerifyError::Rejected(stage) => VerificationStage::GrandProductSumcheck {
+                        layer: layer_index,
+                        stage: Box::new(stage),
+                    }
+                    .into(),
+                    err => err,
+                })?;
+            if claims.len() != layer_proof.left_claims.len()
+                || claims.len() != layer_proof.right_claims.len()
+            {
+                return Err(layer_rejected.into());
+            }
 
             for (left, right) in layer_proof
                 .left_claims
@@ -145,7 +160,9 @@ pub trait BatchedGrandProduct<F: JoltField>: Sized {
                 transcript.append_scalar(b"sumcheck right claim", right);
             }
 
-            assert_eq!(r_grand_product.len(), r_sumcheck.len());
+            if r_grand_product.len() != r_sumcheck.len() {
+                return Err(layer_rejected.into());
+            }
 
             let eq_eval: F = r_grand_product
                 .iter()
@@ -164,10 +181,10 @@ pub trait BatchedGrandProduct<F: JoltField>: Sized {
                 &mut claims_to_verify,
                 &mut r_grand_product,
                 transcript,
-            );
+            )?;
         }
 
-        (claims_to_verify, r_grand_product)
+        Ok((claims_to_verify, r_grand_product))
     }
 }
 
@@ -1461,7 +1478,7 @@ impl<F: JoltField> BatchedGrandProduct<F> for ToggledBatchedGrandProduct<F> {
         grand_product_claims: &mut Vec<F>,
         r_grand_product: &mut Vec<F>,
         transcript: &mut ProofTranscript,
-    ) {
+    ) -> Result<(), ProofVerifyError> {
         let layer_proof = &layer_proofs[layer_index];
         if layer_index != layer_proofs.len() - 1 {
             // Normal grand product layer (multiplication gates)
@@ -1471,7 +1488,9 @@ impl<F: JoltField> BatchedGrandProduct<F> for ToggledBatchedGrandProduct<F> {
                 })
                 .sum();
 
-            assert_eq!(expected_sumcheck_claim, sumcheck_claim);
+            if expected_sumcheck_claim != sumcheck_claim {
+                return Err(VerificationStage::GrandProductLayer { layer: layer_index }.into());
+            }
 
             // produce a random challenge to condense two claims into a single claim
             let r_layer = transcript.challenge_scalar(b"challenge_r_layer");
@@ -1498,7 +1517,9 @@ impl<F: JoltField> BatchedGrandProduct<F> for ToggledBatchedGrandProduct<F> {
                 })
                 .sum();
 
-            assert_eq!(expected_sumcheck_claim, sumcheck_claim);
+            if expected_sumcheck_claim != sumcheck_claim {
+                return Err(VerificationStage::GrandProductLayer { layer: layer_index }.into());
+            }
 
             *grand_product_claims = layer_proof
                 .left_claims
@@ -1509,6 +1530,7 @@ impl<F: JoltField> BatchedGrandProduct<F> for ToggledBatchedGrandProduct<F> {
                 })
                 .collect();
         }
+        Ok(())
     }
 }
 
@@ -1540,10 +1562,67 @@ mod grand_product_tests {
 
         let mut transcript: ProofTranscript = ProofTranscript::new(b"test_transcript");
         let (_, r_verifier) =
-            BatchedDenseGrandProduct::verify_grand_product(&proof, &claims, &mut transcript);
+            BatchedDenseGrandProduct::verify_grand_product(&proof, &claims, &mut transcript)
+                .unwrap();
         assert_eq!(r_prover, r_verifier);
     }
 
+    #[test]
+    fn dense_verify_rejects_tampered_layer() {
+        const LAYER_SIZE: usize = 1 << 4;
+        let mut rng = test_rng();
+        let leaves: Vec<Vec<Fr>> = vec![std::iter::repeat_with(|| Fr::random(&mut rng))
+            .take(LAYER_SIZE)
+            .collect()];
+
+        let mut batched_circuit = BatchedDenseGrandProduct::construct(leaves);
+        let mut transcript: ProofTranscript = ProofTranscript::new(b"test_transcript");
+
+        let claims = batched_circuit.claims();
+        let (mut proof, _) = batched_circuit.prove_grand_product(&mut transcript);
+        proof.layers[2].left_claims[0] += Fr::from(1u64);
+
+        let mut transcript: ProofTranscript = ProofTranscript::new(b"test_transcript");
+        let result =
+            BatchedDenseGrandProduct::verify_grand_product(&proof, &claims, &mut transcript);
+        assert!(matches!(
+            result,
+            Err(ProofVerifyError::Rejected(
+                VerificationStage::GrandProductLayer { layer: 2 }
+            ))
+        ));
+    }
+
+    #[test]
+    fn dense_verify_reports_sumcheck_round() {
+        const LAYER_SIZE: usize = 1 << 4;
+        let mut rng = test_rng();
+        let leaves: Vec<Vec<Fr>> = vec![std::iter::repeat_with(|| Fr::random(&mut rng))
+            .take(LAYER_SIZE)
+            .collect()];
+
+        let mut batched_circuit = BatchedDenseGrandProduct::construct(leaves);
+        let mut transcript: ProofTranscript = ProofTranscript::new(b"test_transcript");
+
+        let claims = batched_circuit.claims();
+        let (mut proof, _) = batched_circuit.prove_grand_product(&mut transcript);
+        // Layer i runs a sumcheck of i rounds
+        let (head, tail) = proof.layers.split_at_mut(3);
+        std::mem::swap(&mut head[2].proof, &mut tail[0].proof);
+
+        let mut transcript: ProofTranscript = ProofTranscript::new(b"test_transcript");
+        let result =
+            BatchedDenseGrandProduct::verify_grand_product(&proof, &claims, &mut transcript);
+        let expected = VerificationStage::GrandProductSumcheck {
+            layer: 2,
+            stage: Box::new(VerificationStage::SumcheckRoundCount {
+                expected: 2,
+                actual: 3,
+            }),
+        };
+        assert!(matches!(result, Err(ProofVerifyError::Rejected(stage)) if stage == expected));
+    }
+
     #[test]
     fn dense_sparse_bind_parity() {
         const LAYER_SIZE: usize = 1 << 4;
@@ -53,7 +53,7 @@ pub trait QuarkBatchedGrandProduct<C: CommitmentScheme>: Sized {
         claim: &[C::Field],
         transcript: &mut ProofTranscript,
         n_rounds: usize,
-        setup: &C::Setup,
+        setup: &C::VerifierSetup,
     ) -> Result<(), QuarkError>;
 }
 
@@ -195,7 +195,7 @@ impl<C: CommitmentScheme> QuarkBatchedGrandProduct<C> for QuarkGrandProductProof
         claims: &[C::Field],
         transcript: &mut ProofTranscript,
         n_rounds: usize,
-        setup: &C::Setup,
+        setup: &C::VerifierSetup,
     ) -> Result<(), QuarkError> {
         // First we append the claimed values for the commitment and the product
         transcript.append_scalars(b"grand product claim", claims);
@@ -423,7 +423,7 @@ mod quark_grand_product_tests {
 
         // Note resetting the transcript is important
         transcript = ProofTranscript::new(b"test_transcript");
-        let result = proof.verify(&product, &mut transcript, 8, &setup);
+        let result = proof.verify(&product, &mut transcript, 8, &setup.1);
 
         assert_eq!(result, Ok(()), "Proof doesn't verify");
     }
@@ -5,7 +5,7 @@ use crate::field::JoltField;
 use crate::poly::dense_mlpoly::DensePolynomial;
 use crate::poly::unipoly::{CompressedUniPoly, UniPoly};
 use crate::r1cs::spartan::IndexablePoly;
-use crate::utils::errors::ProofVerifyError;
+use crate::utils::errors::{ProofVerifyError, VerificationStage};
 use crate::utils::mul_0_optimized;
 use crate::utils::thread::drop_in_background_thread;
 use crate::utils::transcript::{AppendToTranscript, ProofTranscript};
@@ -498,20 +498,30 @@ impl<F: JoltField> SumcheckInstanceProof<F> {
         let mut r: Vec<F> = Vec::new();
 
         // verify that there is a univariate polynomial for each round
-        assert_eq!(self.compressed_polys.len(), num_rounds);
+        if self.compressed_polys.len() != num_rounds {
+            return Err(VerificationStage::SumcheckRoundCount {
+                expected: num_rounds,
+                actual: self.compressed_polys.len(),
+            }
+            .into());
+        }
         for i in 0..self.compressed_polys.len() {
             let poly = self.compressed_polys[i].decompress(&e);
 
             // verify degree bound
             if poly.degree() != degree_bound {
-                return Err(ProofVerifyError::InvalidInputLength(
-                    degree_bound,
-                    poly.degree(),
-                ));
+                return Err(VerificationStage::SumcheckDegree {
+                    round: i,
+                    expected: degree_bound,
+                    actual: poly.degree(),
+                }
+                .into());
             }
 
             // check if G_k(0) + G_k(1) = e
-            assert_eq!(poly.eval_at_zero() + poly.eval_at_one(), e);
+            if poly.eval_at_zero() + poly.eval_at_one() != e {
+                return Err(VerificationStage::SumcheckRound { round: i }.into());
+            }
 
             // append the prover's message to the transcript
             poly.append_to_transcript(b"poly", transcript);
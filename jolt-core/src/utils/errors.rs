@@ -1,6 +1,63 @@
-use core::fmt::Debug;
+use core::fmt::{self, Debug, Display};
 use thiserror::Error;
 
+/// A sub-protocol of the Jolt verifier, used to attribute a rejected proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubProtocol {
+    Bytecode,
+    ReadWriteMemory,
+    TimestampRange,
+    InstructionLookups,
+    R1CS,
+}
+
+impl Display for SubProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SubProtocol::Bytecode => "Bytecode",
+            SubProtocol::ReadWriteMemory => "Read-write memory",
+            SubProtocol::TimestampRange => "Timestamp range check",
+            SubProtocol::InstructionLookups => "Instruction lookups",
+            SubProtocol::R1CS => "R1CS",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The verifier check that rejected a proof.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum VerificationStage {
+    #[error("sumcheck round {round}: G(0) + G(1) does not match the claim")]
+    SumcheckRound { round: usize },
+    #[error("sumcheck round {round}: expected degree {expected} but got {actual}")]
+    SumcheckDegree {
+        round: usize,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("sumcheck: expected {expected} rounds but got {actual}")]
+    SumcheckRoundCount { expected: usize, actual: usize },
+    #[error("sumcheck: final claim does not match the opened evaluations")]
+    SumcheckFinalClaim,
+    #[error("grand product layer {layer}")]
+    GrandProductLayer { layer: usize },
+    #[error("grand product layer {layer}, {stage}")]
+    GrandProductSumcheck {
+        layer: usize,
+        stage: Box<VerificationStage>,
+    },
+    #[error("opening proof: {reason}")]
+    OpeningProof { reason: String },
+    #[error("expected {expected} opened evaluations but got {actual}")]
+    OpeningCount { expected: usize, actual: usize },
+    #[error("multiset hashes: expected {expected} but got {actual}")]
+    MultisetHashCount { expected: usize, actual: usize },
+    #[error("multiset hash equality of memory {memory}")]
+    MultisetHashEquality { memory: usize },
+    #[error("fingerprint {index} does not match its grand product claim")]
+    Fingerprint { index: usize },
+}
+
 #[derive(Error, Debug, Default)]
 pub enum ProofVerifyError {
     #[error("Invalid input length, expected length {0} but got {1}")]
@@ -12,8 +69,96 @@ pub enum ProofVerifyError {
     InternalError,
     #[error("Compressed group element failed to decompress: {0:?}")]
     DecompressionError([u8; 32]),
-    #[error("R1CS proof verification failed: {0}")]
-    SpartanError(String),
     #[error("Length Error: SRS Length: {0}, Key Length: {0}")]
     KeyLengthError(usize, usize),
+    #[error("Program did not signal termination")]
+    ProgramDidNotHalt,
+    #[error("Memory layout of the program I/O does not match the preprocessing")]
+    MemoryLayoutMismatch,
+    #[error("Program input is {0} bytes, exceeding the maximum of {1}")]
+    ProgramInputTooLarge(usize, usize),
+    #[error("Program output is {0} bytes, exceeding the maximum of {1}")]
+    ProgramOutputTooLarge(usize, usize),
+    #[error("Trace length {0} exceeds the maximum of {1}")]
+    TraceTooLong(usize, usize),
+    #[error("Proof does not have the shape its position in the segment chain requires")]
+    SegmentShapeMismatch,
+    #[error("Segment {0} does not start from the final memory state of the previous segment")]
+    SegmentNotLinked(usize),
+    #[error("Segment {0} does not start at the PC the previous segment continues at")]
+    SegmentPCNotLinked(usize),
+    #[error("Segment {0} claims different program I/O than the last segment")]
+    SegmentIOMismatch(usize),
+    #[error("Segmented proof has no segments")]
+    EmptySegmentChain,
+    #[error("Proof rejected at {0}")]
+    Rejected(VerificationStage),
+    #[error("{protocol} proof rejected at {stage}")]
+    SubProtocolRejected {
+        protocol: SubProtocol,
+        stage: VerificationStage,
+    },
+}
+
+impl ProofVerifyError {
+    /// Attributes a rejected check to the sub-protocol that ran it. Errors that are already
+    /// attributed, or that don't come from a verifier check, are returned unchanged.
+    pub fn in_sub_protocol(self, protocol: SubProtocol) -> Self {
+        match self {
+            ProofVerifyError::Rejected(stage) => {
+                ProofVerifyError::SubProtocolRejected { protocol, stage }
+            }
+            err => err,
+        }
+    }
+}
+
+impl VerificationStage {
+    /// Wraps the error of a rejected opening proof, keeping the reason the commitment scheme
+    /// gave.
+    pub fn opening_proof(err: ProofVerifyError) -> Self {
+        VerificationStage::OpeningProof {
+            reason: err.to_string(),
+        }
+    }
+}
+
+impl From<VerificationStage> for ProofVerifyError {
+    fn from(stage: VerificationStage) -> Self {
+        ProofVerifyError::Rejected(stage)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum HostError {
+    #[error("Failed to compile guest `{guest}`:\n{stderr}")]
+    CompileFailed { guest: String, stderr: String },
+    #[error("Jolt RISC-V toolchain is not available: {0}")]
+    ToolchainMissing(String),
+    #[error("Failed to parse guest ELF: {0}")]
+    ElfParse(String),
+    #[error("Guest trapped at pc {pc:#x}: {cause}")]
+    EmulatorTrap { pc: u64, cause: String },
+    #[error("Guest did not terminate within {max_cycles} cycles")]
+    CycleLimit { max_cycles: u64 },
+    #[error("Segment length {0} is not a power of two")]
+    SegmentLength(usize),
+    #[error("Guest execution is not deterministic: tracing it again gave a different program I/O")]
+    NonDeterministicGuest,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+impl From<tracer::TracerError> for HostError {
+    fn from(err: tracer::TracerError) -> Self {
+        match err {
+            tracer::TracerError::Io(e) => HostError::Io(e),
+            tracer::TracerError::ElfParse(e) => HostError::ElfParse(e),
+            tracer::TracerError::Trap { pc, cause, value } => HostError::EmulatorTrap {
+                pc,
+                cause: format!("{} (value {:#x})", cause, value),
+            },
+            tracer::TracerError::CycleLimit { max_cycles } => HostError::CycleLimit { max_cycles },
+        }
+    }
 }
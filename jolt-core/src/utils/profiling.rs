@@ -1,4 +1,12 @@
-use std::{collections::HashMap, sync::Mutex};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
 
 use memory_stats::memory_stats;
 
@@ -69,3 +77,53 @@ pub fn print_current_memory_usage(label: &str) {
         println!("Failed to get current memory usage (\"{}\")", label);
     }
 }
+
+/// Tracks the peak physical memory usage of the process by sampling it on a background
+/// thread until dropped.
+pub struct PeakMemoryMonitor {
+    peak: Arc<AtomicUsize>,
+    stop: Arc<AtomicBool>,
+    sampler: Option<JoinHandle<()>>,
+}
+
+impl PeakMemoryMonitor {
+    pub fn start(interval: Duration) -> Self {
+        let peak = Arc::new(AtomicUsize::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+        let sampler = {
+            let (peak, stop) = (peak.clone(), stop.clone());
+            thread::spawn(move || loop {
+                if let Some(usage) = memory_stats() {
+                    peak.fetch_max(usage.physical_mem, Ordering::Relaxed);
+                }
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                thread::sleep(interval);
+            })
+        };
+        Self {
+            peak,
+            stop,
+            sampler: Some(sampler),
+        }
+    }
+
+    /// Peak physical memory in bytes observed so far, or `None` if memory usage cannot be
+    /// queried on this platform.
+    pub fn peak(&self) -> Option<usize> {
+        if let Some(usage) = memory_stats() {
+            self.peak.fetch_max(usage.physical_mem, Ordering::Relaxed);
+        }
+        Some(self.peak.load(Ordering::Relaxed)).filter(|&peak| peak > 0)
+    }
+}
+
+impl Drop for PeakMemoryMonitor {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(sampler) = self.sampler.take() {
+            let _ = sampler.join();
+        }
+    }
+}
@@ -7,7 +7,9 @@ use std::collections::HashMap;
 
 use common::{
     constants::{
-        DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE, DEFAULT_MEMORY_SIZE, DEFAULT_STACK_SIZE,
+        CONSOLE_ADDRESS, DEFAULT_MAX_BYTECODE_SIZE, DEFAULT_MAX_INPUT_SIZE,
+        DEFAULT_MAX_MEMORY_ADDRESS, DEFAULT_MAX_OUTPUT_SIZE, DEFAULT_MAX_TRACE_LENGTH,
+        DEFAULT_MEMORY_SIZE, DEFAULT_STACK_SIZE,
     },
     rv_trace::MemoryLayout,
 };
@@ -19,6 +21,9 @@ use syn::{
     ReturnType, Type,
 };
 
+/// Names of the commitment schemes whose setup is loaded from a structured reference string
+const KZG_SCHEMES: [&str; 2] = ["zeromorph", "hyperkzg"];
+
 #[proc_macro_attribute]
 pub fn provable(attr: TokenStream, item: TokenStream) -> TokenStream {
     let attr = parse_macro_input!(attr as AttributeArgs);
@@ -55,6 +60,7 @@ impl MacroBuilder {
         let analyze_fn = self.make_analyze_function();
         let preprocess_fn = self.make_preprocess_func();
         let prove_fn = self.make_prove_func();
+        let verify_fn = self.make_verify_func();
 
         let main_fn = if let Some(func) = self.get_func_selector() {
             if *self.get_func_name() == func {
@@ -72,6 +78,7 @@ impl MacroBuilder {
             #analyze_fn
             #preprocess_fn
             #prove_fn
+            #verify_fn
             #main_fn
         }
         .into()
@@ -88,32 +95,26 @@ impl MacroBuilder {
         let preprocess_fn_name = Ident::new(&format!("preprocess_{}", fn_name), fn_name.span());
         let prove_fn_name = Ident::new(&format!("prove_{}", fn_name), fn_name.span());
         let imports = self.make_imports();
+        let pcs = self.parse_attributes().pcs;
 
         quote! {
             #[cfg(not(feature = "guest"))]
             pub fn #build_fn_name() -> (
                 impl Fn(#(#input_types),*) -> #prove_output_ty,
-                impl Fn(jolt::Proof) -> bool
+                impl Fn(jolt::Proof<#pcs>) -> bool
             ) {
                 #imports
-                let (program, preprocessing) = #preprocess_fn_name();
-                let program = std::rc::Rc::new(program);
-                let preprocessing = std::rc::Rc::new(preprocessing);
-
-                let program_cp = program.clone();
-                let preprocessing_cp = preprocessing.clone();
+                let (program, prover_key) = #preprocess_fn_name();
+                let verifier_key = prover_key.verifier_key();
 
                 let prove_closure = move |#inputs| {
-                    let program = (*program).clone();
-                    let preprocessing = (*preprocessing).clone();
-                    #prove_fn_name(program, preprocessing, #(#input_names),*)
+                    let program = program.clone();
+                    #prove_fn_name(program, &prover_key, #(#input_names),*)
                 };
 
 
-                let verify_closure = move |proof: jolt::Proof| {
-                    let program = (*program_cp).clone();
-                    let preprocessing = (*preprocessing_cp).clone();
-                    RV32IJoltVM::verify(preprocessing, proof.proof, proof.commitments).is_ok()
+                let verify_closure = move |proof: jolt::Proof<#pcs>| {
+                    RV32IJoltVM::verify(&verifier_key, proof.proof, proof.commitments).is_ok()
                 };
 
                 (prove_closure, verify_closure)
@@ -161,7 +162,7 @@ impl MacroBuilder {
                 #set_mem_size
                 #(#set_program_args;)*
 
-                program.trace_analyze::<jolt::F>()
+                program.trace_analyze::<jolt::F>().unwrap()
              }
         }
     }
@@ -175,31 +176,64 @@ impl MacroBuilder {
         let fn_name = self.get_func_name();
         let fn_name_str = fn_name.to_string();
         let preprocess_fn_name = Ident::new(&format!("preprocess_{}", fn_name), fn_name.span());
+
+        let attributes = self.parse_attributes();
+        let max_bytecode_size = match attributes.max_bytecode_size {
+            Some(value) => quote! { #value as usize },
+            None => quote! { bytecode.len() },
+        };
+        // The default bound also caps the tight one, since `memory_size` defaults to 10 MiB
+        let max_memory_address = match attributes.max_memory_address {
+            Some(value) => quote! { #value as usize },
+            None => quote! {
+                program
+                    .max_memory_address()
+                    .min(#DEFAULT_MAX_MEMORY_ADDRESS as usize)
+            },
+        };
+        let max_trace_length = attributes.max_trace_length;
+        let pcs = attributes.pcs;
+        // Without a loaded SRS, the KZG-based schemes sample one from a public seed
+        let check_srs = attributes.requires_srs.then(|| {
+            quote! {
+                assert!(
+                    std::env::var_os(jolt::SRS_PATH_ENV).is_some(),
+                    "{} must name a structured reference string file for {}",
+                    jolt::SRS_PATH_ENV,
+                    stringify!(#pcs)
+                );
+            }
+        });
+
         quote! {
             #[cfg(not(feature = "guest"))]
             pub fn #preprocess_fn_name() -> (
                 jolt::host::Program,
-                jolt::JoltPreprocessing<jolt::F, jolt::CommitmentScheme>
+                jolt::JoltProverKey<jolt::F, #pcs>
             ) {
                 #imports
+                #check_srs
 
                 let mut program = Program::new(#guest_name);
                 program.set_func(#fn_name_str);
                 #set_std
                 #set_mem_size
-                let (bytecode, memory_init) = program.decode();
+                let (bytecode, memory_init) = program.decode().unwrap();
+                let memory_layout = program.memory_layout();
+                let max_bytecode_size = #max_bytecode_size;
+                let max_memory_address = #max_memory_address;
 
-                // TODO(moodlezoup): Feed in size parameters via macro
-                let preprocessing: JoltPreprocessing<jolt::F, jolt::CommitmentScheme> =
+                let prover_key: JoltProverKey<jolt::F, #pcs> =
                     RV32IJoltVM::preprocess(
                         bytecode,
                         memory_init,
-                        1 << 20,
-                        1 << 20,
-                        1 << 24
+                        memory_layout,
+                        max_bytecode_size,
+                        max_memory_address,
+                        #max_trace_length as usize
                     );
 
-                (program, preprocessing)
+                (program, prover_key)
             }
         }
     }
@@ -226,12 +260,14 @@ impl MacroBuilder {
         let inputs = &self.func.sig.inputs;
         let imports = self.make_imports();
 
+        let pcs = self.parse_attributes().pcs;
+
         let prove_fn_name = syn::Ident::new(&format!("prove_{}", fn_name), fn_name.span());
         quote! {
             #[cfg(not(feature = "guest"))]
             pub fn #prove_fn_name(
                 mut program: jolt::host::Program,
-                preprocessing: jolt::JoltPreprocessing<jolt::F, jolt::CommitmentScheme>,
+                prover_key: &jolt::JoltProverKey<jolt::F, #pcs>,
                 #inputs
             ) -> #prove_output_ty {
                 #imports
@@ -239,7 +275,7 @@ impl MacroBuilder {
                 #(#set_program_args;)*
 
                 let (io_device, trace, circuit_flags) =
-                    program.trace();
+                    program.trace().unwrap();
 
                 let output_bytes = io_device.outputs.clone();
 
@@ -247,7 +283,7 @@ impl MacroBuilder {
                     io_device,
                     trace,
                     circuit_flags,
-                    preprocessing,
+                    prover_key,
                 );
 
                 #handle_return
@@ -262,12 +298,65 @@ impl MacroBuilder {
         }
     }
 
+    fn make_verify_func(&self) -> TokenStream2 {
+        let (expected_output, serialize_output) = match &self.func.sig.output {
+            ReturnType::Default => (quote! {}, quote! { let outputs = Vec::<u8>::new(); }),
+            ReturnType::Type(_, ty) => (
+                quote! { expected_output: #ty, },
+                quote! {
+                    let outputs = jolt::postcard::to_stdvec::<#ty>(&expected_output).unwrap();
+                },
+            ),
+        };
+
+        let serialize_inputs = self.func_args.iter().map(|(name, _)| {
+            quote! {
+                inputs.append(&mut jolt::postcard::to_stdvec(&#name).unwrap());
+            }
+        });
+
+        let input_names = self.func_args.iter().map(|(name, _)| name);
+        let input_types = self.func_args.iter().map(|(_, ty)| ty);
+        let fn_name = self.get_func_name();
+        let imports = self.make_imports();
+        let pcs = self.parse_attributes().pcs;
+
+        let verify_fn_name = syn::Ident::new(&format!("verify_{}", fn_name), fn_name.span());
+        quote! {
+            /// Verifies `proof` and checks that it attests to an execution on the given inputs
+            /// which returned `expected_output`. Returns `Ok(false)` if the public inputs or
+            /// outputs of the proof differ, or if the program panicked.
+            #[cfg(not(feature = "guest"))]
+            pub fn #verify_fn_name(
+                verifier_key: &jolt::JoltVerifierKey<jolt::F, #pcs>,
+                proof: jolt::Proof<#pcs>,
+                #(#input_names: #input_types,)*
+                #expected_output
+            ) -> Result<bool, jolt::ProofVerifyError> {
+                #imports
+
+                let mut inputs = Vec::new();
+                #(#serialize_inputs)*
+                #serialize_output
+
+                let program_io = &proof.proof.program_io;
+                if program_io.panic || program_io.inputs != inputs || program_io.outputs != outputs {
+                    return Ok(false);
+                }
+
+                RV32IJoltVM::verify(verifier_key, proof.proof, proof.commitments)?;
+                Ok(true)
+            }
+        }
+    }
+
     fn make_main_func(&self) -> TokenStream2 {
         let attributes = self.parse_attributes();
         let memory_layout =
             MemoryLayout::new(attributes.max_input_size, attributes.max_output_size);
         let input_start = memory_layout.input_start;
         let output_start = memory_layout.output_start;
+        let termination_address = memory_layout.termination;
         let max_input_len = attributes.max_input_size as usize;
         let max_output_len = attributes.max_output_size as usize;
 
@@ -332,6 +421,11 @@ impl MacroBuilder {
                 #check_input_len
                 #block
                 #handle_return
+
+                // Signal to the host that the program has terminated
+                unsafe {
+                    core::ptr::write_volatile(#termination_address as *mut u8, 1);
+                }
             }
 
             #panic_fn
@@ -339,11 +433,18 @@ impl MacroBuilder {
     }
 
     fn make_panic(&self, panic_address: u64) -> TokenStream2 {
+        // Ends any message the guest was printing, so that the console is left zeroed
+        let clear_console = quote! {
+            unsafe {
+                core::ptr::write_volatile(#CONSOLE_ADDRESS as *mut u8, 0);
+            }
+        };
         if self.std {
             quote! {
                 #[cfg(feature = "guest")]
                 #[no_mangle]
                 pub extern "C" fn jolt_panic() {
+                    #clear_console
                     unsafe {
                         core::ptr::write_volatile(#panic_address as *mut u8, 1);
                     }
@@ -359,6 +460,7 @@ impl MacroBuilder {
                 #[cfg(feature = "guest")]
                 #[panic_handler]
                 fn panic(_info: &PanicInfo) -> ! {
+                    #clear_console
                     unsafe {
                         core::ptr::write_volatile(#panic_address as *mut u8, 1);
                     }
@@ -370,7 +472,13 @@ impl MacroBuilder {
     }
 
     fn make_allocator(&self) -> TokenStream2 {
-        if self.std {
+        if cfg!(feature = "free-list-allocator") {
+            quote! {
+                #[cfg(feature = "guest")]
+                #[global_allocator]
+                static ALLOCATOR: jolt::FreeListAllocator = jolt::FreeListAllocator;
+            }
+        } else if self.std {
             quote! {}
         } else {
             quote! {
@@ -387,7 +495,7 @@ impl MacroBuilder {
             use jolt::{
                 JoltField,
                 host::Program,
-                JoltPreprocessing,
+                JoltProverKey,
                 Jolt,
                 JoltCommitments,
                 RV32IJoltVM,
@@ -445,19 +553,42 @@ impl MacroBuilder {
 
     fn parse_attributes(&self) -> Attributes {
         let mut attributes = HashMap::<_, u64>::new();
+        let mut tight_bounds = false;
+        let mut pcs = None;
+        let mut requires_srs = false;
         for attr in &self.attr {
             match attr {
                 NestedMeta::Meta(Meta::NameValue(MetaNameValue { path, lit, .. })) => {
+                    let ident = &path.get_ident().expect("Expected identifier");
+                    if *ident == "pcs" {
+                        pcs = match lit {
+                            Lit::Str(lit) => {
+                                requires_srs = KZG_SCHEMES.contains(&lit.value().as_str());
+                                Some(Self::parse_pcs(&lit.value()))
+                            }
+                            _ => panic!("expected string literal"),
+                        };
+                        continue;
+                    }
+                    if *ident == "tight_bounds" {
+                        tight_bounds = match lit {
+                            Lit::Bool(lit) => lit.value,
+                            _ => panic!("expected boolean literal"),
+                        };
+                        continue;
+                    }
                     let value: u64 = match lit {
                         Lit::Int(lit) => lit.base10_parse().unwrap(),
                         _ => panic!("expected integer literal"),
                     };
-                    let ident = &path.get_ident().expect("Expected identifier");
                     match ident.to_string().as_str() {
                         "memory_size" => attributes.insert("memory_size", value),
                         "stack_size" => attributes.insert("stack_size", value),
                         "max_input_size" => attributes.insert("max_input_size", value),
                         "max_output_size" => attributes.insert("max_output_size", value),
+                        "max_trace_length" => attributes.insert("max_trace_length", value),
+                        "max_bytecode_size" => attributes.insert("max_bytecode_size", value),
+                        "max_memory_address" => attributes.insert("max_memory_address", value),
                         _ => panic!("invalid attribute"),
                     };
                 }
@@ -476,21 +607,53 @@ impl MacroBuilder {
             .get("max_output_size")
             .unwrap_or(&DEFAULT_MAX_OUTPUT_SIZE);
 
+        let max_trace_length = *attributes
+            .get("max_trace_length")
+            .unwrap_or(&DEFAULT_MAX_TRACE_LENGTH);
+        // With `tight_bounds`, missing bounds are derived from the decoded program
+        let max_bytecode_size = attributes
+            .get("max_bytecode_size")
+            .copied()
+            .or((!tight_bounds).then_some(DEFAULT_MAX_BYTECODE_SIZE));
+        let max_memory_address = attributes
+            .get("max_memory_address")
+            .copied()
+            .or((!tight_bounds).then_some(DEFAULT_MAX_MEMORY_ADDRESS));
+
         Attributes {
             memory_size,
             stack_size,
             max_input_size,
             max_output_size,
+            max_trace_length,
+            max_bytecode_size,
+            max_memory_address,
+            pcs: pcs.unwrap_or_else(|| Self::parse_pcs("hyrax")),
+            requires_srs,
         }
     }
 
+    /// Maps the name of a commitment scheme supported by the SDK to its type. Any other value
+    /// is parsed as the path of a type implementing `CommitmentScheme<Field = jolt::F>`.
+    /// Zeromorph and HyperKZG need a structured reference string from `JOLT_KZG_SRS`.
+    fn parse_pcs(pcs: &str) -> Type {
+        let ty = match pcs {
+            "hyrax" => "jolt::CommitmentScheme",
+            "zeromorph" => "jolt::Zeromorph<jolt::Bn254>",
+            "hyperkzg" => "jolt::HyperKZG<jolt::Bn254>",
+            ty => ty,
+        };
+        syn::parse_str(ty).expect("expected commitment scheme name or type")
+    }
+
     fn get_prove_output_type(&self) -> TokenStream2 {
+        let pcs = self.parse_attributes().pcs;
         match &self.func.sig.output {
             ReturnType::Default => quote! {
-                ((), jolt::Proof)
+                ((), jolt::Proof<#pcs>)
             },
             ReturnType::Type(_, ty) => quote! {
-                (#ty, jolt::Proof)
+                (#ty, jolt::Proof<#pcs>)
             },
         }
     }
@@ -530,4 +693,124 @@ struct Attributes {
     stack_size: u64,
     max_input_size: u64,
     max_output_size: u64,
+    max_trace_length: u64,
+    /// `None` if it should be derived from the decoded bytecode
+    max_bytecode_size: Option<u64>,
+    /// `None` if it should be derived from the program's memory size, up to the default
+    max_memory_address: Option<u64>,
+    pcs: Type,
+    /// Whether `pcs` names a KZG-based scheme, whose setup must be loaded from a file
+    requires_srs: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builder(attr: &[&str]) -> MacroBuilder {
+        let attr = attr
+            .iter()
+            .map(|attr| syn::parse_str(attr).unwrap())
+            .collect();
+        let func = syn::parse_str("fn fib(n: u32) -> u128 { n as u128 }").unwrap();
+        MacroBuilder::new(attr, func)
+    }
+
+    #[test]
+    fn default_bounds() {
+        let attributes = builder(&[]).parse_attributes();
+        assert_eq!(attributes.memory_size, DEFAULT_MEMORY_SIZE);
+        assert_eq!(attributes.stack_size, DEFAULT_STACK_SIZE);
+        assert_eq!(attributes.max_input_size, DEFAULT_MAX_INPUT_SIZE);
+        assert_eq!(attributes.max_output_size, DEFAULT_MAX_OUTPUT_SIZE);
+        assert_eq!(attributes.max_trace_length, DEFAULT_MAX_TRACE_LENGTH);
+        assert_eq!(
+            attributes.max_bytecode_size,
+            Some(DEFAULT_MAX_BYTECODE_SIZE)
+        );
+        assert_eq!(
+            attributes.max_memory_address,
+            Some(DEFAULT_MAX_MEMORY_ADDRESS)
+        );
+    }
+
+    #[test]
+    fn explicit_bounds() {
+        let attributes = builder(&[
+            "memory_size = 65536",
+            "stack_size = 1024",
+            "max_input_size = 128",
+            "max_output_size = 256",
+            "max_trace_length = 4096",
+            "max_bytecode_size = 512",
+            "max_memory_address = 8192",
+        ])
+        .parse_attributes();
+        assert_eq!(attributes.memory_size, 65536);
+        assert_eq!(attributes.stack_size, 1024);
+        assert_eq!(attributes.max_input_size, 128);
+        assert_eq!(attributes.max_output_size, 256);
+        assert_eq!(attributes.max_trace_length, 4096);
+        assert_eq!(attributes.max_bytecode_size, Some(512));
+        assert_eq!(attributes.max_memory_address, Some(8192));
+    }
+
+    #[test]
+    fn tight_bounds() {
+        let attributes = builder(&["tight_bounds = true"]).parse_attributes();
+        assert_eq!(attributes.max_trace_length, DEFAULT_MAX_TRACE_LENGTH);
+        assert_eq!(attributes.max_bytecode_size, None);
+        assert_eq!(attributes.max_memory_address, None);
+
+        // Explicit bounds take precedence
+        let attributes =
+            builder(&["max_memory_address = 8192", "tight_bounds = true"]).parse_attributes();
+        assert_eq!(attributes.max_bytecode_size, None);
+        assert_eq!(attributes.max_memory_address, Some(8192));
+
+        let attributes = builder(&["tight_bounds = false"]).parse_attributes();
+        assert_eq!(
+            attributes.max_bytecode_size,
+            Some(DEFAULT_MAX_BYTECODE_SIZE)
+        );
+    }
+
+    #[test]
+    fn commitment_scheme() {
+        let pcs = |attr: &[&str]| {
+            let pcs = builder(attr).parse_attributes().pcs;
+            quote!(#pcs).to_string()
+        };
+        assert_eq!(pcs(&[]), pcs(&["pcs = \"hyrax\""]));
+        assert_eq!(pcs(&["pcs = \"hyrax\""]), "jolt :: CommitmentScheme");
+        assert_eq!(
+            pcs(&["pcs = \"zeromorph\""]),
+            "jolt :: Zeromorph < jolt :: Bn254 >"
+        );
+        assert_eq!(
+            pcs(&["pcs = \"hyperkzg\""]),
+            "jolt :: HyperKZG < jolt :: Bn254 >"
+        );
+        let requires_srs = |attr: &[&str]| builder(attr).parse_attributes().requires_srs;
+        assert!(requires_srs(&["pcs = \"zeromorph\""]));
+        assert!(requires_srs(&["pcs = \"hyperkzg\""]));
+        assert!(!requires_srs(&["pcs = \"hyrax\""]));
+        assert!(!requires_srs(&[]));
+        assert_eq!(
+            pcs(&["pcs = \"my_crate::MyScheme\""]),
+            "my_crate :: MyScheme"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "expected boolean literal")]
+    fn tight_bounds_must_be_bool() {
+        builder(&["tight_bounds = 1"]).parse_attributes();
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid attribute")]
+    fn unknown_bound() {
+        builder(&["max_heap_size = 1024"]).parse_attributes();
+    }
 }
@@ -10,30 +10,258 @@ unsafe impl GlobalAlloc for BumpAllocator {
     unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {}
 }
 
-extern "C" {
-    static _HEAP_PTR: u8;
+/// Allocator which reuses freed memory. Allocations are rounded up to a power of two, and
+/// freed blocks are kept in one free list per size, to be handed out again to allocations of
+/// the same size. New blocks are taken from the heap with `sys_alloc`.
+pub struct FreeListAllocator;
+
+/// Fresh blocks are aligned to their size, but at most to this bound
+const MAX_BLOCK_ALIGN: usize = 4096;
+
+/// Heads of the free lists, indexed by log2 of the block size. Each free block stores the
+/// address of the next one, or 0 at the end of the list.
+static mut FREE_LISTS: [usize; usize::BITS as usize] = [0; usize::BITS as usize];
+
+unsafe impl GlobalAlloc for FreeListAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let (class, size) = match size_class(&layout) {
+            Some(class) => class,
+            None => return core::ptr::null_mut(),
+        };
+
+        let head = unsafe { FREE_LISTS[class] };
+        // Only blocks of an alignment above `MAX_BLOCK_ALIGN` may be misaligned
+        if head != 0 && head & (layout.align() - 1) == 0 {
+            unsafe { FREE_LISTS[class] = *(head as *const usize) };
+            return head as *mut u8;
+        }
+
+        sys_alloc(size, size.min(MAX_BLOCK_ALIGN).max(layout.align()))
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let (class, _) = size_class(&layout).unwrap();
+        unsafe {
+            *(ptr as *mut usize) = FREE_LISTS[class];
+            FREE_LISTS[class] = ptr as usize;
+        }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_layout = unsafe { Layout::from_size_align_unchecked(new_size, layout.align()) };
+        if size_class(&new_layout) == size_class(&layout) {
+            return ptr;
+        }
+
+        let new_ptr = unsafe { self.alloc(new_layout) };
+        if !new_ptr.is_null() {
+            unsafe {
+                core::ptr::copy_nonoverlapping(ptr, new_ptr, layout.size().min(new_size));
+                self.dealloc(ptr, layout);
+            }
+        }
+        new_ptr
+    }
+}
+
+/// Returns log2 of the size of the block backing an allocation, and the size itself. Blocks
+/// are large enough to hold a free list pointer.
+fn size_class(layout: &Layout) -> Option<(usize, usize)> {
+    let min_size = layout.size().max(layout.align());
+    let mut class = core::mem::size_of::<usize>().trailing_zeros() as usize;
+    let mut size = core::mem::size_of::<usize>();
+    while size < min_size {
+        size = size.checked_mul(2)?;
+        class += 1;
+    }
+    Some((class, size))
+}
+
+/// Start and end address of the heap, as placed by the guest's linker script
+#[cfg(not(test))]
+fn heap_bounds() -> (usize, usize) {
+    extern "C" {
+        static _HEAP_PTR: u8;
+        static _HEAP_END: u8;
+    }
+
+    unsafe {
+        (
+            (&_HEAP_PTR) as *const u8 as usize,
+            (&_HEAP_END) as *const u8 as usize,
+        )
+    }
 }
 
+#[cfg(test)]
+use tests::heap_bounds;
+
 static mut ALLOC_NEXT: usize = 0;
 
+/// Takes `size` bytes aligned to `align`, a power of two, from the heap. Returns null if the
+/// heap is exhausted, so that the allocation error handler reports it.
 #[allow(clippy::missing_safety_doc)]
 #[no_mangle]
 pub unsafe extern "C" fn sys_alloc(size: usize, align: usize) -> *mut u8 {
+    let (heap_start, heap_end) = heap_bounds();
     let mut next = unsafe { ALLOC_NEXT };
 
     if next == 0 {
-        next = unsafe { (&_HEAP_PTR) as *const u8 as usize };
+        next = heap_start;
     }
 
-    next = align_up(next, align);
-
-    let ptr = next as *mut u8;
-    next += size;
+    let end = align_up(next, align).and_then(|start| Some((start, start.checked_add(size)?)));
+    match end {
+        Some((start, end)) if end <= heap_end => {
+            unsafe { ALLOC_NEXT = end };
+            start as *mut u8
+        }
+        _ => core::ptr::null_mut(),
+    }
+}
 
-    unsafe { ALLOC_NEXT = next };
-    ptr
+fn align_up(addr: usize, align: usize) -> Option<usize> {
+    Some(addr.checked_add(align - 1)? & !(align - 1))
 }
 
-fn align_up(addr: usize, align: usize) -> usize {
-    (addr + align - 1) & !(align - 1)
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::cell::UnsafeCell;
+    use std::sync::Mutex;
+
+    use super::*;
+
+    const HEAP_SIZE: usize = 1 << 20;
+
+    #[repr(align(4096))]
+    struct Heap(UnsafeCell<[u8; HEAP_SIZE]>);
+
+    unsafe impl Sync for Heap {}
+
+    static HEAP: Heap = Heap(UnsafeCell::new([0; HEAP_SIZE]));
+
+    /// The allocator's state is global, so tests take turns
+    static LOCK: Mutex<()> = Mutex::new(());
+
+    pub(super) fn heap_bounds() -> (usize, usize) {
+        let start = HEAP.0.get() as usize;
+        (start, start + HEAP_SIZE)
+    }
+
+    fn layout(size: usize, align: usize) -> Layout {
+        Layout::from_size_align(size, align).unwrap()
+    }
+
+    fn alloc(layout: Layout) -> *mut u8 {
+        let ptr = unsafe { FreeListAllocator.alloc(layout) };
+        assert!(!ptr.is_null());
+        assert_eq!(ptr as usize % layout.align(), 0);
+        let (heap_start, heap_end) = heap_bounds();
+        assert!(heap_start <= ptr as usize && ptr as usize + layout.size() <= heap_end);
+        ptr
+    }
+
+    #[test]
+    fn size_classes() {
+        let word = core::mem::size_of::<usize>();
+        let class = |size, align| size_class(&layout(size, align)).unwrap();
+        // Blocks hold at least a free list pointer
+        assert_eq!(class(0, 1).1, word);
+        assert_eq!(class(1, 1).1, word);
+        assert_eq!(class(word, 1).1, word);
+        assert_eq!(
+            class(word + 1, 1),
+            (word.trailing_zeros() as usize + 1, 2 * word)
+        );
+        assert_eq!(class(100, 4), (7, 128));
+        assert_eq!(class(128, 4), (7, 128));
+        assert_eq!(class(129, 4), (8, 256));
+        // Blocks are at least as large as their alignment
+        assert_eq!(class(8, 64), (6, 64));
+        let bits = usize::BITS as usize;
+        assert_eq!(class(isize::MAX as usize, 1), (bits - 1, 1 << (bits - 1)));
+    }
+
+    #[test]
+    fn reuses_freed_blocks() {
+        let _lock = LOCK.lock().unwrap();
+        let a = alloc(layout(24, 8));
+        let b = alloc(layout(32, 4));
+        assert_ne!(a, b);
+        unsafe { FreeListAllocator.dealloc(a, layout(24, 8)) };
+        // Same size class
+        assert_eq!(alloc(layout(20, 4)), a);
+        // Free list is empty again
+        let c = alloc(layout(24, 8));
+        assert!(c != a && c != b);
+
+        unsafe {
+            FreeListAllocator.dealloc(b, layout(32, 4));
+            FreeListAllocator.dealloc(c, layout(24, 8));
+        }
+        // Last in, first out
+        assert_eq!(alloc(layout(32, 8)), c);
+        assert_eq!(alloc(layout(32, 8)), b);
+        // Other size classes are unaffected
+        let d = alloc(layout(64, 8));
+        assert!(d != b && d != c);
+    }
+
+    #[test]
+    fn large_alignments() {
+        let _lock = LOCK.lock().unwrap();
+        let align = 4 * MAX_BLOCK_ALIGN;
+        let a = alloc(layout(16, align));
+        unsafe { FreeListAllocator.dealloc(a, layout(16, align)) };
+        // Blocks of this size class are only aligned to `MAX_BLOCK_ALIGN`
+        let b = alloc(layout(align, MAX_BLOCK_ALIGN));
+        assert_eq!(b, a);
+        // A free block which lacks the requested alignment is not handed out
+        let misaligned = b as usize + MAX_BLOCK_ALIGN;
+        unsafe { FreeListAllocator.dealloc(misaligned as *mut u8, layout(align, 1)) };
+        let c = alloc(layout(16, align));
+        assert_ne!(c as usize, misaligned);
+    }
+
+    #[test]
+    fn realloc() {
+        let _lock = LOCK.lock().unwrap();
+        let old_layout = layout(20, 4);
+        let a = alloc(old_layout);
+        let bytes: std::vec::Vec<u8> = (0..20).collect();
+        unsafe { core::ptr::copy_nonoverlapping(bytes.as_ptr(), a, bytes.len()) };
+
+        // Within the block's size class, the block is kept
+        let b = unsafe { FreeListAllocator.realloc(a, old_layout, 32) };
+        assert_eq!(b, a);
+
+        // Across size classes, the contents move to a new block and the old one is freed
+        let c = unsafe { FreeListAllocator.realloc(b, layout(32, 4), 100) };
+        assert_ne!(c, b);
+        assert_eq!(unsafe { core::slice::from_raw_parts(c, 20) }, &bytes[..]);
+        assert_eq!(alloc(layout(32, 4)), b);
+
+        // Shrinking keeps the prefix
+        let d = unsafe { FreeListAllocator.realloc(c, layout(100, 4), 8) };
+        assert_ne!(d, c);
+        assert_eq!(unsafe { core::slice::from_raw_parts(d, 8) }, &bytes[..8]);
+    }
+
+    #[test]
+    fn exhausted_heap() {
+        let _lock = LOCK.lock().unwrap();
+        alloc(layout(8, 8));
+        let next = unsafe { ALLOC_NEXT };
+        // Allocations which don't fit return null without moving the heap pointer
+        assert!(unsafe { sys_alloc(HEAP_SIZE, 1) }.is_null());
+        assert!(unsafe { sys_alloc(usize::MAX, 1) }.is_null());
+        assert!(unsafe { sys_alloc(1, 1 << (usize::BITS - 1)) }.is_null());
+        assert!(unsafe { FreeListAllocator.alloc(layout(HEAP_SIZE, 1)) }.is_null());
+        assert_eq!(unsafe { ALLOC_NEXT }, next);
+
+        assert_eq!(align_up(13, 8), Some(16));
+        assert_eq!(align_up(usize::MAX, 2), None);
+    }
 }
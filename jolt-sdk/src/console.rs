@@ -0,0 +1,61 @@
+use core::fmt::{self, Write};
+
+/// Address of the debug console, `common::constants::CONSOLE_ADDRESS`
+#[cfg_attr(feature = "host", allow(dead_code))]
+const CONSOLE_ADDRESS: usize = 0x7fff_ffff;
+
+#[cfg(feature = "host")]
+const _: () = assert!(CONSOLE_ADDRESS as u64 == common::constants::CONSOLE_ADDRESS);
+
+/// Debug console of the guest. The tracer forwards what is written to it to the host, but it
+/// isn't part of the program's public I/O, so it costs cycles without affecting the proof.
+/// Natively, e.g. when a provable function is called on the host, it writes to stdout.
+/// Private, since a message must be ended by `_print` to leave the console zeroed, as the
+/// verifier expects of the I/O region.
+struct Console;
+
+impl Write for Console {
+    #[cfg(not(feature = "host"))]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        // Zero bytes end a message, so they are dropped
+        for byte in s.bytes().filter(|&byte| byte != 0) {
+            unsafe { core::ptr::write_volatile(CONSOLE_ADDRESS as *mut u8, byte) };
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "host")]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        std::print!("{s}");
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+pub fn _print(args: fmt::Arguments) {
+    let _ = Console.write_fmt(args);
+    #[cfg(not(feature = "host"))]
+    unsafe {
+        core::ptr::write_volatile(CONSOLE_ADDRESS as *mut u8, 0)
+    };
+}
+
+/// Prints to the debug console of the guest. The tracer forwards what is printed to the host,
+/// but it isn't part of the program's public I/O. Natively, it prints to stdout.
+#[macro_export]
+macro_rules! print {
+    ($($arg:tt)*) => {
+        $crate::_print(core::format_args!($($arg)*))
+    };
+}
+
+/// Prints to the debug console of the guest, with a newline, see [`print!`].
+#[macro_export]
+macro_rules! println {
+    () => {
+        $crate::print!("\n")
+    };
+    ($($arg:tt)*) => {
+        $crate::_print(core::format_args!("{}\n", core::format_args!($($arg)*)))
+    };
+}
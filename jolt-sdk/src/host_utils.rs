@@ -4,10 +4,16 @@ use std::path::PathBuf;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use eyre::Result;
 
-pub use ark_bn254::{Fr as F, G1Projective as G};
+pub use ark_bn254::{Bn254, Fr as F, G1Projective as G};
 pub use ark_ec::CurveGroup;
 pub use jolt_core::field::JoltField;
-use jolt_core::poly::commitment::hyrax::HyraxScheme;
+pub use jolt_core::poly::commitment::{
+    commitment_scheme::CommitmentScheme as PolynomialCommitmentScheme,
+    hyperkzg::HyperKZG,
+    hyrax::HyraxScheme,
+    kzg::{SrsError, INSECURE_SRS_ENV, SRS, SRS_PATH_ENV},
+    zeromorph::Zeromorph,
+};
 
 pub use common::{
     constants::MEMORY_OPS_PER_INSTRUCTION,
@@ -17,20 +23,27 @@ pub use jolt_core::host;
 pub use jolt_core::jolt::instruction;
 pub use jolt_core::jolt::vm::{
     bytecode::BytecodeRow,
-    rv32i_vm::{RV32IJoltProof, RV32IJoltVM, RV32I},
-    Jolt, JoltCommitments, JoltPreprocessing, JoltProof,
+    observer::{ProverEvent, ProverObserver, ProverPhase},
+    rv32i_vm::{RV32IJoltProof, RV32IJoltVM, RV32ISegmentedProof, RV32I},
+    Jolt, JoltCommitmentSize, JoltCommitments, JoltProof, JoltProofSize, JoltProverKey,
+    JoltSegmentedProof, JoltVerifierKey,
 };
+pub use jolt_core::utils::errors::ProofVerifyError;
 pub use tracer;
 
+/// The commitment scheme used by provable functions which don't specify a `pcs`
 pub type CommitmentScheme = HyraxScheme<G>;
 
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
-pub struct Proof {
-    pub proof: RV32IJoltProof<F, CommitmentScheme>,
-    pub commitments: JoltCommitments<CommitmentScheme>,
+pub struct Proof<PCS = CommitmentScheme>
+where
+    PCS: PolynomialCommitmentScheme<Field = F>,
+{
+    pub proof: RV32IJoltProof<F, PCS>,
+    pub commitments: JoltCommitments<PCS>,
 }
 
-impl Proof {
+impl<PCS: PolynomialCommitmentScheme<Field = F>> Proof<PCS> {
     /// Gets the byte size of the full proof
     pub fn size(&self) -> Result<usize> {
         let mut buffer = Vec::new();
@@ -38,6 +51,11 @@ impl Proof {
         Ok(buffer.len())
     }
 
+    /// Gets the byte size of each component of the proof and of each commitment group
+    pub fn size_breakdown(&self) -> (JoltProofSize, JoltCommitmentSize) {
+        (self.proof.size(), self.commitments.size())
+    }
+
     /// Saves the proof to a file
     pub fn save_to_file<P: Into<PathBuf>>(&self, path: P) -> Result<()> {
         let file = File::create(path.into())?;
@@ -12,3 +12,6 @@ pub use host_utils::*;
 
 pub mod alloc;
 pub use alloc::*;
+
+pub mod console;
+pub use console::*;
@@ -1,14 +1,31 @@
 use std::{
     fs::{self, File},
-    io::Write,
+    io::{BufReader, BufWriter, Write},
+    path::PathBuf,
 };
 
-use clap::{Parser, Subcommand};
-use eyre::Result;
+use ark_bn254::Bn254;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use clap::{Parser, Subcommand, ValueEnum};
+use eyre::{eyre, Result, WrapErr};
 use rand::prelude::SliceRandom;
 use sysinfo::System;
 
-use jolt_core::host::toolchain;
+use common::constants::{
+    DEFAULT_MAX_BYTECODE_SIZE, DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_MEMORY_ADDRESS,
+    DEFAULT_MAX_OUTPUT_SIZE, DEFAULT_MAX_TRACE_LENGTH,
+};
+use jolt_core::{
+    host::{toolchain, Program},
+    jolt::vm::{
+        observer::ProverEvent, rv32i_vm::RV32IJoltVM, Jolt, JoltProverKey, JoltVerifierKey,
+    },
+    poly::commitment::{
+        commitment_scheme::CommitmentScheme, hyperkzg::HyperKZG, hyrax::HyraxScheme,
+        zeromorph::Zeromorph,
+    },
+};
+use jolt_sdk::{Proof, F, G};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -25,15 +42,206 @@ enum Command {
         name: String,
     },
     /// Installs the required RISC-V toolchains for Rust
-    InstallToolchain,
+    InstallToolchain {
+        /// Install from a local toolchain archive instead of downloading it
+        #[arg(long)]
+        from: Option<PathBuf>,
+        /// Expected SHA-256 checksum of the archive, instead of the pinned one
+        #[arg(long)]
+        sha256: Option<String>,
+    },
+    /// Proves the execution of a guest ELF on the given input
+    Prove {
+        /// Path to the guest ELF
+        #[arg(long)]
+        elf: PathBuf,
+        /// Path to the guest input, serialized with postcard
+        #[arg(long)]
+        input: Option<PathBuf>,
+        /// Path to write the proof to
+        #[arg(long)]
+        out: PathBuf,
+        /// Polynomial commitment scheme
+        #[arg(long, value_enum, default_value_t = Pcs::Hyrax)]
+        pcs: Pcs,
+        /// Maximum input size of the guest, in bytes
+        #[arg(long, default_value_t = DEFAULT_MAX_INPUT_SIZE)]
+        max_input_size: u64,
+        /// Maximum output size of the guest, in bytes
+        #[arg(long, default_value_t = DEFAULT_MAX_OUTPUT_SIZE)]
+        max_output_size: u64,
+        /// Maximum trace length supported by the preprocessing
+        #[arg(long, default_value_t = DEFAULT_MAX_TRACE_LENGTH as usize)]
+        max_trace_length: usize,
+    },
+    /// Preprocesses a guest ELF and writes the key needed to verify its proofs
+    Preprocess {
+        /// Path to the guest ELF
+        #[arg(long)]
+        elf: PathBuf,
+        /// Path to write the verifier key to
+        #[arg(long)]
+        verifier_key: PathBuf,
+        /// Polynomial commitment scheme
+        #[arg(long, value_enum, default_value_t = Pcs::Hyrax)]
+        pcs: Pcs,
+        /// Maximum input size of the guest, in bytes
+        #[arg(long, default_value_t = DEFAULT_MAX_INPUT_SIZE)]
+        max_input_size: u64,
+        /// Maximum output size of the guest, in bytes
+        #[arg(long, default_value_t = DEFAULT_MAX_OUTPUT_SIZE)]
+        max_output_size: u64,
+        /// Maximum trace length supported by the preprocessing
+        #[arg(long, default_value_t = DEFAULT_MAX_TRACE_LENGTH as usize)]
+        max_trace_length: usize,
+    },
+    /// Verifies a proof produced by `jolt prove` against a guest ELF or a verifier key
+    Verify {
+        /// Path to the proof
+        #[arg(long)]
+        proof: PathBuf,
+        /// Path to the guest ELF
+        #[arg(long, required_unless_present = "verifier_key")]
+        elf: Option<PathBuf>,
+        /// Path to a verifier key written by `jolt preprocess`, instead of the guest ELF
+        #[arg(long, conflicts_with = "elf")]
+        verifier_key: Option<PathBuf>,
+        /// Polynomial commitment scheme the proof was generated with
+        #[arg(long, value_enum, default_value_t = Pcs::Hyrax)]
+        pcs: Pcs,
+        /// Maximum input size the proof was generated with, in bytes
+        #[arg(long, default_value_t = DEFAULT_MAX_INPUT_SIZE)]
+        max_input_size: u64,
+        /// Maximum output size the proof was generated with, in bytes
+        #[arg(long, default_value_t = DEFAULT_MAX_OUTPUT_SIZE)]
+        max_output_size: u64,
+        /// Maximum trace length the proof was generated with
+        #[arg(long, default_value_t = DEFAULT_MAX_TRACE_LENGTH as usize)]
+        max_trace_length: usize,
+    },
+    /// Traces a guest ELF and reports its instruction mix, trace length and proving cost
+    Analyze {
+        /// Path to the guest ELF
+        #[arg(long)]
+        elf: PathBuf,
+        /// Path to the guest input, serialized with postcard
+        #[arg(long)]
+        input: Option<PathBuf>,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+        /// Maximum input size of the guest, in bytes
+        #[arg(long, default_value_t = DEFAULT_MAX_INPUT_SIZE)]
+        max_input_size: u64,
+        /// Maximum output size of the guest, in bytes
+        #[arg(long, default_value_t = DEFAULT_MAX_OUTPUT_SIZE)]
+        max_output_size: u64,
+        /// Witness field elements the prover gets through per second and core on this
+        /// machine, to estimate the proving time with
+        #[arg(long)]
+        throughput: Option<f64>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Pcs {
+    Hyrax,
+    Zeromorph,
+    #[value(name = "hyperkzg")]
+    HyperKZG,
 }
 
-fn main() {
+fn main() -> Result<()> {
+    // Shows the warnings and progress that jolt-core reports through `tracing`
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
     let cli = Cli::parse();
     match cli.command {
         Command::New { name } => create_project(name),
-        Command::InstallToolchain => install_toolchain(),
+        Command::InstallToolchain { from, sha256 } => install_toolchain(from, sha256)?,
+        Command::Prove {
+            elf,
+            input,
+            out,
+            pcs,
+            max_input_size,
+            max_output_size,
+            max_trace_length,
+        } => {
+            let mut program = guest_program(elf);
+            program.set_max_input_size(max_input_size);
+            program.set_max_output_size(max_output_size);
+            if let Some(input) = input {
+                program.set_input_bytes(&fs::read(input)?);
+            }
+            match pcs {
+                Pcs::Hyrax => prove::<HyraxScheme<G>>(program, max_trace_length, out)?,
+                Pcs::Zeromorph => prove::<Zeromorph<Bn254>>(program, max_trace_length, out)?,
+                Pcs::HyperKZG => prove::<HyperKZG<Bn254>>(program, max_trace_length, out)?,
+            }
+        }
+        Command::Preprocess {
+            elf,
+            verifier_key,
+            pcs,
+            max_input_size,
+            max_output_size,
+            max_trace_length,
+        } => {
+            let mut program = guest_program(elf);
+            program.set_max_input_size(max_input_size);
+            program.set_max_output_size(max_output_size);
+            let source = VerifierKeySource::Elf(program, max_trace_length);
+            match pcs {
+                Pcs::Hyrax => write_verifier_key::<HyraxScheme<G>>(source, verifier_key)?,
+                Pcs::Zeromorph => write_verifier_key::<Zeromorph<Bn254>>(source, verifier_key)?,
+                Pcs::HyperKZG => write_verifier_key::<HyperKZG<Bn254>>(source, verifier_key)?,
+            }
+        }
+        Command::Verify {
+            proof,
+            elf,
+            verifier_key,
+            pcs,
+            max_input_size,
+            max_output_size,
+            max_trace_length,
+        } => {
+            let source = match (verifier_key, elf) {
+                (Some(verifier_key), _) => VerifierKeySource::File(verifier_key),
+                (None, Some(elf)) => {
+                    let mut program = guest_program(elf);
+                    program.set_max_input_size(max_input_size);
+                    program.set_max_output_size(max_output_size);
+                    VerifierKeySource::Elf(program, max_trace_length)
+                }
+                (None, None) => unreachable!("clap requires --elf without --verifier-key"),
+            };
+            match pcs {
+                Pcs::Hyrax => verify::<HyraxScheme<G>>(source, proof)?,
+                Pcs::Zeromorph => verify::<Zeromorph<Bn254>>(source, proof)?,
+                Pcs::HyperKZG => verify::<HyperKZG<Bn254>>(source, proof)?,
+            }
+        }
+        Command::Analyze {
+            elf,
+            input,
+            json,
+            max_input_size,
+            max_output_size,
+            throughput,
+        } => {
+            let mut program = guest_program(elf);
+            program.set_max_input_size(max_input_size);
+            program.set_max_output_size(max_output_size);
+            if let Some(input) = input {
+                program.set_input_bytes(&fs::read(input)?);
+            }
+            analyze(program, json, throughput)?
+        }
     }
+    Ok(())
 }
 
 fn create_project(name: String) {
@@ -42,11 +250,158 @@ fn create_project(name: String) {
     create_guest_files(&name).expect("file creation failed");
 }
 
-fn install_toolchain() {
-    if let Err(err) = toolchain::install_toolchain() {
-        panic!("toolchain install failed: {}", err);
-    }
+fn install_toolchain(from: Option<PathBuf>, sha256: Option<String>) -> Result<()> {
+    let result = match from {
+        Some(archive) => toolchain::install_toolchain_from(&archive, sha256.as_deref()),
+        None => toolchain::install_toolchain(),
+    };
+    result.wrap_err("toolchain install failed")?;
     display_welcome();
+    Ok(())
+}
+
+fn guest_program(elf: PathBuf) -> Program {
+    let guest = elf.file_stem().map_or("guest".to_string(), |stem| {
+        stem.to_string_lossy().into_owned()
+    });
+    let mut program = Program::new(&guest);
+    program.elf = Some(elf);
+    program
+}
+
+/// Where `jolt verify` gets the verifier key from
+enum VerifierKeySource {
+    /// Preprocess the guest ELF, supporting traces up to the given length
+    Elf(Program, usize),
+    /// Read a key written by `jolt preprocess`
+    File(PathBuf),
+}
+
+impl VerifierKeySource {
+    fn load<PCS: CommitmentScheme<Field = F>>(self) -> Result<JoltVerifierKey<F, PCS>> {
+        match self {
+            VerifierKeySource::Elf(mut program, max_trace_length) => {
+                Ok(preprocess::<PCS>(&mut program, max_trace_length)?.verifier_key())
+            }
+            VerifierKeySource::File(path) => {
+                let file = File::open(&path)
+                    .wrap_err_with(|| format!("failed to open {}", path.display()))?;
+                // The key may have been tampered with, so its curve points are validated
+                Ok(JoltVerifierKey::deserialize_uncompressed(BufReader::new(
+                    file,
+                ))?)
+            }
+        }
+    }
+}
+
+fn preprocess<PCS: CommitmentScheme<Field = F>>(
+    program: &mut Program,
+    max_trace_length: usize,
+) -> Result<JoltProverKey<F, PCS>> {
+    let (bytecode, memory_init) = program.decode()?;
+    Ok(RV32IJoltVM::preprocess(
+        bytecode,
+        memory_init,
+        program.memory_layout(),
+        DEFAULT_MAX_BYTECODE_SIZE as usize,
+        DEFAULT_MAX_MEMORY_ADDRESS as usize,
+        max_trace_length,
+    ))
+}
+
+fn write_verifier_key<PCS: CommitmentScheme<Field = F>>(
+    source: VerifierKeySource,
+    out: PathBuf,
+) -> Result<()> {
+    let verifier_key = source.load::<PCS>()?;
+    verifier_key.serialize_uncompressed(BufWriter::new(File::create(&out)?))?;
+    println!("Verifier key written to {}", out.display());
+    Ok(())
+}
+
+fn prove<PCS: CommitmentScheme<Field = F>>(
+    mut program: Program,
+    max_trace_length: usize,
+    out: PathBuf,
+) -> Result<()> {
+    let prover_key = preprocess::<PCS>(&mut program, max_trace_length)?;
+
+    let (io_device, trace, circuit_flags) = program.trace()?;
+    if io_device.panic {
+        eprintln!("Warning: the guest panicked");
+    }
+    let (proof, commitments) = RV32IJoltVM::prove_with_observer(
+        io_device,
+        trace,
+        circuit_flags,
+        &prover_key,
+        &mut report_progress,
+    );
+
+    let proof = Proof::<PCS> { proof, commitments };
+    proof.save_to_file(&out)?;
+    println!("Proof written to {}", out.display());
+    Ok(())
+}
+
+fn report_progress(event: ProverEvent) {
+    match event {
+        ProverEvent::TraceLength {
+            trace_length,
+            padded_trace_length,
+        } => eprintln!("Trace length: {trace_length} (padded to {padded_trace_length})"),
+        ProverEvent::SegmentStarted {
+            segment,
+            num_segments,
+        } => eprintln!("Segment {}/{}", segment + 1, num_segments),
+        ProverEvent::SegmentFinished {
+            segment,
+            peak_memory,
+        } => {
+            if let Some(peak_memory) = peak_memory {
+                eprintln!(
+                    "Segment {} done, peak memory {:.2} GB",
+                    segment + 1,
+                    peak_memory as f64 / 1e9
+                );
+            }
+        }
+        ProverEvent::PhaseStarted { .. } => {}
+        ProverEvent::PhaseFinished {
+            phase,
+            elapsed,
+            remaining_work,
+        } => eprintln!(
+            "{phase} done in {:.2}s, ~{:.0}% remaining",
+            elapsed.as_secs_f64(),
+            remaining_work * 100.0
+        ),
+    }
+}
+
+fn verify<PCS: CommitmentScheme<Field = F>>(
+    source: VerifierKeySource,
+    proof: PathBuf,
+) -> Result<()> {
+    let proof = Proof::<PCS>::from_file(proof)?;
+    let verifier_key = source.load::<PCS>()?;
+
+    RV32IJoltVM::verify(&verifier_key, proof.proof, proof.commitments)
+        .map_err(|e| eyre!("proof verification failed: {}", e))?;
+    println!("Proof verified");
+    Ok(())
+}
+
+fn analyze(program: Program, json: bool, throughput: Option<f64>) -> Result<()> {
+    let summary = program.trace_analyze::<F>()?;
+    let report = summary.report::<F>(throughput);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", report);
+    }
+    Ok(())
 }
 
 fn create_folder_structure(name: &str) -> Result<()> {
@@ -84,8 +84,17 @@ pub struct Cpu {
     decode_cache: DecodeCache,
     unsigned_data_mask: u64,
     pub tracer: Rc<Tracer>,
+    // The most recent exception raised by an instruction, along with the
+    // address of that instruction
+    last_exception: Option<(Trap, u64)>,
+    custom_instruction_handler: Option<CustomInstructionHandler>,
 }
 
+/// Computes the value written to `rd` by a custom-0/custom-1 instruction, given its
+/// trace row (with `rs1_val` and `rs2_val` populated). Returns `None` if the
+/// instruction is not supported, in which case it raises an illegal instruction trap.
+pub type CustomInstructionHandler = fn(&RVTraceRow) -> Option<u64>;
+
 #[derive(Clone)]
 pub enum Xlen {
     Bit32,
@@ -101,11 +110,13 @@ pub enum PrivilegeMode {
     Machine,
 }
 
+#[derive(Clone, Debug)]
 pub struct Trap {
     pub trap_type: TrapType,
     pub value: u64, // Trap type specific value
 }
 
+#[derive(Clone, Copy, Debug)]
 #[allow(dead_code)]
 pub enum TrapType {
     InstructionAddressMisaligned,
@@ -222,6 +233,26 @@ fn get_trap_cause(trap: &Trap, xlen: &Xlen) -> u64 {
     }
 }
 
+/// The `imm` field of the JAL instruction that C.J and C.JAL expand to.
+fn compressed_jump_imm(halfword: u32) -> u32 {
+    let offset = match halfword & 0x1000 {
+        0x1000 => 0xfffff000,
+        _ => 0,
+    } | // offset[31:12] <= [12]
+        ((halfword >> 1) & 0x800) | // offset[11] <= [12]
+        ((halfword >> 7) & 0x10) | // offset[4] <= [11]
+        ((halfword >> 1) & 0x300) | // offset[9:8] <= [10:9]
+        ((halfword << 2) & 0x400) | // offset[10] <= [8]
+        ((halfword >> 1) & 0x40) | // offset[6] <= [7]
+        ((halfword << 1) & 0x80) | // offset[7] <= [6]
+        ((halfword >> 2) & 0xe) | // offset[3:1] <= [5:3]
+        ((halfword << 3) & 0x20); // offset[5] <= [2]
+    ((offset >> 1) & 0x80000) | // imm[19] <= offset[20]
+        ((offset << 8) & 0x7fe00) | // imm[18:9] <= offset[10:1]
+        ((offset >> 3) & 0x100) | // imm[8] <= offset[11]
+        ((offset >> 12) & 0xff) // imm[7:0] <= offset[19:12]
+}
+
 impl Cpu {
     /// Creates a new `Cpu`.
     ///
@@ -245,6 +276,8 @@ impl Cpu {
             decode_cache: DecodeCache::new(),
             unsigned_data_mask: 0xffffffffffffffff,
             tracer,
+            last_exception: None,
+            custom_instruction_handler: None,
         };
         cpu.x[0xb] = 0x1020; // I don't know why but Linux boot seems to require this initialization
         cpu.write_csr_raw(CSR_MISA_ADDRESS, 0x800000008014312f);
@@ -294,7 +327,10 @@ impl Cpu {
         let instruction_address = self.pc;
         match self.tick_operate() {
             Ok(()) => {}
-            Err(e) => self.handle_exception(e, instruction_address),
+            Err(e) => {
+                self.last_exception = Some((e.clone(), instruction_address));
+                self.handle_exception(e, instruction_address)
+            }
         }
         self.mmu.tick(&mut self.csr[CSR_MIP_ADDRESS as usize]);
         self.handle_interrupt(self.pc);
@@ -327,14 +363,16 @@ impl Cpu {
             }
             false => {
                 self.pc = self.pc.wrapping_add(2); // 16-bit length compressed instruction
-                self.uncompress(original_word & 0xffff)
+                Self::uncompress(original_word & 0xffff, &self.xlen)
             }
         };
 
         match self.decode(word).cloned() {
             Ok(inst) => {
                 // setup trace
-                let trace_inst = inst.trace.unwrap()(&inst, &self.xlen, word, instruction_address);
+                let mut trace_inst =
+                    inst.trace.unwrap()(&inst, &self.xlen, word, instruction_address);
+                trace_inst.is_compressed = (original_word & 0x3) != 0x3;
                 self.tracer.start_instruction(trace_inst);
                 self.tracer.capture_pre_state(self.x, &self.xlen);
 
@@ -887,8 +925,10 @@ impl Cpu {
         }
     }
 
+    /// Expands a 16-bit RVC instruction to its 32-bit equivalent. Returns an invalid
+    /// instruction word if `halfword` is not a valid compressed instruction for `xlen`.
     // @TODO: Optimize
-    fn uncompress(&self, halfword: u32) -> u32 {
+    pub fn uncompress(halfword: u32, xlen: &Xlen) -> u32 {
         let op = halfword & 0x3; // [1:0]
         let funct3 = (halfword >> 13) & 0x7; // [15:13]
 
@@ -1014,8 +1054,12 @@ impl Cpu {
                         // @TODO: Support HINTs
                         // r == 0 and imm != 0 is HINTs
                     }
+                    1 if matches!(xlen, Xlen::Bit32) => {
+                        // C.JAL
+                        // jal x1, imm
+                        return (compressed_jump_imm(halfword) << 12) | (1 << 7) | 0x6f;
+                    }
                     1 => {
-                        // @TODO: Support C.JAL in 32-bit mode
                         // C.ADDIW
                         // addiw r, r, imm
                         let r = (halfword >> 7) & 0x1f;
@@ -1204,23 +1248,7 @@ impl Cpu {
                     5 => {
                         // C.J
                         // jal x0, imm
-                        let offset = match halfword & 0x1000 {
-								0x1000 => 0xfffff000,
-								_ => 0
-							} | // offset[31:12] <= [12]
-							((halfword >> 1) & 0x800) | // offset[11] <= [12]
-							((halfword >> 7) & 0x10) | // offset[4] <= [11]
-							((halfword >> 1) & 0x300) | // offset[9:8] <= [10:9]
-							((halfword << 2) & 0x400) | // offset[10] <= [8]
-							((halfword >> 1) & 0x40) | // offset[6] <= [7]
-							((halfword << 1) & 0x80) | // offset[7] <= [6]
-							((halfword >> 2) & 0xe) | // offset[3:1] <= [5:3]
-							((halfword << 3) & 0x20); // offset[5] <= [2]
-                        let imm = ((offset >> 1) & 0x80000) | // imm[19] <= offset[20]
-							((offset << 8) & 0x7fe00) | // imm[18:9] <= offset[10:1]
-							((offset >> 3) & 0x100) | // imm[8] <= offset[11]
-							((offset >> 12) & 0xff); // imm[7:0] <= offset[19:12]
-                        return (imm << 12) | 0x6f;
+                        return (compressed_jump_imm(halfword) << 12) | 0x6f;
                     }
                     6 => {
                         // C.BEQZ
@@ -1428,7 +1456,7 @@ impl Cpu {
             true => original_word,
             false => {
                 original_word &= 0xffff;
-                self.uncompress(original_word)
+                Self::uncompress(original_word, &self.xlen)
             }
         };
 
@@ -1456,6 +1484,52 @@ impl Cpu {
         &mut self.mmu
     }
 
+    /// Sets the handler used to execute custom-0/custom-1 instructions. Without a
+    /// handler, these instructions raise an illegal instruction trap.
+    pub fn set_custom_instruction_handler(&mut self, handler: CustomInstructionHandler) {
+        self.custom_instruction_handler = Some(handler);
+    }
+
+    fn execute_custom_instruction(
+        &mut self,
+        opcode: RV32IM,
+        word: u32,
+        address: u64,
+    ) -> Result<(), Trap> {
+        let illegal_instruction = Trap {
+            trap_type: TrapType::IllegalInstruction,
+            value: word as u64,
+        };
+        let handler = match self.custom_instruction_handler {
+            Some(handler) => handler,
+            None => return Err(illegal_instruction),
+        };
+
+        let f = parse_format_r(word);
+        let row = RVTraceRow {
+            instruction: trace_custom_instruction(opcode, &self.xlen, word, address),
+            register_state: RegisterState {
+                rs1_val: Some(self.unsigned_data(self.x[f.rs1])),
+                rs2_val: Some(self.unsigned_data(self.x[f.rs2])),
+                rd_post_val: None,
+            },
+            memory_state: None,
+        };
+        match handler(&row) {
+            Some(rd_val) => {
+                self.x[f.rd] = self.sign_extend(rd_val as i64);
+                Ok(())
+            }
+            None => Err(illegal_instruction),
+        }
+    }
+
+    /// Takes the most recent exception raised by an instruction, if any,
+    /// along with the address of the instruction that raised it.
+    pub fn take_exception(&mut self) -> Option<(Trap, u64)> {
+        self.last_exception.take()
+    }
+
     /// Returns mutable `Terminal`
     pub fn get_mut_terminal(&mut self) -> &mut Box<dyn Terminal> {
         self.mmu.get_mut_uart().get_mut_terminal()
@@ -1848,6 +1922,36 @@ fn trace_r(inst: &Instruction, xlen: &Xlen, word: u32, address: u64) -> ELFInstr
         rs2: Some(normalize_register(f.rs2)),
         rd: Some(normalize_register(f.rd)),
         virtual_sequence_index: None,
+        virtual_sequence_remaining: None,
+        is_compressed: false,
+    }
+}
+
+fn trace_custom(inst: &Instruction, xlen: &Xlen, word: u32, address: u64) -> ELFInstruction {
+    trace_custom_instruction(RV32IM::from_str(inst.name).unwrap(), xlen, word, address)
+}
+
+// Custom instructions are R-type; funct3 and funct7 are packed into `imm` so that
+// the instruction set can tell apart instructions sharing a custom opcode.
+fn trace_custom_instruction(
+    opcode: RV32IM,
+    xlen: &Xlen,
+    word: u32,
+    address: u64,
+) -> ELFInstruction {
+    let f = parse_format_r(word);
+    let funct3 = (word >> 12) & 0x7;
+    let funct7 = word >> 25;
+    ELFInstruction {
+        opcode,
+        address: normalize_u64(address, xlen),
+        imm: Some(funct7 << 3 | funct3),
+        rs1: Some(normalize_register(f.rs1)),
+        rs2: Some(normalize_register(f.rs2)),
+        rd: Some(normalize_register(f.rd)),
+        virtual_sequence_index: None,
+        virtual_sequence_remaining: None,
+        is_compressed: false,
     }
 }
 
@@ -1861,6 +1965,8 @@ fn trace_i(inst: &Instruction, xlen: &Xlen, word: u32, address: u64) -> ELFInstr
         rs2: None,
         rd: Some(normalize_register(f.rd)),
         virtual_sequence_index: None,
+        virtual_sequence_remaining: None,
+        is_compressed: false,
     }
 }
 
@@ -1874,6 +1980,8 @@ fn trace_s(inst: &Instruction, xlen: &Xlen, word: u32, address: u64) -> ELFInstr
         rs2: Some(normalize_register(f.rs2)),
         rd: None,
         virtual_sequence_index: None,
+        virtual_sequence_remaining: None,
+        is_compressed: false,
     }
 }
 
@@ -1887,6 +1995,8 @@ fn trace_b(inst: &Instruction, xlen: &Xlen, word: u32, address: u64) -> ELFInstr
         rs2: Some(normalize_register(f.rs2)),
         rd: None,
         virtual_sequence_index: None,
+        virtual_sequence_remaining: None,
+        is_compressed: false,
     }
 }
 
@@ -1900,6 +2010,8 @@ fn trace_u(inst: &Instruction, xlen: &Xlen, word: u32, address: u64) -> ELFInstr
         rs2: None,
         rd: Some(normalize_register(f.rd)),
         virtual_sequence_index: None,
+        virtual_sequence_remaining: None,
+        is_compressed: false,
     }
 }
 
@@ -1914,10 +2026,12 @@ fn trace_j(inst: &Instruction, xlen: &Xlen, word: u32, address: u64) -> ELFInstr
         rs2: None,
         rd: Some(normalize_register(f.rd)),
         virtual_sequence_index: None,
+        virtual_sequence_remaining: None,
+        is_compressed: false,
     }
 }
 
-const INSTRUCTION_NUM: usize = 116;
+const INSTRUCTION_NUM: usize = 122;
 
 // @TODO: Reorder in often used order as
 pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
@@ -2013,7 +2127,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_r,
-        trace: None,
+        trace: Some(trace_r),
     },
     Instruction {
         mask: 0xf800707f,
@@ -2059,7 +2173,31 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_r,
-        trace: None,
+        trace: Some(trace_r),
+    },
+    Instruction {
+        mask: 0xf800707f,
+        data: 0xa000202f,
+        name: "AMOMAX.W",
+        operation: |cpu, word, _address| {
+            let f = parse_format_r(word);
+            let tmp = match cpu.mmu.load_word(cpu.x[f.rs1] as u64) {
+                Ok(data) => data as i32,
+                Err(e) => return Err(e),
+            };
+            let max = match cpu.x[f.rs2] as i32 >= tmp {
+                true => cpu.x[f.rs2] as i32,
+                false => tmp,
+            };
+            match cpu.mmu.store_word(cpu.x[f.rs1] as u64, max as u32) {
+                Ok(()) => {}
+                Err(e) => return Err(e),
+            };
+            cpu.x[f.rd] = tmp as i64;
+            Ok(())
+        },
+        disassemble: dump_format_r,
+        trace: Some(trace_r),
     },
     Instruction {
         mask: 0xf800707f,
@@ -2107,7 +2245,55 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_r,
-        trace: None,
+        trace: Some(trace_r),
+    },
+    Instruction {
+        mask: 0xf800707f,
+        data: 0x8000202f,
+        name: "AMOMIN.W",
+        operation: |cpu, word, _address| {
+            let f = parse_format_r(word);
+            let tmp = match cpu.mmu.load_word(cpu.x[f.rs1] as u64) {
+                Ok(data) => data as i32,
+                Err(e) => return Err(e),
+            };
+            let min = match cpu.x[f.rs2] as i32 <= tmp {
+                true => cpu.x[f.rs2] as i32,
+                false => tmp,
+            };
+            match cpu.mmu.store_word(cpu.x[f.rs1] as u64, min as u32) {
+                Ok(()) => {}
+                Err(e) => return Err(e),
+            };
+            cpu.x[f.rd] = tmp as i64;
+            Ok(())
+        },
+        disassemble: dump_format_r,
+        trace: Some(trace_r),
+    },
+    Instruction {
+        mask: 0xf800707f,
+        data: 0xc000202f,
+        name: "AMOMINU.W",
+        operation: |cpu, word, _address| {
+            let f = parse_format_r(word);
+            let tmp = match cpu.mmu.load_word(cpu.x[f.rs1] as u64) {
+                Ok(data) => data,
+                Err(e) => return Err(e),
+            };
+            let min = match cpu.x[f.rs2] as u32 <= tmp {
+                true => cpu.x[f.rs2] as u32,
+                false => tmp,
+            };
+            match cpu.mmu.store_word(cpu.x[f.rs1] as u64, min) {
+                Ok(()) => {}
+                Err(e) => return Err(e),
+            };
+            cpu.x[f.rd] = tmp as i32 as i64;
+            Ok(())
+        },
+        disassemble: dump_format_r,
+        trace: Some(trace_r),
     },
     Instruction {
         mask: 0xf800707f,
@@ -2153,7 +2339,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_r,
-        trace: None,
+        trace: Some(trace_r),
     },
     Instruction {
         mask: 0xf800707f,
@@ -2196,7 +2382,30 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_r,
-        trace: None,
+        trace: Some(trace_r),
+    },
+    Instruction {
+        mask: 0xf800707f,
+        data: 0x2000202f,
+        name: "AMOXOR.W",
+        operation: |cpu, word, _address| {
+            let f = parse_format_r(word);
+            let tmp = match cpu.mmu.load_word(cpu.x[f.rs1] as u64) {
+                Ok(data) => data as i32 as i64,
+                Err(e) => return Err(e),
+            };
+            match cpu
+                .mmu
+                .store_word(cpu.x[f.rs1] as u64, (cpu.x[f.rs2] ^ tmp) as u32)
+            {
+                Ok(()) => {}
+                Err(e) => return Err(e),
+            };
+            cpu.x[f.rd] = tmp;
+            Ok(())
+        },
+        disassemble: dump_format_r,
+        trace: Some(trace_r),
     },
     Instruction {
         mask: 0xfe00707f,
@@ -2441,6 +2650,26 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
         disassemble: dump_format_csr,
         trace: None,
     },
+    Instruction {
+        mask: 0x0000007f,
+        data: 0x0000000b,
+        name: "CUSTOM_0",
+        operation: |cpu, word, address| {
+            cpu.execute_custom_instruction(RV32IM::CUSTOM_0, word, address)
+        },
+        disassemble: dump_format_r,
+        trace: Some(trace_custom),
+    },
+    Instruction {
+        mask: 0x0000007f,
+        data: 0x0000002b,
+        name: "CUSTOM_1",
+        operation: |cpu, word, address| {
+            cpu.execute_custom_instruction(RV32IM::CUSTOM_1, word, address)
+        },
+        disassemble: dump_format_r,
+        trace: Some(trace_custom),
+    },
     Instruction {
         mask: 0xfe00707f,
         data: 0x02004033,
@@ -3076,7 +3305,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_r,
-        trace: None,
+        trace: Some(trace_r),
     },
     Instruction {
         mask: 0x0000007f,
@@ -3386,7 +3615,7 @@ pub const INSTRUCTIONS: [Instruction; INSTRUCTION_NUM] = [
             Ok(())
         },
         disassemble: dump_format_r,
-        trace: None,
+        trace: Some(trace_r),
     },
     Instruction {
         mask: 0x0000707f,
@@ -4080,6 +4309,88 @@ mod test_cpu {
         // @TODO: Test compressed instruction operation
     }
 
+    #[test]
+    fn custom_instruction() {
+        // custom-0 instruction with funct3 = 1: a0 = a1 + a2
+        let word = (12 << 20) | (11 << 15) | (1 << 12) | (10 << 7) | 0x0b;
+        let mut cpu = create_cpu();
+        cpu.get_mut_mmu().init_memory(4);
+        cpu.update_pc(DRAM_BASE);
+        cpu.get_mut_mmu().store_word(DRAM_BASE, word).unwrap();
+        cpu.x[11] = 3;
+        cpu.x[12] = 4;
+
+        // Without a handler, custom instructions are illegal
+        assert!(cpu.tick_operate().is_err());
+
+        cpu.update_pc(DRAM_BASE);
+        cpu.set_custom_instruction_handler(|row| match row.instruction.custom_funct() {
+            Some((1, 0)) => Some(row.register_state.rs1_val? + row.register_state.rs2_val?),
+            _ => None,
+        });
+        cpu.tick_operate().unwrap();
+        assert_eq!(7, cpu.read_register(10));
+    }
+
+    #[test]
+    fn trace_atomic_instructions() {
+        let amo = |funct5: u32, rs2: u32, rd: u32| {
+            (funct5 << 27) | (rs2 << 20) | (11 << 15) | (2 << 12) | (rd << 7) | 0x2f
+        };
+        // amomin.w a0, a2, (a1); lr.w a3, (a1); sc.w a4, a2, (a1); sc.w a5, a2, (a1)
+        let program = [
+            amo(0b10000, 12, 10),
+            amo(0b00010, 0, 13),
+            amo(0b00011, 12, 14),
+            amo(0b00011, 12, 15),
+        ];
+        let mut cpu = create_cpu();
+        cpu.get_mut_mmu().init_memory(64);
+        cpu.update_pc(DRAM_BASE);
+        for (i, word) in program.iter().enumerate() {
+            cpu.get_mut_mmu()
+                .store_word(DRAM_BASE + 4 * i as u64, *word)
+                .unwrap();
+        }
+        let address = DRAM_BASE + 32;
+        cpu.get_mut_mmu().store_word(address, 5).unwrap();
+        cpu.x[11] = address as i64;
+        cpu.x[12] = -3;
+
+        for _ in 0..program.len() {
+            cpu.tick_operate().unwrap();
+        }
+
+        let rows = cpu.tracer.rows.borrow();
+        // The AMO's memory state is its load; the stored value follows from it
+        assert_eq!(rows[0].instruction.opcode, RV32IM::AMOMIN_W);
+        assert_eq!(rows[0].register_state.rd_post_val, Some(5));
+        assert_eq!(
+            rows[0].memory_state,
+            Some(MemoryState::Read { address, value: 5 })
+        );
+        assert_eq!(rows[1].instruction.opcode, RV32IM::LR_W);
+        assert_eq!(
+            rows[1].memory_state,
+            Some(MemoryState::Read {
+                address,
+                value: 0xffff_fffd
+            })
+        );
+        // The first SC.W consumes the reservation, so the second one fails
+        assert_eq!(rows[2].instruction.opcode, RV32IM::SC_W);
+        assert_eq!(rows[2].register_state.rd_post_val, Some(0));
+        assert_eq!(
+            rows[2].memory_state,
+            Some(MemoryState::Write {
+                address,
+                post_value: 0xffff_fffd
+            })
+        );
+        assert_eq!(rows[3].register_state.rd_post_val, Some(1));
+        assert_eq!(rows[3].memory_state, None);
+    }
+
     #[test]
     fn fetch() {
         // .fetch() reads four bytes from the memory
@@ -4129,13 +4440,26 @@ mod test_cpu {
         let mut cpu = create_cpu();
         // .uncompress() doesn't directly return an instruction but
         // it returns uncompressed word. Then you need to call .decode().
-        match cpu.decode(cpu.uncompress(0x20)) {
+        match cpu.decode(Cpu::uncompress(0x20, &cpu.xlen)) {
             Ok(inst) => assert_eq!(inst.name, "ADDI"),
             Err(_e) => panic!("Failed to decode"),
         };
         // @TODO: Should I test all compressed instructions?
     }
 
+    #[test]
+    fn uncompress_c_jal() {
+        // c.jal 72 in RV32C shares its encoding with c.addiw x1, 8 in RV64C
+        let halfword = 0x20a1;
+        assert_eq!(Cpu::uncompress(halfword, &Xlen::Bit32), 0x048000ef);
+
+        let mut cpu = create_cpu();
+        match cpu.decode(Cpu::uncompress(halfword, &Xlen::Bit64)) {
+            Ok(inst) => assert_eq!(inst.name, "ADDIW"),
+            Err(_e) => panic!("Failed to decode"),
+        };
+    }
+
     #[test]
     fn wfi() {
         let wfi_instruction = 0x10500073;
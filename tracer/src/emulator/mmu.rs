@@ -40,6 +40,8 @@ pub struct Mmu {
     uart: Uart,
 
     pub jolt_device: JoltDevice,
+    /// Bytes written to the debug console which haven't been forwarded to the host yet
+    pub console: Vec<u8>,
     tracer: Rc<Tracer>,
 
     /// Address translation can be affected `mstatus` (MPRV, MPP in machine mode)
@@ -113,6 +115,7 @@ impl Mmu {
             clint: Clint::new(),
             uart: Uart::new(terminal),
             jolt_device: JoltDevice::new(0, 0),
+            console: Vec::new(),
             tracer,
             mstatus: 0,
             page_cache_enabled: false,
@@ -543,6 +546,8 @@ impl Mmu {
         if effective_address < DRAM_BASE {
             if self.jolt_device.is_output(effective_address)
                 || self.jolt_device.is_panic(effective_address)
+                || self.jolt_device.is_termination(effective_address)
+                || self.jolt_device.is_console(effective_address)
             {
                 self.tracer.push_memory(MemoryState::Write {
                     address: effective_address,
@@ -642,8 +647,11 @@ impl Mmu {
                 0x10000000..=0x100000ff => self.uart.store(effective_address, value),
                 0x10001000..=0x10001FFF => self.disk.store(effective_address, value),
                 _ => {
-                    if self.jolt_device.is_output(effective_address)
+                    if self.jolt_device.is_console(effective_address) {
+                        self.console.push(value);
+                    } else if self.jolt_device.is_output(effective_address)
                         || self.jolt_device.is_panic(effective_address)
+                        || self.jolt_device.is_termination(effective_address)
                     {
                         self.jolt_device.store(effective_address, value);
                     } else {
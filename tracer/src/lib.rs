@@ -5,77 +5,159 @@ use std::{fs::File, io::Read, path::PathBuf};
 
 use common::{self, constants::RAM_START_ADDRESS};
 use emulator::{
-    cpu::{self, Xlen},
+    cpu::{self, Cpu, Xlen},
     default_terminal::DefaultTerminal,
     Emulator,
 };
 
-use object::{Object, ObjectSection, SectionKind};
+use object::{Object, ObjectSection, ObjectSymbol, SectionKind, SymbolKind};
+use thiserror::Error;
 
 mod decode;
 mod emulator;
+#[doc(hidden)]
+pub mod test_utils;
 mod trace;
 
 pub use common::rv_trace::{
-    ELFInstruction, JoltDevice, MemoryState, RVTraceRow, RegisterState, RV32IM,
+    ELFInstruction, ELFSymbol, JoltDevice, MemoryState, RVTraceRow, RegisterState, RV32IM,
 };
+pub use emulator::cpu::CustomInstructionHandler;
 
 use crate::decode::decode_raw;
 
+#[derive(Error, Debug)]
+pub enum TracerError {
+    #[error("Failed to read ELF file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to parse ELF file: {0}")]
+    ElfParse(String),
+    #[error("Guest trapped at pc {pc:#x}: {cause} (value {value:#x})")]
+    Trap { pc: u64, cause: String, value: u64 },
+    #[error("Guest did not terminate within {max_cycles} cycles")]
+    CycleLimit { max_cycles: u64 },
+}
+
+/// Runs the guest program to completion and returns its execution trace. Guests which run for
+/// more than `max_cycles` cycles are stopped with an error. Custom-0/custom-1 instructions are
+/// executed by `custom_instruction_handler`; without one they trap. Each message the guest
+/// writes to its debug console is passed to `on_console`.
 #[tracing::instrument(skip_all)]
 pub fn trace(
     elf: &PathBuf,
     inputs: &[u8],
     input_size: u64,
     output_size: u64,
-) -> (Vec<RVTraceRow>, JoltDevice) {
+    max_cycles: u64,
+    custom_instruction_handler: Option<CustomInstructionHandler>,
+    on_console: impl FnMut(&[u8]),
+) -> Result<(Vec<RVTraceRow>, JoltDevice), TracerError> {
+    let mut output = Vec::new();
+    let device = trace_chunked(
+        elf,
+        inputs,
+        input_size,
+        output_size,
+        max_cycles,
+        custom_instruction_handler,
+        usize::MAX,
+        |mut rows| output.append(&mut rows),
+        on_console,
+    )?;
+    Ok((output, device))
+}
+
+/// Same as `trace`, but hands the trace to `on_chunk` in chunks of `chunk_length` rows (the
+/// last one may be shorter) as the guest runs, so that the whole trace is never held in memory.
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all)]
+pub fn trace_chunked(
+    elf: &PathBuf,
+    inputs: &[u8],
+    input_size: u64,
+    output_size: u64,
+    max_cycles: u64,
+    custom_instruction_handler: Option<CustomInstructionHandler>,
+    chunk_length: usize,
+    mut on_chunk: impl FnMut(Vec<RVTraceRow>),
+    mut on_console: impl FnMut(&[u8]),
+) -> Result<JoltDevice, TracerError> {
+    assert!(chunk_length > 0, "chunk length must be positive");
     let term = DefaultTerminal::new();
     let mut emulator = Emulator::new(Box::new(term));
     emulator.update_xlen(get_xlen());
+    if let Some(handler) = custom_instruction_handler {
+        emulator
+            .get_mut_cpu()
+            .set_custom_instruction_handler(handler);
+    }
 
     let mut jolt_device = JoltDevice::new(input_size, output_size);
     jolt_device.inputs = inputs.to_vec();
     emulator.get_mut_cpu().get_mut_mmu().jolt_device = jolt_device;
 
-    let mut elf_file = File::open(elf).unwrap();
-
-    let mut elf_contents = Vec::new();
-    elf_file.read_to_end(&mut elf_contents).unwrap();
-
+    let elf_contents = read_elf(elf)?;
     emulator.setup_program(elf_contents);
 
-    let mut prev_pc = 0;
-    loop {
-        let pc = emulator.get_cpu().read_pc();
+    // The guest signals termination (or a panic) by writing to a reserved
+    // address in the I/O region; see `MemoryLayout`.
+    let mut cycles = 0;
+    while !emulator.get_mut_cpu().get_mut_mmu().jolt_device.halted() {
+        if cycles == max_cycles {
+            return Err(TracerError::CycleLimit { max_cycles });
+        }
         emulator.tick();
+        cycles += 1;
 
-        // This is a trick to see if the program has terminated by throwing itself
-        // into an infinite loop. It seems to be a good heuristic for now but we
-        // should eventually migrate to an explicit shutdown signal.
-        if prev_pc == pc {
-            break;
+        // Jolt guests have no trap handlers, so any exception is fatal
+        if let Some((trap, pc)) = emulator.get_mut_cpu().take_exception() {
+            return Err(TracerError::Trap {
+                pc,
+                cause: format!("{:?}", trap.trap_type),
+                value: trap.value,
+            });
         }
 
-        prev_pc = pc;
+        // A zero byte ends a console message
+        let console = &mut emulator.get_mut_cpu().get_mut_mmu().console;
+        if console.last() == Some(&0) {
+            console.pop();
+            if !console.is_empty() {
+                on_console(console);
+                console.clear();
+            }
+        }
+
+        let mut rows = emulator.get_mut_cpu().tracer.rows.try_borrow_mut().unwrap();
+        if rows.len() >= chunk_length {
+            let rest = rows.split_off(chunk_length);
+            on_chunk(std::mem::replace(&mut rows, rest));
+        }
     }
 
-    let mut rows = emulator.get_mut_cpu().tracer.rows.try_borrow_mut().unwrap();
-    let mut output = Vec::new();
-    output.append(&mut rows);
-    drop(rows);
+    let rows = emulator.get_mut_cpu().tracer.rows.take();
+    if !rows.is_empty() {
+        on_chunk(rows);
+    }
+    // Unterminated message, e.g. if the guest panicked while printing
+    let console = &emulator.get_mut_cpu().get_mut_mmu().console;
+    if !console.is_empty() {
+        on_console(console);
+    }
 
     let device = emulator.get_mut_cpu().get_mut_mmu().jolt_device.clone();
 
-    (output, device)
+    Ok(device)
 }
 
-#[tracing::instrument(skip_all)]
-pub fn decode(elf: &PathBuf) -> (Vec<ELFInstruction>, Vec<(u64, u8)>) {
-    let mut elf_file = File::open(elf).unwrap();
-    let mut elf_contents = Vec::new();
-    elf_file.read_to_end(&mut elf_contents).unwrap();
+/// Instructions of an ELF and the initial memory (address, byte) of its data sections.
+pub type DecodedElf = (Vec<ELFInstruction>, Vec<(u64, u8)>);
 
-    let obj = object::File::parse(&*elf_contents).unwrap();
+#[tracing::instrument(skip_all)]
+pub fn decode(elf: &PathBuf) -> Result<DecodedElf, TracerError> {
+    let elf_contents = read_elf(elf)?;
+    let obj =
+        object::File::parse(&*elf_contents).map_err(|e| TracerError::ElfParse(e.to_string()))?;
 
     let sections = obj
         .sections()
@@ -86,16 +168,30 @@ pub fn decode(elf: &PathBuf) -> (Vec<ELFInstruction>, Vec<(u64, u8)>) {
     let mut data = Vec::new();
 
     for section in sections {
-        let raw_data = section.data().unwrap();
+        let raw_data = section
+            .data()
+            .map_err(|e| TracerError::ElfParse(e.to_string()))?;
 
         if let SectionKind::Text = section.kind() {
-            for (chunk, word) in raw_data.chunks(4).enumerate() {
-                let word = u32::from_le_bytes(word.try_into().unwrap());
-                let address = chunk as u64 * 4 + section.address();
+            // RVC instructions are 2 bytes long, so instructions are only 2-byte aligned
+            let mut offset = 0;
+            while offset + 2 <= raw_data.len() {
+                let address = section.address() + offset as u64;
+                let halfword = u16::from_le_bytes([raw_data[offset], raw_data[offset + 1]]);
+                let is_compressed = halfword & 0x3 != 0x3;
+                let word = if is_compressed {
+                    Cpu::uncompress(halfword as u32, &get_xlen())
+                } else if let Some(bytes) = raw_data.get(offset..offset + 4) {
+                    u32::from_le_bytes(bytes.try_into().unwrap())
+                } else {
+                    break;
+                };
+                offset += if is_compressed { 2 } else { 4 };
 
                 if let Ok(inst) = decode_raw(word) {
                     if let Some(trace) = inst.trace {
-                        let inst = trace(&inst, &get_xlen(), word, address);
+                        let mut inst = trace(&inst, &get_xlen(), word, address);
+                        inst.is_compressed = is_compressed;
                         instructions.push(inst);
                         continue;
                     }
@@ -109,6 +205,8 @@ pub fn decode(elf: &PathBuf) -> (Vec<ELFInstruction>, Vec<(u64, u8)>) {
                     rd: None,
                     imm: None,
                     virtual_sequence_index: None,
+                    virtual_sequence_remaining: None,
+                    is_compressed,
                 });
             }
         }
@@ -118,7 +216,42 @@ pub fn decode(elf: &PathBuf) -> (Vec<ELFInstruction>, Vec<(u64, u8)>) {
         }
     }
 
-    (instructions, data)
+    Ok((instructions, data))
+}
+
+/// Returns the function symbols of the ELF file, sorted by address.
+pub fn symbols(elf: &PathBuf) -> Result<Vec<ELFSymbol>, TracerError> {
+    let elf_contents = read_elf(elf)?;
+    let obj =
+        object::File::parse(&*elf_contents).map_err(|e| TracerError::ElfParse(e.to_string()))?;
+
+    let mut symbols: Vec<ELFSymbol> = obj
+        .symbols()
+        .filter(|symbol| symbol.kind() == SymbolKind::Text && symbol.size() > 0)
+        .filter_map(|symbol| {
+            let name = symbol.name().ok()?;
+            Some(ELFSymbol {
+                name: format!("{:#}", rustc_demangle::demangle(name)),
+                address: symbol.address(),
+                size: symbol.size(),
+            })
+        })
+        .collect();
+    symbols.sort_by_key(|symbol| symbol.address);
+    symbols.dedup_by_key(|symbol| symbol.address);
+
+    Ok(symbols)
+}
+
+/// Reads the ELF file at the given path, checking that it can be parsed
+/// before it is handed to the emulator (which panics on malformed input).
+fn read_elf(elf: &PathBuf) -> Result<Vec<u8>, TracerError> {
+    let mut elf_file = File::open(elf)?;
+    let mut elf_contents = Vec::new();
+    elf_file.read_to_end(&mut elf_contents)?;
+
+    object::File::parse(&*elf_contents).map_err(|e| TracerError::ElfParse(e.to_string()))?;
+    Ok(elf_contents)
 }
 
 fn get_xlen() -> Xlen {
@@ -128,3 +261,146 @@ fn get_xlen() -> Xlen {
         _ => panic!("Emulator only supports 32 / 64 bit registers."),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::*;
+    use common::{constants::CONSOLE_ADDRESS, rv_trace::MemoryLayout};
+
+    fn write_elf(name: &str, code: &[u32]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("jolt-tracer-test-{name}.elf"));
+        std::fs::write(&path, elf(code)).unwrap();
+        path
+    }
+
+    #[test]
+    fn trace_terminates() {
+        let layout = MemoryLayout::new(64, 64);
+        let code = [vec![addi(5, 0, 1)], terminate(layout.termination)].concat();
+        let elf = write_elf("terminates", &code);
+
+        let (rows, device) = trace(&elf, &[], 64, 64, 100, None, |_| {}).unwrap();
+        assert_eq!(rows.len(), 4);
+        assert!(device.termination && !device.panic);
+    }
+
+    #[test]
+    fn trace_atomics() {
+        let layout = MemoryLayout::new(64, 64);
+        let address = RAM_START_ADDRESS as u32 + 0x100;
+        let code = [
+            li(5, address),
+            vec![
+                addi(6, 0, 7),
+                sw(6, 5, 0),
+                addi(7, 0, 3),
+                amoadd_w(10, 5, 7),
+                addi(7, 0, 4),
+                amoswap_w(11, 5, 7),
+                lr_w(12, 5),
+                addi(7, 0, 9),
+                sc_w(13, 5, 7),
+                lw(14, 5, 0),
+            ],
+            terminate(layout.termination),
+        ]
+        .concat();
+        let elf = write_elf("atomics", &code);
+
+        let (rows, device) = trace(&elf, &[], 64, 64, 100, None, |_| {}).unwrap();
+        assert!(device.termination);
+        let rd_post_val = |opcode: RV32IM| {
+            let row = rows
+                .iter()
+                .find(|row| row.instruction.opcode == opcode)
+                .unwrap();
+            row.register_state.rd_post_val
+        };
+        assert_eq!(rd_post_val(RV32IM::AMOADD_W), Some(7));
+        assert_eq!(rd_post_val(RV32IM::AMOSWAP_W), Some(10));
+        assert_eq!(rd_post_val(RV32IM::LR_W), Some(4));
+        // The reservation taken by LR.W is still held, so SC.W succeeds
+        assert_eq!(rd_post_val(RV32IM::SC_W), Some(0));
+        assert_eq!(rd_post_val(RV32IM::LW), Some(9));
+    }
+
+    #[test]
+    fn trace_forwards_console() {
+        let layout = MemoryLayout::new(64, 64);
+        let print = |message: &[u8]| {
+            message
+                .iter()
+                .flat_map(|&byte| [addi(6, 0, byte as u32), sb(6, 5, 0)])
+                .collect::<Vec<_>>()
+        };
+        let code = [
+            li(5, CONSOLE_ADDRESS as u32),
+            print(b"hi\0"),
+            // Empty messages are dropped
+            print(b"\0"),
+            print(b"there\0"),
+            // Forwarded even though the guest doesn't end it
+            print(b"!"),
+            terminate(layout.termination),
+        ]
+        .concat();
+        let elf = write_elf("console", &code);
+
+        let mut messages = vec![];
+        let (_, device) = trace(&elf, &[], 64, 64, 100, None, |message| {
+            messages.push(message.to_vec())
+        })
+        .unwrap();
+        assert!(device.termination);
+        assert_eq!(messages, [&b"hi"[..], b"there", b"!"]);
+        // The console isn't part of the program I/O
+        assert!(device.outputs.is_empty());
+    }
+
+    #[test]
+    fn decode_compressed() {
+        let text = [
+            c_li(5, 1).to_le_bytes().to_vec(),
+            words(&[addi(6, 5, 1)]),
+            c_addi(6, -3).to_le_bytes().to_vec(),
+            // The first half of a full instruction, cut off by the end of the section
+            words(&[addi(7, 0, 1)])[..2].to_vec(),
+        ]
+        .concat();
+        let path = std::env::temp_dir().join("jolt-tracer-test-decode-compressed.elf");
+        std::fs::write(&path, elf_text(&text)).unwrap();
+
+        let (instructions, _) = decode(&path).unwrap();
+        let decoded: Vec<_> = instructions
+            .iter()
+            .map(|inst| {
+                (
+                    inst.address - RAM_START_ADDRESS,
+                    inst.opcode,
+                    inst.rd,
+                    inst.is_compressed,
+                )
+            })
+            .collect();
+        assert_eq!(
+            decoded,
+            [
+                (0, RV32IM::ADDI, Some(5), true),
+                (2, RV32IM::ADDI, Some(6), false),
+                (6, RV32IM::ADDI, Some(6), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn trace_stops_at_cycle_limit() {
+        let elf = write_elf("loops", &[jal(0, 0)]);
+
+        let result = trace(&elf, &[], 64, 64, 1000, None, |_| {});
+        assert!(matches!(
+            result,
+            Err(TracerError::CycleLimit { max_cycles: 1000 })
+        ));
+    }
+}
@@ -0,0 +1,199 @@
+//! Helpers to build small guest programs by hand, so that the tracer and the prover can be
+//! tested without the guest toolchain.
+
+use common::constants::RAM_START_ADDRESS;
+
+const EHDR_SIZE: usize = 52;
+const SHDR_SIZE: usize = 40;
+const SHSTRTAB: &[u8] = b"\0.text\0.shstrtab\0";
+
+/// Builds a minimal RV32 executable whose `.text` section holds `code`, loaded at (and
+/// entered from) `RAM_START_ADDRESS`.
+pub fn elf(code: &[u32]) -> Vec<u8> {
+    elf_text(&words(code))
+}
+
+/// Same as `elf`, but takes the `.text` section as bytes, so that it can mix compressed
+/// instructions (see `c_li`) with full ones (see `words`).
+pub fn elf_text(text: &[u8]) -> Vec<u8> {
+    let text_offset = EHDR_SIZE;
+    let shstrtab_offset = text_offset + text.len();
+    let shdr_offset = (shstrtab_offset + SHSTRTAB.len()).next_multiple_of(4);
+
+    let mut elf = Vec::new();
+    // e_ident: ELF magic, 32-bit, little-endian, version 1
+    elf.extend_from_slice(&[0x7f, b'E', b'L', b'F', 1, 1, 1, 0]);
+    elf.resize(16, 0);
+    push_u16(&mut elf, 2); // e_type: executable
+    push_u16(&mut elf, 0xf3); // e_machine: RISC-V
+    push_u32(&mut elf, 1); // e_version
+    push_u32(&mut elf, RAM_START_ADDRESS as u32); // e_entry
+    push_u32(&mut elf, 0); // e_phoff
+    push_u32(&mut elf, shdr_offset as u32); // e_shoff
+    push_u32(&mut elf, 0); // e_flags
+    push_u16(&mut elf, EHDR_SIZE as u16); // e_ehsize
+    push_u16(&mut elf, 32); // e_phentsize
+    push_u16(&mut elf, 0); // e_phnum
+    push_u16(&mut elf, SHDR_SIZE as u16); // e_shentsize
+    push_u16(&mut elf, 3); // e_shnum
+    push_u16(&mut elf, 2); // e_shstrndx
+
+    elf.extend_from_slice(text);
+    elf.extend_from_slice(SHSTRTAB);
+    elf.resize(shdr_offset, 0);
+
+    // Null section
+    elf.resize(shdr_offset + SHDR_SIZE, 0);
+    // .text: PROGBITS, ALLOC | EXECINSTR
+    push_section_header(
+        &mut elf,
+        1,
+        1,
+        0x6,
+        RAM_START_ADDRESS as u32,
+        text_offset,
+        text.len(),
+    );
+    // .shstrtab: STRTAB
+    push_section_header(&mut elf, 7, 3, 0, 0, shstrtab_offset, SHSTRTAB.len());
+
+    elf
+}
+
+/// Little-endian bytes of the instructions `code`.
+pub fn words(code: &[u32]) -> Vec<u8> {
+    code.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+/// Loads the 32-bit constant `value` into `rd`.
+pub fn li(rd: u32, value: u32) -> Vec<u32> {
+    // ADDI sign-extends its immediate, which LUI has to make up for
+    let upper = value.wrapping_add(0x800) & 0xffff_f000;
+    vec![lui(rd, upper), addi(rd, rd, value.wrapping_sub(upper))]
+}
+
+/// Halts the guest by writing to `termination`, the termination address of its
+/// `MemoryLayout`. Clobbers x31.
+pub fn terminate(termination: u64) -> Vec<u32> {
+    [li(31, termination as u32), vec![sb(0, 31, 0)]].concat()
+}
+
+pub fn lui(rd: u32, upper: u32) -> u32 {
+    (upper & 0xffff_f000) | (rd << 7) | 0b0110111
+}
+
+pub fn addi(rd: u32, rs1: u32, imm: u32) -> u32 {
+    i_type(imm, rs1, 0b000, rd, 0b0010011)
+}
+
+pub fn add(rd: u32, rs1: u32, rs2: u32) -> u32 {
+    r_type(0, rs2, rs1, 0b000, rd, 0b0110011)
+}
+
+pub fn div(rd: u32, rs1: u32, rs2: u32) -> u32 {
+    r_type(0b0000001, rs2, rs1, 0b100, rd, 0b0110011)
+}
+
+pub fn lw(rd: u32, rs1: u32, offset: u32) -> u32 {
+    i_type(offset, rs1, 0b010, rd, 0b0000011)
+}
+
+pub fn sb(rs2: u32, rs1: u32, offset: u32) -> u32 {
+    s_type(offset, rs2, rs1, 0b000)
+}
+
+pub fn sw(rs2: u32, rs1: u32, offset: u32) -> u32 {
+    s_type(offset, rs2, rs1, 0b010)
+}
+
+/// Jumps by `offset` bytes, which must be even and fit in 21 bits.
+pub fn jal(rd: u32, offset: i32) -> u32 {
+    let imm = offset as u32;
+    ((imm >> 20) & 1) << 31
+        | ((imm >> 1) & 0x3ff) << 21
+        | ((imm >> 11) & 1) << 20
+        | ((imm >> 12) & 0xff) << 12
+        | (rd << 7)
+        | 0b1101111
+}
+
+pub fn lr_w(rd: u32, rs1: u32) -> u32 {
+    r_type(0b00010 << 2, 0, rs1, 0b010, rd, 0b0101111)
+}
+
+pub fn sc_w(rd: u32, rs1: u32, rs2: u32) -> u32 {
+    r_type(0b00011 << 2, rs2, rs1, 0b010, rd, 0b0101111)
+}
+
+pub fn amoadd_w(rd: u32, rs1: u32, rs2: u32) -> u32 {
+    r_type(0, rs2, rs1, 0b010, rd, 0b0101111)
+}
+
+pub fn amoswap_w(rd: u32, rs1: u32, rs2: u32) -> u32 {
+    r_type(0b00001 << 2, rs2, rs1, 0b010, rd, 0b0101111)
+}
+
+/// C.LI: loads the 6-bit signed immediate `imm` into `rd`.
+pub fn c_li(rd: u32, imm: i32) -> u16 {
+    c_i_type(0b010, rd, imm)
+}
+
+/// C.ADDI: adds the 6-bit signed immediate `imm` to `rd`.
+pub fn c_addi(rd: u32, imm: i32) -> u16 {
+    c_i_type(0b000, rd, imm)
+}
+
+fn c_i_type(funct3: u32, rd: u32, imm: i32) -> u16 {
+    let imm = imm as u32;
+    ((funct3 << 13) | ((imm >> 5) & 1) << 12 | (rd << 7) | (imm & 0x1f) << 2 | 0b01) as u16
+}
+
+fn r_type(funct7: u32, rs2: u32, rs1: u32, funct3: u32, rd: u32, opcode: u32) -> u32 {
+    (funct7 << 25) | (rs2 << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | opcode
+}
+
+fn i_type(imm: u32, rs1: u32, funct3: u32, rd: u32, opcode: u32) -> u32 {
+    ((imm & 0xfff) << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | opcode
+}
+
+fn s_type(imm: u32, rs2: u32, rs1: u32, funct3: u32) -> u32 {
+    ((imm >> 5) & 0x7f) << 25
+        | (rs2 << 20)
+        | (rs1 << 15)
+        | (funct3 << 12)
+        | (imm & 0x1f) << 7
+        | 0b0100011
+}
+
+fn push_section_header(
+    elf: &mut Vec<u8>,
+    name: u32,
+    kind: u32,
+    flags: u32,
+    address: u32,
+    offset: usize,
+    size: usize,
+) {
+    for field in [
+        name,
+        kind,
+        flags,
+        address,
+        offset as u32,
+        size as u32,
+        0,
+        0,
+        1,
+        0,
+    ] {
+        push_u32(elf, field);
+    }
+}
+
+fn push_u16(elf: &mut Vec<u8>, value: u16) {
+    elf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn push_u32(elf: &mut Vec<u8>, value: u32) {
+    elf.extend_from_slice(&value.to_le_bytes());
+}
@@ -64,6 +64,13 @@ impl Tracer {
         }
 
         if let Some(row) = self.rows.try_borrow_mut().unwrap().last_mut() {
+            // Atomic memory operations load and then store to the same address. Keep the
+            // load: the stored value is a function of the loaded value and rs2.
+            if matches!(row.memory_state, Some(MemoryState::Read { .. }))
+                && matches!(memory_state, MemoryState::Write { .. })
+            {
+                return;
+            }
             row.memory_state = Some(memory_state);
         }
     }